@@ -1,3 +1,5 @@
+import {apiToken} from "./utils.js";
+
 export class Api {
   constructor(url) {
     this.url = url;
@@ -13,6 +15,16 @@ export class Api {
       path = encodePath(path);
     }
 
+    let token = apiToken();
+
+    if (!!token) {
+      data = Object.assign({}, data, {
+        headers: Object.assign({}, data.headers || {}, {
+          "Authorization": `Bearer ${token}`,
+        }),
+      });
+    }
+
     return fetch(`${this.url}/${path}`, data).then((r) => {
       if (!r.ok) {
         return r.text().then(text => {
@@ -74,6 +86,83 @@ export class Api {
     });
   }
 
+  /**
+   * Search the durable chat log.
+   *
+   * @param {object} filter the channel, user, and phrase to search for.
+   */
+  chatSearch(filter = {}) {
+    let queries = [];
+
+    if (!!filter.channel) {
+      queries.push(`channel=${encodeURIComponent(filter.channel)}`);
+    }
+
+    if (!!filter.user) {
+      queries.push(`user=${encodeURIComponent(filter.user)}`);
+    }
+
+    if (!!filter.q) {
+      queries.push(`q=${encodeURIComponent(filter.q)}`);
+    }
+
+    return this.fetch(`chat/search?${queries.join("&")}`);
+  }
+
+  /**
+   * Get the last messages by the given user from the durable chat log.
+   *
+   * @param {string} user user to get messages for.
+   */
+  chatUser(user) {
+    return this.fetch(["chat", "user", user]);
+  }
+
+  /**
+   * List all settings profiles.
+   */
+  profiles() {
+    return this.fetch("profiles");
+  }
+
+  /**
+   * Snapshot the current value of settings into a profile.
+   *
+   * @param {string} name name of the profile.
+   * @param {object} request the game and keys to snapshot.
+   */
+  profileSnapshot(name, request) {
+    return this.fetch(["profiles", name, "snapshot"], {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(request),
+    });
+  }
+
+  /**
+   * Activate the given settings profile.
+   *
+   * @param {string} name name of the profile to activate.
+   */
+  profileActivate(name) {
+    return this.fetch(["profiles", name, "activate"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * Delete the given settings profile.
+   *
+   * @param {string} name name of the profile to delete.
+   */
+  profileDelete(name) {
+    return this.fetch(["profiles", name], {
+      method: "DELETE",
+    });
+  }
+
   /**
    * Get the list of settings.
    */
@@ -106,6 +195,99 @@ export class Api {
     );
   }
 
+  /**
+   * List recorded changes to settings, newest first.
+   *
+   * @param {object} filter optional key to filter changes by.
+   */
+  settingsHistory(filter = {}) {
+    let query = "";
+
+    if (!!filter.key) {
+      query = `?key=${encodeURIComponent(filter.key)}`;
+    }
+
+    return this.fetch(`settings/history${query}`);
+  }
+
+  /**
+   * Revert the recorded settings change with the given id.
+   *
+   * @param {number} id id of the change to revert.
+   */
+  settingsRevert(id) {
+    return this.fetch(["settings", "history", String(id), "revert"], {
+      method: "POST",
+    });
+  }
+
+  /**
+   * List all scheduled settings changes.
+   */
+  schedule() {
+    return this.fetch("schedule");
+  }
+
+  /**
+   * Schedule a change to a setting.
+   *
+   * @param {object} request the key, value, when to apply it, and when to revert it.
+   */
+  scheduleCreate(request) {
+    return this.fetch("schedule", {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(request),
+    });
+  }
+
+  /**
+   * Cancel the scheduled change with the given id.
+   *
+   * @param {number} id id of the change to cancel.
+   */
+  scheduleCancel(id) {
+    return this.fetch(["schedule", String(id)], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List all API tokens.
+   */
+  tokens() {
+    return this.fetch("tokens");
+  }
+
+  /**
+   * Create a new API token.
+   *
+   * @param {string} name the name of the token.
+   * @param {array<string>} scopes the scopes the token grants access to.
+   */
+  tokensCreate(name, scopes) {
+    return this.fetch("tokens", {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({name, scopes}),
+    });
+  }
+
+  /**
+   * Delete the API token with the given name.
+   *
+   * @param {string} name the name of the token to delete.
+   */
+  tokensDelete(name) {
+    return this.fetch(["tokens", name], {
+      method: "DELETE",
+    });
+  }
+
   /**
    * Get all cache entries.
    */
@@ -181,6 +363,40 @@ export class Api {
     });
   }
 
+  /**
+   * Export a full backup of the bot.
+   *
+   * @param {object} options if secrets should be included, and the passphrase to encrypt them with.
+   */
+  exportBackup(options) {
+    return this.fetch("backup/export", {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({
+        secrets: !!options.secrets,
+        passphrase: options.secrets ? (options.passphrase || null) : null,
+      }),
+    });
+  }
+
+  /**
+   * Import a full backup of the bot.
+   *
+   * @param {object} archive the archive to import.
+   * @param {string} passphrase passphrase to decrypt secrets with.
+   */
+  importBackup(archive, passphrase) {
+    return this.fetch("backup/import", {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({archive, passphrase: passphrase || null}),
+    });
+  }
+
   /**
    * Get information on the current user.
    */
@@ -286,7 +502,9 @@ export class Api {
   }
 
   /**
-   * Get a list of all enabled grants.
+   * Get a list of all enabled grants, including temporary grants which
+   * haven't expired. Temporary grants have an `id`, and the time `remaining`
+   * until they expire.
    */
   authGrants() {
     return this.fetch(["auth", "grants"]);
@@ -314,6 +532,113 @@ export class Api {
     });
   }
 
+  /**
+   * Revoke a temporary grant.
+   *
+   * @param {number} id the id of the grant to revoke.
+   */
+  authDeleteTemporaryGrant(id) {
+    return this.fetch(["auth", "grants", String(id)], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * Create or update a custom role.
+   *
+   * @param {string} role the name of the role, like `@editor`.
+   * @param {object} data the documentation and inherited roles of the role.
+   */
+  authPutRole(role, data) {
+    return this.fetch(["auth", "roles", role], {
+      method: "PUT",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(data),
+    });
+  }
+
+  /**
+   * Delete a custom role.
+   *
+   * @param {string} role the name of the role to delete.
+   */
+  authDeleteRole(role) {
+    return this.fetch(["auth", "roles", role], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * Add a member to a custom role.
+   *
+   * @param {string} role the name of the role.
+   * @param {string} user the user to add.
+   */
+  authInsertMember(role, user) {
+    return this.fetch(["auth", "roles", role, "members", user], {
+      method: "PUT",
+    });
+  }
+
+  /**
+   * Remove a member from a custom role.
+   *
+   * @param {string} role the name of the role.
+   * @param {string} user the user to remove.
+   */
+  authDeleteMember(role, user) {
+    return this.fetch(["auth", "roles", role, "members", user], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List pending and active GTA V effects.
+   */
+  gtavQueue() {
+    return this.fetch(["gtav", "queue"]);
+  }
+
+  /**
+   * Veto a queued GTA V effect, refunding it.
+   *
+   * @param {number} id id of the effect to veto.
+   */
+  gtavVeto(id) {
+    return this.fetch(["gtav", "queue", id.toString()], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * Veto all queued GTA V effects, refunding them.
+   */
+  gtavClear() {
+    return this.fetch(["gtav", "queue"], {
+      method: "DELETE",
+    });
+  }
+
+  /**
+   * List recently created clips.
+   */
+  clips() {
+    return this.fetch(["clips"]);
+  }
+
+  /**
+   * Delete a recorded clip.
+   *
+   * @param {number} id id of the clip to delete.
+   */
+  deleteClip(id) {
+    return this.fetch(["clips", id.toString()], {
+      method: "DELETE",
+    });
+  }
+
   /**
    * Get all existing chat messages.
    */
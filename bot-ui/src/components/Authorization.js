@@ -4,6 +4,8 @@ import {Alert, Table, Button, InputGroup, Form, Modal} from "react-bootstrap";
 import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
 import * as ReactMarkdown from 'react-markdown';
 import { Loading, Error } from 'shared-ui/components';
+import CustomRoles from "./CustomRoles.js";
+import TemporaryGrants from "./TemporaryGrants.js";
 
 /**
  * Special role that everyone belongs to.
@@ -13,6 +15,44 @@ const STREAMER = "@streamer";
 const MODERATOR = "@moderator";
 const SUBSCRIBER = "@subscriber";
 
+/**
+ * Build a map from every role to all the roles it inherits, directly or
+ * indirectly.
+ *
+ * @param {array<object>} roles the roles to build the map for.
+ */
+function inheritedRoles(roles) {
+  let direct = {};
+
+  for (let role of roles) {
+    direct[role.role] = role.inherits || [];
+  }
+
+  let out = {};
+
+  for (let role of roles) {
+    let seen = {[role.role]: true};
+    let queue = direct[role.role].slice();
+    let inherited = [];
+
+    while (queue.length > 0) {
+      let next = queue.shift();
+
+      if (seen[next]) {
+        continue;
+      }
+
+      seen[next] = true;
+      inherited.push(next);
+      queue.push(...(direct[next] || []));
+    }
+
+    out[role.role] = inherited;
+  }
+
+  return out;
+}
+
 /**
  * Check if the given role is a risky role.
  *
@@ -87,14 +127,16 @@ export default class Authorization extends React.Component {
       let [roles, scopes, grants] = await Promise.all(requests);
       let allowsObject = {};
 
-      for (let [scope, role] of grants) {
-        allowsObject[`${scope}:${role}`] = true;
+      for (let grant of grants) {
+        if (grant.id === undefined) {
+          allowsObject[`${grant.scope}:${grant.principal}`] = true;
+        }
       }
 
       this.setState({
         loading: false,
         error: null,
-        data: {roles, scopes, grants: allowsObject},
+        data: {roles, scopes, grants: allowsObject, inherited: inheritedRoles(roles)},
       });
     } catch (e) {
       this.setState({
@@ -163,7 +205,7 @@ export default class Authorization extends React.Component {
   /**
    * Render authentication button.
    */
-  renderAuthButton(scope, role, grants) {
+  renderAuthButton(scope, role, grants, inherited) {
     let has_implicit = null;
     let title = null;
 
@@ -179,14 +221,17 @@ export default class Authorization extends React.Component {
       return null;
     }
 
+    let inherits = inherited[role.role] || [];
+
     switch (role.role) {
       case EVERYONE:
+        has_implicit = test_implicit(inherits) || false;
         break;
       case STREAMER:
-        has_implicit = test_implicit([EVERYONE, SUBSCRIBER]) || false;
+        has_implicit = test_implicit([EVERYONE, SUBSCRIBER, ...inherits]) || false;
         break;
       default:
-        has_implicit = test_implicit([EVERYONE]) || false;
+        has_implicit = test_implicit([EVERYONE, ...inherits]) || false;
         break;
     }
 
@@ -265,7 +310,7 @@ export default class Authorization extends React.Component {
             <ReactMarkdown source={scope.doc} />
           </div>
         </td>
-        {data.roles.map(role => this.renderAuthButton(scope, role, data.grants))}
+        {data.roles.map(role => this.renderAuthButton(scope, role, data.grants, data.inherited))}
       </tr>
     );
   }
@@ -396,6 +441,9 @@ export default class Authorization extends React.Component {
         {filter}
         {content}
         {modal}
+
+        <CustomRoles api={this.api} roles={this.state.data ? this.state.data.roles : []} onChange={() => this.list()} />
+        <TemporaryGrants api={this.api} />
       </div>
     );
   }
@@ -77,6 +77,13 @@ function filterUnique(messages) {
   return {seen, ids, messages: out};
 }
 
+/**
+ * Test if the message was relayed from the channel a deletion applies to.
+ */
+function sameChannel(m, data) {
+  return (m.channel || null) === (data.channel || null);
+}
+
 function searchLimit(search) {
   let update = search.get("limit");
 
@@ -282,7 +289,7 @@ export default class Chat extends React.Component {
       case "delete-by-user":
         this.setState(s => {
           let messages = s.messages.map(m => {
-            if (m.user.name !== data.name) {
+            if (m.user.name !== data.name || !sameChannel(m, data)) {
               return m;
             }
 
@@ -314,6 +321,10 @@ export default class Chat extends React.Component {
       case "delete-all":
         this.setState(s => {
           let messages = s.messages.map(m => {
+            if (!sameChannel(m, data)) {
+              return m;
+            }
+
             m = Object.assign({}, m);
             m.deleted = true;
             return m;
@@ -503,9 +514,16 @@ export default class Chat extends React.Component {
         badges = <div className="chat-badges">{badges}</div>;
       }
 
+      let channel = null;
+
+      if (m.channel) {
+        channel = <span className="chat-channel">[{m.channel}]</span>;
+      }
+
       return (
         <div className={`chat-message ${messageClasses}`} key={m.id}>
           <span className="overlay-hidden chat-timestamp">{timestamp}</span>
+          {channel}
           {badges}
           <span className="chat-name" style={nameStyle}>{name}:</span>
           <span className="chat-text">{text}</span>
@@ -0,0 +1,147 @@
+import React from "react";
+import {Button, Alert, Table, Form, InputGroup} from "react-bootstrap";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class ChatHistory extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      channel: "",
+      user: "",
+      q: "",
+      data: null,
+    };
+  }
+
+  /**
+   * Search the chat history.
+   */
+  async search(e) {
+    if (e) {
+      e.preventDefault();
+    }
+
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.chatSearch({
+        channel: this.state.channel,
+        user: this.state.user,
+        q: this.state.q,
+      });
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to search chat history: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Show the last messages by the given user.
+   *
+   * @param {string} user user to show messages for.
+   */
+  async lastByUser(user) {
+    this.setState({
+      loading: true,
+      user,
+      q: "",
+    });
+
+    try {
+      let data = await this.api.chatUser(user);
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request messages by user: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Messages!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>User</th>
+                <th className="table-fill">Message</th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(m => {
+                return (
+                  <tr key={m.id}>
+                    <td className="chat-history-user">
+                      <a className="chat-history-name" onClick={() => this.lastByUser(m.user)}>{m.display_name}</a>
+                      <span className="chat-history-added-at">
+                        <span className="chat-history-channel">{m.channel}</span>
+                        <span className="chat-history-datetime datetime">{m.added_at}</span>
+                      </span>
+                    </td>
+                    <td className={m.deleted ? "chat-history-deleted" : ""}><code>{m.text}</code></td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Chat History</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{key: ["chat-log/enabled", "chat-log/persist"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      <Form className="mb-3" onSubmit={e => this.search(e)}>
+        <InputGroup>
+          <Form.Control placeholder="Channel" value={this.state.channel} onChange={e => this.setState({channel: e.target.value})} />
+          <Form.Control placeholder="User" value={this.state.user} onChange={e => this.setState({user: e.target.value})} />
+          <Form.Control placeholder="Phrase" value={this.state.q} onChange={e => this.setState({q: e.target.value})} />
+          <InputGroup.Append>
+            <Button type="submit" variant="primary">Search</Button>
+          </InputGroup.Append>
+        </InputGroup>
+      </Form>
+
+      {content}
+    </>;
+  }
+}
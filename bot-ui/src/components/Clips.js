@@ -0,0 +1,130 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import ConfigurationPrompt from "./ConfigurationPrompt";
+import {Loading, Error} from 'shared-ui/components';
+
+const CLIPS_URL = "https://clips.twitch.tv";
+
+export default class Clips extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      configLoading: false,
+      error: null,
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of clips.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.clips();
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request clips: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Delete the given clip.
+   *
+   * @param {number} id clip id to delete
+   */
+  async delete(id) {
+    try {
+      await this.api.deleteClip(id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to delete clip: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Clips!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>User</th>
+                <th className="table-fill">Title</th>
+                <th>Game</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map((c, id) => {
+                return (
+                  <tr key={id}>
+                    <td className="clip-user">
+                      <a className="clip-name" href={`https://twitch.tv/${c.user}`}>@{c.user}</a>
+                      <span className="clip-created-at">
+                        <span className="clip-at">at</span>
+                        <span className="clip-datetime datetime">{c.created_at}</span>
+                      </span>
+                    </td>
+                    <td>
+                      <a href={`${CLIPS_URL}/${c.clip_id}`}>{c.title || <em>Untitled</em>}</a>
+                    </td>
+                    <td>{c.game || <em>None</em>}</td>
+                    <td>
+                      <Button size="sm" variant="danger" className="action" onClick={() => this.delete(c.id)}>
+                        <FontAwesomeIcon icon="trash" />
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Clips</h1>
+      <Loading isLoading={this.state.loading || this.state.configLoading} />
+      <Error error={this.state.error} />
+      <ConfigurationPrompt api={this.api} filter={{prefix: ["clip"]}}
+        onLoading={configLoading => this.setState({configLoading, error: null})}
+        onError={error => this.setState({configLoading: false, error})}
+      />
+
+      {content}
+    </>;
+  }
+}
@@ -0,0 +1,156 @@
+import React from "react";
+import {Button, Alert, Table, Form, InputGroup} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import {Error} from 'shared-ui/components';
+
+/**
+ * Split a list of roles separated by commas or spaces.
+ *
+ * @param {string} input the input to split.
+ */
+function splitRoles(input) {
+  return input.split(/[\s,]+/).map(r => r.trim()).filter(r => r.length > 0);
+}
+
+export default class CustomRoles extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      error: null,
+      role: "",
+      inherits: "",
+      members: {},
+    };
+  }
+
+  /**
+   * Run the given request, then notify the parent that roles changed.
+   */
+  async run(what, request) {
+    try {
+      await request();
+      this.setState({error: null});
+      await this.props.onChange();
+    } catch(e) {
+      this.setState({error: `failed to ${what}: ${e}`});
+    }
+  }
+
+  /**
+   * Create a new custom role.
+   */
+  async create(e) {
+    e.preventDefault();
+
+    let role = this.state.role.startsWith("@") ? this.state.role : `@${this.state.role}`;
+    let inherits = splitRoles(this.state.inherits);
+
+    await this.run("create role", async () => {
+      await this.api.authPutRole(role, {inherits});
+      this.setState({role: "", inherits: ""});
+    });
+
+    return false;
+  }
+
+  /**
+   * Add the member currently being edited to the given role.
+   */
+  async addMember(e, role) {
+    e.preventDefault();
+
+    let user = this.state.members[role] || "";
+
+    await this.run("add member", async () => {
+      await this.api.authInsertMember(role, user);
+      this.setState({members: Object.assign({}, this.state.members, {[role]: ""})});
+    });
+
+    return false;
+  }
+
+  render() {
+    let roles = this.props.roles.filter(r => r.custom);
+    let content = null;
+
+    if (roles.length === 0) {
+      content = (
+        <Alert variant="info">
+          No Custom Roles!
+        </Alert>
+      );
+    } else {
+      content = (
+        <Table responsive="sm">
+          <thead>
+            <tr>
+              <th>Role</th>
+              <th>Inherits</th>
+              <th className="table-fill">Members</th>
+              <th></th>
+            </tr>
+          </thead>
+          <tbody>
+            {roles.map(r => {
+              let onMemberChange = e => {
+                this.setState({members: Object.assign({}, this.state.members, {[r.role]: e.target.value})});
+              };
+
+              return (
+                <tr key={r.role}>
+                  <td className="custom-roles-name">{r.role}</td>
+                  <td>{r.inherits.length > 0 ? r.inherits.join(", ") : <em>nothing</em>}</td>
+                  <td>
+                    {r.members.map(m => {
+                      return (
+                        <span className="custom-roles-member" key={m}>
+                          {m}
+                          <a title={`Remove ${m} from ${r.role}`} onClick={() => this.run("remove member", () => this.api.authDeleteMember(r.role, m))}>
+                            <FontAwesomeIcon icon="trash" />
+                          </a>
+                        </span>
+                      );
+                    })}
+
+                    <Form onSubmit={e => this.addMember(e, r.role)}>
+                      <InputGroup size="sm">
+                        <Form.Control placeholder="user" value={this.state.members[r.role] || ""} onChange={onMemberChange} />
+                        <InputGroup.Append>
+                          <Button type="submit" variant="primary" disabled={!this.state.members[r.role]}>Add</Button>
+                        </InputGroup.Append>
+                      </InputGroup>
+                    </Form>
+                  </td>
+                  <td>
+                    <Button size="sm" variant="danger" onClick={() => this.run("delete role", () => this.api.authDeleteRole(r.role))}>
+                      Delete
+                    </Button>
+                  </td>
+                </tr>
+              );
+            })}
+          </tbody>
+        </Table>
+      );
+    }
+
+    return <>
+      <h2 className="mt-4">Custom Roles</h2>
+      <Error error={this.state.error} />
+
+      <Form className="mb-3" onSubmit={e => this.create(e)}>
+        <InputGroup>
+          <Form.Control placeholder="@editor" value={this.state.role} onChange={e => this.setState({role: e.target.value})} />
+          <Form.Control placeholder="Inherits, like @vip" value={this.state.inherits} onChange={e => this.setState({inherits: e.target.value})} />
+          <InputGroup.Append>
+            <Button type="submit" variant="primary" disabled={!this.state.role}>Create</Button>
+          </InputGroup.Append>
+        </InputGroup>
+      </Form>
+
+      {content}
+    </>;
+  }
+}
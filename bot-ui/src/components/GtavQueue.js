@@ -0,0 +1,151 @@
+import React from "react";
+import {Button, Alert, Table, ButtonGroup} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+/**
+ * How frequently the queue is refreshed in milliseconds.
+ */
+const REFRESH_INTERVAL = 2000;
+
+export default class GtavQueue extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      data: null,
+    };
+
+    this.interval = null;
+  }
+
+  async componentDidMount() {
+    this.setState({loading: true});
+    await this.list();
+    this.setState({loading: false});
+
+    this.interval = setInterval(() => this.list(), REFRESH_INTERVAL);
+  }
+
+  componentWillUnmount() {
+    if (this.interval !== null) {
+      clearInterval(this.interval);
+    }
+  }
+
+  /**
+   * List pending and active effects.
+   */
+  async list() {
+    try {
+      let data = await this.api.gtavQueue();
+      this.setState({error: null, data});
+    } catch(e) {
+      this.setState({error: `failed to list effects: ${e}`, data: null});
+    }
+  }
+
+  /**
+   * Veto the given effect, or all queued effects if `id` is `null`.
+   *
+   * @param {number | null} id id of the effect to veto.
+   */
+  async veto(id) {
+    try {
+      if (id === null) {
+        await this.api.gtavClear();
+      } else {
+        await this.api.gtavVeto(id);
+      }
+
+      await this.list();
+    } catch(e) {
+      this.setState({error: `failed to veto effect: ${e}`});
+    }
+  }
+
+  renderTable(effects, empty, veto) {
+    if (effects.length === 0) {
+      return <Alert variant="info">{empty}</Alert>;
+    }
+
+    return (
+      <Table responsive="sm">
+        <thead>
+          <tr>
+            <th>User</th>
+            <th className="table-fill">Effect</th>
+            <th>Cost</th>
+            <th>Duration</th>
+            {veto ? <th></th> : null}
+          </tr>
+        </thead>
+        <tbody>
+          {effects.map(e => {
+            let duration = null;
+
+            if (e.remaining !== null) {
+              duration = `${e.remaining}s left`;
+            } else if (e.duration !== null) {
+              duration = `${e.duration}s`;
+            }
+
+            return (
+              <tr key={e.id}>
+                <td>{e.user}</td>
+                <td title={e.description}>{e.effect}</td>
+                <td>{e.cost}</td>
+                <td>{duration}</td>
+                {veto ? (
+                  <td>
+                    <Button size="sm" variant="danger" onClick={() => this.veto(e.id)}>
+                      Veto
+                    </Button>
+                  </td>
+                ) : null}
+              </tr>
+            );
+          })}
+        </tbody>
+      </Table>
+    );
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      content = <>
+        <h2>
+          Queued
+          <ButtonGroup className="gtav-queue-actions">
+            <Button size="sm" variant="danger" disabled={this.state.data.pending.length === 0} onClick={() => this.veto(null)}>
+              Veto All
+            </Button>
+          </ButtonGroup>
+        </h2>
+
+        {this.renderTable(this.state.data.pending, "No queued effects!", true)}
+
+        <h2>Active</h2>
+
+        {this.renderTable(this.state.data.active, "No active effects!", false)}
+      </>;
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>ChaosMod Queue</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      <p>
+        Effects are only queued when <code>gtav/queue</code> is enabled.
+        Vetoed effects are refunded to the viewer who bought them.
+      </p>
+
+      {content}
+    </>;
+  }
+}
@@ -17,6 +17,9 @@ export default class ImportExport extends React.Component {
       <Row>
         <Col sm="2">
           <Nav className="flex-column" variant="pills">
+            <Nav.Link as={Link} active={path === "/import-export/backup"} to="/import-export/backup">
+              Backup
+            </Nav.Link>
             <Nav.Link as={Link} active={path === "/import-export/phantombot"} to="/import-export/phantombot">
               PhantomBot
             </Nav.Link>
@@ -27,6 +30,7 @@ export default class ImportExport extends React.Component {
         </Col>
         <Col>
           <Route path="/import-export" exact render={props => <Index {...props} />} />
+          <Route path="/import-export/backup" render={props => <Backup api={this.props.api} {...props} />} />
           <Route path="/import-export/phantombot" render={props => <PhantomBot api={this.props.api} {...props} />} />
           <Route path="/import-export/drangrybot" render={props => <DrangryBot api={this.props.api} {...props} />} />
         </Col>
@@ -53,6 +57,141 @@ class Index extends React.Component {
   }
 }
 
+class Backup extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      success: null,
+      error: null,
+      secrets: false,
+      exportPassphrase: "",
+      importPassphrase: "",
+      file: null,
+    };
+  }
+
+  /**
+   * Export a full backup of the bot to a file.
+   */
+  async export(e) {
+    e.preventDefault();
+
+    if (this.state.secrets && !this.state.exportPassphrase) {
+      this.setState({error: "a passphrase is required to export secrets"});
+      return false;
+    }
+
+    this.setState({loading: true, success: null, error: null});
+
+    try {
+      let archive = await this.api.exportBackup({
+        secrets: this.state.secrets,
+        passphrase: this.state.exportPassphrase,
+      });
+
+      utils.download("application/json", JSON.stringify(archive, null, 2), "oxidize-backup.json");
+      this.setState({loading: false});
+    } catch(e) {
+      this.setState({loading: false, error: `failed to export backup: ${e}`});
+    }
+
+    return false;
+  }
+
+  /**
+   * Import a full backup of the bot from a file.
+   */
+  async import(e) {
+    e.preventDefault();
+
+    if (!this.state.file) {
+      return false;
+    }
+
+    this.setState({loading: true, success: null, error: null});
+
+    try {
+      let archive = JSON.parse(await this.state.file.text());
+      await this.api.importBackup(archive, this.state.importPassphrase);
+
+      this.setState({
+        loading: false,
+        success: "Imported backup. Restart the bot for grants, bad words, and the song queue to take effect.",
+      });
+    } catch(e) {
+      this.setState({loading: false, error: `failed to import backup: ${e}`});
+    }
+
+    return false;
+  }
+
+  render() {
+    let error = null;
+
+    if (this.state.error) {
+      error = <Alert variant="danger">{this.state.error}</Alert>;
+    }
+
+    let success = null;
+
+    if (this.state.success) {
+      success = <Alert variant="info">{this.state.success}</Alert>;
+    }
+
+    return (
+      <>
+        <div className="mb-3">
+          <h2>Backup</h2>
+
+          <p>
+            Export or import everything the bot is configured with, like settings, commands, aliases, promotions, themes, bad words, after streams, grants, the song queue, and currency balances.
+          </p>
+        </div>
+
+        <Loading isLoading={this.state.loading} />
+        {error}
+        {success}
+
+        <Row>
+          <Col>
+            <h4>Export</h4>
+
+            <Form onSubmit={e => this.export(e)}>
+              <Form.Group>
+                <Form.Check type="checkbox" label="Include secrets" checked={this.state.secrets} onChange={e => this.setState({secrets: e.target.checked})} />
+              </Form.Group>
+              <Form.Group>
+                <Form.Label>Passphrase</Form.Label>
+                <Form.Control type="password" value={this.state.exportPassphrase} disabled={!this.state.secrets} onChange={e => this.setState({exportPassphrase: e.target.value})} />
+                <Form.Text>Secrets are encrypted with this passphrase. Exporting secrets requires an API token with the <code>web/admin</code> scope.</Form.Text>
+              </Form.Group>
+              <Button type="submit" disabled={this.state.loading}>Export to File</Button>
+            </Form>
+          </Col>
+
+          <Col>
+            <h4>Import</h4>
+
+            <Form onSubmit={e => this.import(e)}>
+              <Form.Group>
+                <Form.Control type="file" accept=".json" onChange={e => this.setState({file: e.target.files[0] || null})} />
+              </Form.Group>
+              <Form.Group>
+                <Form.Label>Passphrase</Form.Label>
+                <Form.Control type="password" value={this.state.importPassphrase} onChange={e => this.setState({importPassphrase: e.target.value})} />
+              </Form.Group>
+              <Button type="submit" disabled={this.state.loading || !this.state.file}>Import from File</Button>
+            </Form>
+          </Col>
+        </Row>
+      </>
+    );
+  }
+}
+
 class PhantomBot extends React.Component {
   constructor(props) {
     super(props);
@@ -123,6 +123,94 @@ class CurrentSong extends React.Component {
   }
 }
 
+class Alert extends React.Component {
+  constructor(props) {
+    super(props);
+  }
+
+  render() {
+    let image = null;
+
+    if (this.props.alert.image) {
+      image = <img className="alert-image" src={this.props.alert.image} />;
+    }
+
+    let message = null;
+
+    if (this.props.alert.message) {
+      message = <div className="alert-message">{this.props.alert.message}</div>;
+    }
+
+    return (
+      <div id="alert" className={`alert-${this.props.alert.kind}`}>
+        {image}
+        <div className="alert-text">{this.props.alert.text}</div>
+        {message}
+      </div>
+    );
+  }
+}
+
+class GtavEffects extends React.Component {
+  constructor(props) {
+    super(props);
+  }
+
+  render() {
+    let active = this.props.active.map(e => {
+      return (
+        <div className="gtav-effect gtav-effect-active" key={e.id}>
+          <span className="gtav-effect-name">{e.effect}</span>
+          <span className="gtav-effect-user">{e.user}</span>
+          <span className="gtav-effect-time">{e.remaining}s</span>
+        </div>
+      );
+    });
+
+    let pending = this.props.pending.map(e => {
+      return (
+        <div className="gtav-effect gtav-effect-pending" key={e.id}>
+          <span className="gtav-effect-name">{e.effect}</span>
+          <span className="gtav-effect-user">{e.user}</span>
+          {e.duration !== null ? <span className="gtav-effect-time">{e.duration}s</span> : null}
+        </div>
+      );
+    });
+
+    return (
+      <div id="gtav-effects">
+        {active}
+        {pending}
+      </div>
+    );
+  }
+}
+
+class Countdowns extends React.Component {
+  constructor(props) {
+    super(props);
+  }
+
+  render() {
+    let timers = this.props.timers.map(t => {
+      let classes = `countdown countdown-${t.kind}`;
+
+      if (!t.running) {
+        classes += " countdown-stopped";
+      }
+
+      return (
+        <div className={classes} key={t.name}>
+          <span className="countdown-name">{t.name}</span>
+          <span className="countdown-text">{t.text}</span>
+        </div>
+      );
+    });
+
+    return <div id="countdowns">{timers}</div>;
+  }
+}
+
 export default class Overlay extends React.Component {
   constructor(props) {
     super(props);
@@ -134,7 +222,40 @@ export default class Overlay extends React.Component {
       albumArt: null,
       elapsed: 0,
       duration: 0,
+      alert: null,
+      gtav: null,
+      countdowns: [],
     };
+
+    this.alertTimeout = null;
+  }
+
+  componentWillUnmount() {
+    if (this.alertTimeout !== null) {
+      clearTimeout(this.alertTimeout);
+    }
+  }
+
+  /**
+   * Show the given alert for its duration.
+   */
+  showAlert(alert) {
+    if (this.alertTimeout !== null) {
+      clearTimeout(this.alertTimeout);
+    }
+
+    if (alert.sound) {
+      new Audio(alert.sound).play().catch(e => {
+        console.log("failed to play alert sound", e);
+      });
+    }
+
+    this.setState({alert});
+
+    this.alertTimeout = setTimeout(() => {
+      this.alertTimeout = null;
+      this.setState({alert: null});
+    }, alert.duration * 1000);
   }
 
   handleData(d) {
@@ -192,11 +313,52 @@ export default class Overlay extends React.Component {
           duration: data.duration,
         });
 
+        break;
+      case "alert":
+        this.showAlert(data);
+        break;
+      case "speedrun/record":
+        this.showAlert({
+          kind: `speedrun-${data.kind}`,
+          text: data.text,
+          duration: data.duration,
+        });
+        break;
+      case "water/reminder":
+        this.showAlert({
+          kind: `reminder-${data.kind}`,
+          text: data.text,
+          duration: data.duration,
+        });
+        break;
+      case "countdown/timers":
+        this.setState({countdowns: data.timers});
+        break;
+      case "gtav/queue":
+        this.setState({gtav: {pending: data.pending, active: data.active}});
         break;
     }
   }
 
   render() {
+    let alert = null;
+
+    if (this.state.alert !== null) {
+      alert = <Alert alert={this.state.alert} />;
+    }
+
+    let gtav = null;
+
+    if (this.state.gtav !== null && (this.state.gtav.pending.length > 0 || this.state.gtav.active.length > 0)) {
+      gtav = <GtavEffects pending={this.state.gtav.pending} active={this.state.gtav.active} />;
+    }
+
+    let countdowns = null;
+
+    if (this.state.countdowns.length > 0) {
+      countdowns = <Countdowns timers={this.state.countdowns} />;
+    }
+
     return (
       <div id="overlay">
         <Websocket url={websocketUrl("ws/overlay")} onMessage={this.handleData.bind(this)} />
@@ -209,6 +371,10 @@ export default class Overlay extends React.Component {
           elapsed={this.state.elapsed}
           duration={this.state.duration}
         />
+
+        {alert}
+        {gtav}
+        {countdowns}
       </div>
     );
   }
@@ -0,0 +1,176 @@
+import React from "react";
+import {Button, Alert, Table, Form} from "react-bootstrap";
+import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
+import {Loading, Error} from 'shared-ui/components';
+
+export default class Profiles extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      data: null,
+      name: "",
+      game: "",
+      keys: "",
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * Refresh the list of profiles.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.profiles();
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to request profiles: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Snapshot the current value of the given keys into a profile.
+   */
+  async snapshot(e) {
+    e.preventDefault();
+
+    let keys = this.state.keys.split(",").map(k => k.trim()).filter(k => k !== "");
+    let game = this.state.game.trim() || null;
+
+    try {
+      await this.api.profileSnapshot(this.state.name, {game, keys});
+      this.setState({name: "", game: "", keys: ""});
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to snapshot profile: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Activate the given profile.
+   *
+   * @param {string} name name of the profile to activate.
+   */
+  async activate(name) {
+    try {
+      await this.api.profileActivate(name);
+    } catch(e) {
+      this.setState({
+        error: `failed to activate profile: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Delete the given profile.
+   *
+   * @param {string} name name of the profile to delete.
+   */
+  async delete(name) {
+    try {
+      await this.api.profileDelete(name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        error: `failed to delete profile: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Profiles!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>Name</th>
+                <th>Game</th>
+                <th className="table-fill">Settings</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(p => {
+                return (
+                  <tr key={p.name}>
+                    <td>{p.name}</td>
+                    <td>{p.game}</td>
+                    <td>
+                      {Object.keys(p.settings).map(key => {
+                        return <div key={key}><code>{key} = {JSON.stringify(p.settings[key])}</code></div>;
+                      })}
+                    </td>
+                    <td className="profiles-actions">
+                      <Button size="sm" variant="primary" className="action" onClick={() => this.activate(p.name)}>
+                        Activate
+                      </Button>
+                      <Button size="sm" variant="danger" className="action" onClick={() => this.delete(p.name)}>
+                        <FontAwesomeIcon icon="trash" />
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Settings Profiles</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      {content}
+
+      <h4>Snapshot Current Settings</h4>
+
+      <Form onSubmit={e => this.snapshot(e)}>
+        <Form.Group>
+          <Form.Label>Name</Form.Label>
+          <Form.Control value={this.state.name} onChange={e => this.setState({name: e.target.value})} />
+        </Form.Group>
+        <Form.Group>
+          <Form.Label>Game</Form.Label>
+          <Form.Control value={this.state.game} placeholder="Activate automatically when playing this game" onChange={e => this.setState({game: e.target.value})} />
+        </Form.Group>
+        <Form.Group>
+          <Form.Label>Settings</Form.Label>
+          <Form.Control value={this.state.keys} placeholder="song/enabled, gtav/enabled" onChange={e => this.setState({keys: e.target.value})} />
+        </Form.Group>
+        <Button type="submit" variant="primary" disabled={this.state.name === ""}>Snapshot</Button>
+      </Form>
+    </>;
+  }
+}
@@ -0,0 +1,231 @@
+import React from "react";
+import {Button, Alert, Table, Form, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+/**
+ * Render a settings value, or a placeholder if it's cleared.
+ */
+function renderValue(value) {
+  if (value === null || value === undefined) {
+    return <em>cleared</em>;
+  }
+
+  return <code>{JSON.stringify(value)}</code>;
+}
+
+export default class Schedule extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      data: null,
+      key: "",
+      value: "",
+      runAt: "",
+      delay: "",
+      revertAfter: "",
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * List all scheduled changes.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.schedule();
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to list scheduled changes: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Schedule a new change.
+   */
+  async create(e) {
+    e.preventDefault();
+
+    let value = null;
+
+    try {
+      value = JSON.parse(this.state.value);
+    } catch(e) {
+      this.setState({error: `value is not valid JSON: ${e}`});
+      return false;
+    }
+
+    let request = {
+      key: this.state.key,
+      value,
+    };
+
+    if (!!this.state.runAt) {
+      request.run_at = new Date(this.state.runAt).toISOString();
+    }
+
+    if (!!this.state.delay) {
+      request.delay = this.state.delay;
+    }
+
+    if (!!this.state.revertAfter) {
+      request.revert_after = this.state.revertAfter;
+    }
+
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      await this.api.scheduleCreate(request);
+
+      this.setState({
+        key: "",
+        value: "",
+        runAt: "",
+        delay: "",
+        revertAfter: "",
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to schedule change: ${e}`,
+      });
+    }
+
+    return false;
+  }
+
+  /**
+   * Cancel the given scheduled change.
+   *
+   * @param {number} id id of the change to cancel.
+   */
+  async cancel(id) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      await this.api.scheduleCancel(id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to cancel scheduled change: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            Nothing Scheduled!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>When</th>
+                <th>Key</th>
+                <th>Value</th>
+                <th className="table-fill">Revert After</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(s => {
+                return (
+                  <tr key={s.id}>
+                    <td className="schedule-datetime datetime">{s.run_at}</td>
+                    <td>{s.key}{s.is_revert ? <span className="schedule-revert"> (revert)</span> : null}</td>
+                    <td>{renderValue(s.value)}</td>
+                    <td>{s.revert_after || <em>never</em>}</td>
+                    <td>
+                      <Button size="sm" variant="danger" disabled={this.state.loading} onClick={() => this.cancel(s.id)}>
+                        Cancel
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Scheduled Settings</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      <Form className="mb-3" onSubmit={e => this.create(e)}>
+        <Row>
+          <Col>
+            <Form.Group>
+              <Form.Label>Key</Form.Label>
+              <Form.Control placeholder="song/enabled" value={this.state.key} onChange={e => this.setState({key: e.target.value})} />
+            </Form.Group>
+          </Col>
+          <Col>
+            <Form.Group>
+              <Form.Label>Value (JSON)</Form.Label>
+              <Form.Control placeholder="false" value={this.state.value} onChange={e => this.setState({value: e.target.value})} />
+            </Form.Group>
+          </Col>
+        </Row>
+        <Row>
+          <Col>
+            <Form.Group>
+              <Form.Label>At</Form.Label>
+              <Form.Control type="datetime-local" value={this.state.runAt} disabled={!!this.state.delay} onChange={e => this.setState({runAt: e.target.value})} />
+            </Form.Group>
+          </Col>
+          <Col>
+            <Form.Group>
+              <Form.Label>Or In</Form.Label>
+              <Form.Control placeholder="10m" value={this.state.delay} disabled={!!this.state.runAt} onChange={e => this.setState({delay: e.target.value})} />
+            </Form.Group>
+          </Col>
+          <Col>
+            <Form.Group>
+              <Form.Label>Revert After</Form.Label>
+              <Form.Control placeholder="30m" value={this.state.revertAfter} onChange={e => this.setState({revertAfter: e.target.value})} />
+            </Form.Group>
+          </Col>
+        </Row>
+        <Button type="submit" variant="primary" disabled={this.state.loading || !this.state.key}>Schedule</Button>
+      </Form>
+
+      {content}
+    </>;
+  }
+}
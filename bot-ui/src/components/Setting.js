@@ -1,5 +1,5 @@
 import React from "react";
-import {Form, Button, ButtonGroup, Row, Col, InputGroup} from "react-bootstrap";
+import {Form, Button, ButtonGroup, Row, Col, InputGroup, Badge} from "react-bootstrap";
 import {FontAwesomeIcon} from "@fortawesome/react-fontawesome";
 import * as ReactMarkdown from 'react-markdown';
 
@@ -56,10 +56,13 @@ export default class Setting extends React.Component {
     let keyOverride = this.props.keyOverride;
 
     let isSecretShown = this.state.secretShown;
+    let isReadOnly = !!setting.readOnly;
 
     // onChange handler used for things which support immediate editing.
     let renderOnChange = value => {
-      this.edit(setting.key, setting.control, value);
+      if (!isReadOnly) {
+        this.edit(setting.key, setting.control, value);
+      }
     };
 
     let buttons = [];
@@ -115,7 +118,7 @@ export default class Setting extends React.Component {
       );
     }
 
-    if (setting.control.optional && !isSecretShown) {
+    if (setting.control.optional && !isSecretShown && !isReadOnly) {
       let del = () => {
         this.setState({
           delete: true,
@@ -131,7 +134,7 @@ export default class Setting extends React.Component {
       }
     }
 
-    if (setting.control.hasEditControl()) {
+    if (setting.control.hasEditControl() && !isReadOnly) {
       if (!isSecretShown) {
         let edit = () => {
           let value = setting.value;
@@ -253,7 +256,10 @@ export default class Setting extends React.Component {
         <td>
           <Row>
             <Col lg="4" className="settings-key mb-1">
-              <div className="settings-key-name mb-1">{key}</div>
+              <div className="settings-key-name mb-1">
+                {key}
+                {isReadOnly ? <Badge className="ml-1" variant="secondary" title="Provided by the configuration file, and can't be edited here">config file</Badge> : null}
+              </div>
               {doc}
             </Col>
 
@@ -82,6 +82,7 @@ export default class Settings extends React.Component {
           key: d.key,
           control,
           value,
+          readOnly: d.read_only,
           ...d.schema,
         }
       });
@@ -0,0 +1,162 @@
+import React from "react";
+import {Button, Alert, Table, Form, InputGroup} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+
+/**
+ * Render a settings value, or a placeholder if it isn't set.
+ */
+function renderValue(value) {
+  if (value === null || value === undefined) {
+    return <em>not set</em>;
+  }
+
+  return <code>{JSON.stringify(value)}</code>;
+}
+
+/**
+ * Render who performed a change.
+ */
+function renderActor(actor) {
+  switch (actor.type) {
+    case "chat":
+      return <>{actor.name} <span className="settings-history-actor">(chat)</span></>;
+    case "web":
+      return <span className="settings-history-actor">web</span>;
+    default:
+      return <span className="settings-history-actor">bot</span>;
+  }
+}
+
+export default class SettingsHistory extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      key: "",
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * List changes, optionally filtered by key.
+   */
+  async list(e) {
+    if (e) {
+      e.preventDefault();
+    }
+
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.settingsHistory({
+        key: this.state.key,
+      });
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to list settings history: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Revert the given change.
+   *
+   * @param {object} change the change to revert.
+   */
+  async revert(change) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      await this.api.settingsRevert(change.id);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to revert change: ${e}`,
+      });
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Changes!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>When</th>
+                <th>Who</th>
+                <th>Key</th>
+                <th>Old</th>
+                <th className="table-fill">New</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(c => {
+                return (
+                  <tr key={c.id}>
+                    <td className="settings-history-datetime datetime">{c.changed_at}</td>
+                    <td>{renderActor(c.actor)}</td>
+                    <td><a className="settings-history-key" onClick={() => this.setState({key: c.key}, () => this.list())}>{c.key}</a></td>
+                    <td>{renderValue(c.old_value)}</td>
+                    <td>{renderValue(c.new_value)}</td>
+                    <td>
+                      <Button size="sm" variant="danger" disabled={this.state.loading} onClick={() => this.revert(c)} title="Restore the value from before this change">
+                        Revert
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>Settings History</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      <Form className="mb-3" onSubmit={e => this.list(e)}>
+        <InputGroup>
+          <Form.Control placeholder="Key" value={this.state.key} onChange={e => this.setState({key: e.target.value})} />
+          <InputGroup.Append>
+            <Button type="submit" variant="primary">Filter</Button>
+          </InputGroup.Append>
+        </InputGroup>
+      </Form>
+
+      {content}
+    </>;
+  }
+}
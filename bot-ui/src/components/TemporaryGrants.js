@@ -0,0 +1,95 @@
+import React from "react";
+import {Button, Alert, Table} from "react-bootstrap";
+import {Error} from 'shared-ui/components';
+
+export default class TemporaryGrants extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      error: null,
+      data: null,
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * List all temporary grants.
+   */
+  async list() {
+    try {
+      let grants = await this.api.authGrants();
+      let data = grants.filter(g => g.id !== undefined);
+      this.setState({error: null, data});
+    } catch(e) {
+      this.setState({error: `failed to list temporary grants: ${e}`, data: null});
+    }
+  }
+
+  /**
+   * Revoke the given temporary grant.
+   *
+   * @param {number} id id of the grant to revoke.
+   */
+  async revoke(id) {
+    try {
+      await this.api.authDeleteTemporaryGrant(id);
+      await this.list();
+    } catch(e) {
+      this.setState({error: `failed to revoke temporary grant: ${e}`});
+    }
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No Temporary Grants!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>Scope</th>
+                <th>Granted To</th>
+                <th className="table-fill">Remaining</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(g => {
+                return (
+                  <tr key={g.id}>
+                    <td>{g.scope}</td>
+                    <td>{g.principal}</td>
+                    <td title={g.expires_at}>{g.remaining}</td>
+                    <td>
+                      <Button size="sm" variant="danger" onClick={() => this.revoke(g.id)}>
+                        Revoke
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    return <>
+      <h2 className="mt-4">Temporary Grants</h2>
+      <Error error={this.state.error} />
+      {content}
+    </>;
+  }
+}
@@ -0,0 +1,256 @@
+import React from "react";
+import {Button, Alert, Table, Form, Row, Col} from "react-bootstrap";
+import {Loading, Error} from 'shared-ui/components';
+import {apiToken, setApiToken} from "../utils.js";
+
+/**
+ * Scopes which can be granted to a token.
+ */
+const SCOPES = [
+  {scope: "web/read", title: "Read", help: "Read information and connect to websockets, like the overlay."},
+  {scope: "web/command", title: "Command", help: "Run commands through the web chat."},
+  {scope: "web/admin", title: "Admin", help: "Full access, including settings, backups and tokens."},
+  {scope: "web/after-streams", title: "After Streams", help: "Manage after stream notes."},
+  {scope: "web/alerts", title: "Alerts", help: "Send test alerts to overlays."},
+  {scope: "web/clips", title: "Clips", help: "Manage clips."},
+  {scope: "web/commands", title: "Commands", help: "Manage custom commands, aliases, promotions and themes."},
+  {scope: "web/currency", title: "Currency", help: "Import stream currency balances."},
+  {scope: "web/gtav", title: "GTA V", help: "Manage queued GTA V commands."},
+  {scope: "web/profiles", title: "Profiles", help: "Manage and activate settings profiles."},
+  {scope: "web/song", title: "Song", help: "Control the song player, like switching the audio device."},
+];
+
+export default class Tokens extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = this.props.api;
+
+    this.state = {
+      loading: false,
+      error: null,
+      data: null,
+      name: "",
+      scopes: ["web/read"],
+      created: null,
+      stored: apiToken(),
+    };
+  }
+
+  async componentDidMount() {
+    await this.list();
+  }
+
+  /**
+   * List all tokens.
+   */
+  async list() {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let data = await this.api.tokens();
+
+      this.setState({
+        loading: false,
+        error: null,
+        data,
+      });
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to list tokens: ${e}`,
+        data: null,
+      });
+    }
+  }
+
+  /**
+   * Create a new token.
+   */
+  async create(e) {
+    e.preventDefault();
+
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      let {token} = await this.api.tokensCreate(this.state.name, this.state.scopes);
+
+      this.setState({
+        name: "",
+        scopes: ["web/read"],
+        created: token,
+      });
+
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to create token: ${e}`,
+      });
+    }
+
+    return false;
+  }
+
+  /**
+   * Delete the given token.
+   *
+   * @param {string} name name of the token to delete.
+   */
+  async delete(name) {
+    this.setState({
+      loading: true,
+    });
+
+    try {
+      await this.api.tokensDelete(name);
+      await this.list();
+    } catch(e) {
+      this.setState({
+        loading: false,
+        error: `failed to delete token: ${e}`,
+      });
+    }
+  }
+
+  /**
+   * Toggle if the given scope should be granted to the new token.
+   */
+  toggleScope(scope, checked) {
+    let scopes = this.state.scopes.filter(s => s !== scope);
+
+    if (checked) {
+      scopes.push(scope);
+    }
+
+    this.setState({scopes});
+  }
+
+  /**
+   * Use the given token in this browser, or stop using a token if `null`.
+   */
+  useToken(token) {
+    setApiToken(token);
+    this.setState({stored: token});
+  }
+
+  render() {
+    let content = null;
+
+    if (this.state.data) {
+      if (this.state.data.length === 0) {
+        content = (
+          <Alert variant="info">
+            No API Tokens!
+          </Alert>
+        );
+      } else {
+        content = (
+          <Table responsive="sm">
+            <thead>
+              <tr>
+                <th>Name</th>
+                <th className="table-fill">Scopes</th>
+                <th>Created</th>
+                <th></th>
+              </tr>
+            </thead>
+            <tbody>
+              {this.state.data.map(t => {
+                return (
+                  <tr key={t.name}>
+                    <td>{t.name}</td>
+                    <td>{t.scopes.map(s => <code className="tokens-scope" key={s}>{s}</code>)}</td>
+                    <td className="tokens-datetime datetime">{t.created_at}</td>
+                    <td>
+                      <Button size="sm" variant="danger" disabled={this.state.loading} onClick={() => this.delete(t.name)}>
+                        Delete
+                      </Button>
+                    </td>
+                  </tr>
+                );
+              })}
+            </tbody>
+          </Table>
+        );
+      }
+    }
+
+    let created = null;
+
+    if (this.state.created) {
+      created = (
+        <Alert variant="success">
+          <p>
+            Token created, copy it now since it won't be shown again:
+          </p>
+
+          <p><code className="tokens-token">{this.state.created}</code></p>
+
+          <Button size="sm" variant="primary" onClick={() => this.useToken(this.state.created)}>
+            Use in this browser
+          </Button>
+        </Alert>
+      );
+    }
+
+    let stored = null;
+
+    if (this.state.stored) {
+      stored = (
+        <Alert variant="secondary">
+          This browser is using an API token.{" "}
+          <Button size="sm" variant="secondary" onClick={() => this.useToken(null)}>
+            Forget token
+          </Button>
+        </Alert>
+      );
+    }
+
+    return <>
+      <h1 className='oxi-page-title'>API Tokens</h1>
+      <Loading isLoading={this.state.loading} />
+      <Error error={this.state.error} />
+
+      <p>
+        Tokens are only required when the <code>web/require-token</code> setting is enabled.
+        If no token has the <code>web/admin</code> scope when the bot starts, it creates one named <code>admin</code> and opens this interface with it.
+        Overlays can be given a token by adding <code>?token=&lt;token&gt;</code> to their URL.
+      </p>
+
+      {stored}
+      {created}
+
+      <Form className="mb-3" onSubmit={e => this.create(e)}>
+        <Row>
+          <Col>
+            <Form.Group>
+              <Form.Label>Name</Form.Label>
+              <Form.Control placeholder="overlay" value={this.state.name} onChange={e => this.setState({name: e.target.value})} />
+            </Form.Group>
+          </Col>
+          <Col>
+            <Form.Group>
+              <Form.Label>Scopes</Form.Label>
+              {SCOPES.map(s => {
+                return <Form.Check
+                  key={s.scope}
+                  id={`token-scope-${s.scope}`}
+                  type="checkbox"
+                  label={<><b>{s.title}</b> - {s.help}</>}
+                  checked={this.state.scopes.indexOf(s.scope) !== -1}
+                  onChange={e => this.toggleScope(s.scope, e.target.checked)} />;
+              })}
+            </Form.Group>
+          </Col>
+        </Row>
+        <Button type="submit" variant="primary" disabled={this.state.loading || !this.state.name || this.state.scopes.length === 0}>Create</Button>
+      </Form>
+
+      {content}
+    </>;
+  }
+}
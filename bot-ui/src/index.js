@@ -8,8 +8,15 @@ import {Container, Row, Col, Navbar, Nav, NavDropdown, Alert, Button, Form} from
 import Connections from "./components/Connections.js";
 import Devices from "./components/Devices.js";
 import AfterStreams from "./components/AfterStreams.js";
+import ChatHistory from "./components/ChatHistory.js";
+import Profiles from "./components/Profiles.js";
 import Overlay from "./components/Overlay.js";
 import Settings from "./components/Settings.js";
+import SettingsHistory from "./components/SettingsHistory.js";
+import Schedule from "./components/Schedule.js";
+import Tokens from "./components/Tokens.js";
+import GtavQueue from "./components/GtavQueue.js";
+import Clips from "./components/Clips.js";
 import Cache from "./components/Cache";
 import Modules from "./components/Modules.js";
 import ImportExport from "./components/ImportExport.js";
@@ -48,6 +55,36 @@ class AfterStreamsPage extends React.Component {
   }
 }
 
+class ChatHistoryPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <ChatHistory api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
+class ProfilesPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <Profiles api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
 class SettingsPage extends React.Component {
   constructor(props) {
     super(props);
@@ -65,6 +102,81 @@ class SettingsPage extends React.Component {
   }
 }
 
+class SettingsHistoryPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <SettingsHistory api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
+class SchedulePage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <Schedule api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
+class TokensPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <Tokens api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
+class GtavQueuePage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <GtavQueue api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
+class ClipsPage extends React.Component {
+  constructor(props) {
+    super(props);
+    this.api = new Api(utils.apiUrl());
+  }
+
+  render() {
+    return (
+      <RouteLayout>
+        <Clips api={this.api} />
+      </RouteLayout>
+    );
+  }
+}
+
 class CachePage extends React.Component {
   constructor(props) {
     super(props);
@@ -316,6 +428,12 @@ class Layout extends React.Component {
                 <NavDropdown.Item as={Link} active={path === "/aliases"} to="/aliases">
                   Aliases
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/chat-history"} to="/chat-history">
+                  Chat History
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/clips"} to="/clips">
+                  Clips
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/commands"} to="/commands">
                   Commands
                 </NavDropdown.Item>
@@ -325,12 +443,27 @@ class Layout extends React.Component {
                 <NavDropdown.Item as={Link} active={path === "/themes"} to="/themes">
                   Themes
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/gtav-queue"} to="/gtav-queue">
+                  ChaosMod Queue
+                </NavDropdown.Item>
               </NavDropdown>
 
               <NavDropdown title="Advanced">
                 <NavDropdown.Item as={Link} active={path === "/settings"} to="/settings">
                   Settings
                 </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/settings/history"} to="/settings/history">
+                  Settings History
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/schedule"} to="/schedule">
+                  Scheduled Settings
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/tokens"} to="/tokens">
+                  API Tokens
+                </NavDropdown.Item>
+                <NavDropdown.Item as={Link} active={path === "/profiles"} to="/profiles">
+                  Profiles
+                </NavDropdown.Item>
                 <NavDropdown.Item as={Link} active={path === "/cache"} to="/cache">
                   Cache
                 </NavDropdown.Item>
@@ -380,7 +513,14 @@ function AppRouter() {
     <Router>
       <Route path="/" exact component={IndexPage} />
       <Route path="/after-streams" exact component={AfterStreamsPage} />
+      <Route path="/chat-history" exact component={ChatHistoryPage} />
       <Route path="/settings" exact component={SettingsPage} />
+      <Route path="/settings/history" exact component={SettingsHistoryPage} />
+      <Route path="/schedule" exact component={SchedulePage} />
+      <Route path="/tokens" exact component={TokensPage} />
+      <Route path="/gtav-queue" exact component={GtavQueuePage} />
+      <Route path="/clips" exact component={ClipsPage} />
+      <Route path="/profiles" exact component={ProfilesPage} />
       <Route path="/cache" exact component={CachePage} />
       <Route path="/modules" component={ModulesPage} />
       <Route path="/authorization" exact component={props => (
@@ -406,4 +546,5 @@ function AppRouter() {
   );
 }
 
+utils.storeQueryApiToken();
 ReactDOM.render(<AppRouter />, document.getElementById("index"));
\ No newline at end of file
@@ -92,6 +92,62 @@ export function percentage(part, total) {
   return Math.round((part / total) * 10000) / 100;
 }
 
+/**
+ * Key used to store the API token in local storage.
+ */
+const API_TOKEN_KEY = "oxidize-api-token";
+
+/**
+ * Get the API token to use, if any.
+ *
+ * A `token` query parameter takes precedence over the token stored in this
+ * browser, so that overlays can be given a token through their URL.
+ */
+export function apiToken() {
+  let token = new URLSearchParams(window.location.search).get("token");
+
+  if (!!token) {
+    return token;
+  }
+
+  return window.localStorage.getItem(API_TOKEN_KEY);
+}
+
+/**
+ * Store the API token to use in this browser, or clear it if `null`.
+ *
+ * @param {string | null} token the token to store.
+ */
+export function setApiToken(token) {
+  if (token === null) {
+    window.localStorage.removeItem(API_TOKEN_KEY);
+  } else {
+    window.localStorage.setItem(API_TOKEN_KEY, token);
+  }
+}
+
+/**
+ * Store an API token handed to this browser through the `api-token` query
+ * parameter, and remove it from the URL.
+ *
+ * This is used by the bot to hand out the token it creates when no token can
+ * administer it.
+ */
+export function storeQueryApiToken() {
+  let q = new URLSearchParams(window.location.search);
+  let token = q.get("api-token");
+
+  if (!token) {
+    return;
+  }
+
+  setApiToken(token);
+  q.delete("api-token");
+
+  let search = q.toString();
+  window.history.replaceState(null, "", `${window.location.pathname}${!!search ? `?${search}` : ""}`);
+}
+
 /**
  * Get the current URL to connect to.
  */
@@ -103,7 +159,14 @@ export function websocketUrl(path) {
     scheme = "wss";
   }
 
-  return `${scheme}://${loc.host}/${path}`;
+  let url = `${scheme}://${loc.host}/${path}`;
+  let token = apiToken();
+
+  if (!!token) {
+    url = `${url}?token=${encodeURIComponent(token)}`;
+  }
+
+  return url;
 }
 
 /**
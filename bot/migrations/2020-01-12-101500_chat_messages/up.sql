@@ -0,0 +1,12 @@
+CREATE TABLE chat_messages (
+    id VARCHAR NOT NULL PRIMARY KEY,
+    channel VARCHAR NOT NULL,
+    added_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    user VARCHAR NOT NULL,
+    display_name VARCHAR NOT NULL,
+    text TEXT NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX chat_messages_user_idx ON chat_messages(channel, user, added_at);
+CREATE INDEX chat_messages_added_at_idx ON chat_messages(added_at);
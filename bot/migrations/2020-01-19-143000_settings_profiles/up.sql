@@ -0,0 +1,5 @@
+CREATE TABLE settings_profiles (
+    name VARCHAR NOT NULL PRIMARY KEY,
+    game VARCHAR,
+    settings TEXT NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE settings_history (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    key VARCHAR NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    actor VARCHAR NOT NULL,
+    actor_name VARCHAR
+);
+
+CREATE INDEX settings_history_key_idx ON settings_history(key, changed_at);
@@ -0,0 +1,11 @@
+CREATE TABLE scheduled_settings (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    key VARCHAR NOT NULL,
+    value TEXT,
+    run_at TIMESTAMP NOT NULL,
+    revert_after VARCHAR,
+    actor TEXT NOT NULL,
+    is_revert BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX scheduled_settings_run_at_idx ON scheduled_settings(run_at);
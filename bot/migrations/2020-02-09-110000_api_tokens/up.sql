@@ -0,0 +1,6 @@
+CREATE TABLE api_tokens (
+    name VARCHAR NOT NULL PRIMARY KEY,
+    token_hash VARCHAR NOT NULL UNIQUE,
+    scopes TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
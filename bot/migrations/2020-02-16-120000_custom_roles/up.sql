@@ -0,0 +1,11 @@
+CREATE TABLE custom_roles (
+    role VARCHAR NOT NULL PRIMARY KEY,
+    doc TEXT NOT NULL DEFAULT '',
+    inherits TEXT NOT NULL DEFAULT '[]'
+);
+
+CREATE TABLE role_members (
+    role VARCHAR NOT NULL,
+    user VARCHAR NOT NULL,
+    PRIMARY KEY (role, user)
+);
@@ -0,0 +1,8 @@
+CREATE TABLE temporary_grants (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    scope VARCHAR NOT NULL,
+    principal VARCHAR NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX temporary_grants_expires_at_idx ON temporary_grants(expires_at);
@@ -0,0 +1,11 @@
+CREATE TABLE clips (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    channel VARCHAR NOT NULL,
+    clip_id VARCHAR NOT NULL,
+    user VARCHAR NOT NULL,
+    title TEXT,
+    game VARCHAR,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX clips_created_at_idx ON clips(channel, created_at);
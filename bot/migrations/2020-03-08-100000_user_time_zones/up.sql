@@ -0,0 +1,6 @@
+CREATE TABLE user_time_zones (
+    channel VARCHAR NOT NULL,
+    user VARCHAR NOT NULL,
+    time_zone VARCHAR NOT NULL,
+    PRIMARY KEY (channel, user)
+);
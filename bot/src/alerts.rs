@@ -0,0 +1,256 @@
+//! Queue of alerts to show on the overlay.
+//!
+//! Alerts are shown one at a time for a configurable duration, and are sent
+//! over the global bus as `alert` messages.
+
+use crate::bus;
+use crate::irc;
+use crate::prelude::*;
+use crate::template::Template;
+use crate::utils::Duration;
+use anyhow::Result;
+use std::collections::VecDeque;
+
+/// Maximum number of alerts we keep in the queue.
+const MAX_QUEUE: usize = 100;
+
+/// The kind of an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum AlertKind {
+    #[serde(rename = "follow")]
+    Follow,
+    #[serde(rename = "sub")]
+    Sub,
+    #[serde(rename = "raid")]
+    Raid,
+    #[serde(rename = "cheer")]
+    Cheer,
+    #[serde(rename = "donation")]
+    Donation,
+}
+
+impl AlertKind {
+    /// The settings group for the given alert kind.
+    fn key(self) -> &'static str {
+        match self {
+            AlertKind::Follow => "follow",
+            AlertKind::Sub => "sub",
+            AlertKind::Raid => "raid",
+            AlertKind::Cheer => "cheer",
+            AlertKind::Donation => "donation",
+        }
+    }
+}
+
+/// An alert to queue up.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    /// Display name of the user responsible for the alert.
+    pub name: String,
+    /// Amount associated with the alert, like months subscribed, bits cheered
+    /// or viewers raided with.
+    #[serde(default)]
+    pub amount: Option<u64>,
+    /// Message attached to the alert.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl Alert {
+    /// Convert a channel event into an alert, if it's something we alert on.
+    pub fn from_channel_event(event: &irc::ChannelEvent) -> Option<Self> {
+        use irc::ChannelEvent::*;
+
+        let alert = match event {
+            Sub { name, message, .. } => Alert {
+                kind: AlertKind::Sub,
+                name: name.clone(),
+                amount: Some(1),
+                message: message.clone(),
+            },
+            Resub {
+                name,
+                months,
+                message,
+                ..
+            } => Alert {
+                kind: AlertKind::Sub,
+                name: name.clone(),
+                amount: Some(*months as u64),
+                message: message.clone(),
+            },
+            SubGift {
+                recipient_name,
+                months,
+                ..
+            } => Alert {
+                kind: AlertKind::Sub,
+                name: recipient_name.clone(),
+                amount: Some(*months as u64),
+                message: None,
+            },
+            Raid { name, viewers, .. } => Alert {
+                kind: AlertKind::Raid,
+                name: name.clone(),
+                amount: Some(*viewers as u64),
+                message: None,
+            },
+            Cheer {
+                name,
+                bits,
+                message,
+                ..
+            } => Alert {
+                kind: AlertKind::Cheer,
+                name: name.clone(),
+                amount: Some(*bits as u64),
+                message: Some(message.clone()),
+            },
+            Host { .. } => return None,
+        };
+
+        Some(alert)
+    }
+}
+
+/// Handle to the alert queue.
+#[derive(Clone)]
+pub struct Alerts {
+    tx: mpsc::UnboundedSender<Alert>,
+}
+
+impl Alerts {
+    /// Construct a handle which queues alerts into the returned receiver.
+    #[cfg(test)]
+    pub(crate) fn channel() -> (Self, mpsc::UnboundedReceiver<Alert>) {
+        let (tx, rx) = mpsc::unbounded();
+        (Self { tx }, rx)
+    }
+
+    /// Queue up the given alert.
+    pub fn push(&self, alert: Alert) -> Result<()> {
+        self.tx
+            .unbounded_send(alert)
+            .map_err(|_| anyhow::anyhow!("alert queue is not running"))
+    }
+}
+
+/// Per-kind alert configuration.
+struct Config {
+    settings: settings::Settings,
+}
+
+impl Config {
+    /// Render the alert into a message for the overlay.
+    async fn render(&self, alert: Alert, duration: Duration) -> Result<bus::Global> {
+        let settings = self.settings.scoped(alert.kind.key());
+
+        let template = match settings.get::<Template>("template").await? {
+            Some(template) => template,
+            None => Template::compile(default_template(alert.kind))?,
+        };
+
+        let text = template.render_to_string(&alert)?;
+
+        return Ok(bus::Global::Alert {
+            kind: alert.kind,
+            text,
+            name: alert.name,
+            amount: alert.amount,
+            message: alert.message,
+            duration: duration.as_std().as_secs(),
+            sound: settings.get::<String>("sound").await?,
+            image: settings.get::<String>("image").await?,
+        });
+
+        fn default_template(kind: AlertKind) -> &'static str {
+            match kind {
+                AlertKind::Follow => "{{name}} just followed!",
+                AlertKind::Sub => "{{name}} just subscribed!",
+                AlertKind::Raid => "{{name}} is raiding with {{amount}} viewers!",
+                AlertKind::Cheer => "{{name}} cheered {{amount}} bits!",
+                AlertKind::Donation => "{{name}} donated {{amount}}!",
+            }
+        }
+    }
+}
+
+/// Set up the alert queue.
+pub async fn setup(
+    settings: settings::Settings,
+    global_bus: Arc<bus::Bus<bus::Global>>,
+) -> Result<(Alerts, impl Future<Output = Result<()>>)> {
+    let (tx, mut rx) = mpsc::unbounded();
+
+    let (mut enabled_stream, mut enabled) = settings.stream::<bool>("enabled").or_default().await?;
+    let (mut duration_stream, mut duration) = settings
+        .stream("duration")
+        .or_with(Duration::seconds(10))
+        .await?;
+
+    let config = Config { settings };
+
+    let future = async move {
+        let mut events = global_bus.subscribe().fuse();
+        let mut queue = VecDeque::new();
+        let mut current = None::<tokio::time::Delay>;
+
+        loop {
+            futures::select! {
+                update = enabled_stream.select_next_some() => {
+                    enabled = update;
+
+                    if !enabled {
+                        queue.clear();
+                    }
+                }
+                update = duration_stream.select_next_some() => {
+                    duration = update;
+                }
+                alert = rx.next() => {
+                    let alert = match alert {
+                        Some(alert) => alert,
+                        None => return Ok(()),
+                    };
+
+                    if enabled {
+                        queue.push_back(alert);
+                    }
+                }
+                event = events.select_next_some() => {
+                    if let Ok(bus::Global::ChannelEvent { event }) = event {
+                        if let (true, Some(alert)) = (enabled, Alert::from_channel_event(&event)) {
+                            queue.push_back(alert);
+                        }
+                    }
+                }
+                _ = current.current() => {
+                    current = None;
+                }
+            }
+
+            while queue.len() > MAX_QUEUE {
+                queue.pop_front();
+            }
+
+            if current.is_some() {
+                continue;
+            }
+
+            if let Some(alert) = queue.pop_front() {
+                match config.render(alert, duration).await {
+                    Ok(m) => {
+                        global_bus.send(m).await;
+                        current = Some(tokio::time::delay_for(duration.as_std()));
+                    }
+                    Err(e) => {
+                        log_error!(e, "Failed to render alert");
+                    }
+                }
+            }
+        }
+    };
+
+    Ok((Alerts { tx }, future))
+}
@@ -0,0 +1,319 @@
+//! Client for the LiveSplit Server text protocol.
+//!
+//! The server accepts one command per line, like `getsplitindex`, and replies
+//! to each query with a single line.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Duration;
+use std::time;
+use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio::net::TcpStream;
+
+/// The default address of LiveSplit Server.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:16834";
+
+/// How long to wait for the server to connect or reply.
+const TIMEOUT: time::Duration = time::Duration::from_secs(2);
+
+/// The phase of the timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimerPhase {
+    NotRunning,
+    Running,
+    Ended,
+    Paused,
+}
+
+impl TimerPhase {
+    /// Parse the timer phase as returned by `getcurrenttimerphase`.
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s {
+            "NotRunning" => TimerPhase::NotRunning,
+            "Running" => TimerPhase::Running,
+            "Ended" => TimerPhase::Ended,
+            "Paused" => TimerPhase::Paused,
+            other => bail!("unsupported timer phase `{}`", other),
+        })
+    }
+}
+
+/// A connection to LiveSplit Server.
+///
+/// Connects lazily, and reconnects on the next command if the connection is
+/// lost.
+pub struct LiveSplit {
+    address: String,
+    stream: Option<BufReader<TcpStream>>,
+}
+
+impl LiveSplit {
+    /// Construct a new client for the server at the given address.
+    pub fn new(address: String) -> Self {
+        Self {
+            address,
+            stream: None,
+        }
+    }
+
+    /// Change the address of the server, disconnecting if it changed.
+    pub fn set_address(&mut self, address: String) {
+        if self.address != address {
+            self.address = address;
+            self.stream = None;
+        }
+    }
+
+    /// Get the current phase of the timer.
+    pub async fn timer_phase(&mut self) -> Result<TimerPhase> {
+        TimerPhase::parse(&self.command("getcurrenttimerphase").await?)
+    }
+
+    /// Get the index of the current split, if the timer is running.
+    pub async fn split_index(&mut self) -> Result<Option<usize>> {
+        let index = str::parse::<i64>(&self.command("getsplitindex").await?)?;
+
+        if index < 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(index as usize))
+    }
+
+    /// Get the name of the current split.
+    pub async fn current_split_name(&mut self) -> Result<Option<String>> {
+        Ok(name(self.command("getcurrentsplitname").await?))
+    }
+
+    /// Get the name of the previous split.
+    pub async fn previous_split_name(&mut self) -> Result<Option<String>> {
+        Ok(name(self.command("getprevioussplitname").await?))
+    }
+
+    /// Get the current time of the timer.
+    pub async fn current_time(&mut self) -> Result<Option<Duration>> {
+        parse_optional_time(&self.command("getcurrenttime").await?)
+    }
+
+    /// Get the time of the last split.
+    pub async fn last_split_time(&mut self) -> Result<Option<Duration>> {
+        parse_optional_time(&self.command("getlastsplittime").await?)
+    }
+
+    /// Get how far ahead (negative) or behind (positive) the current run is
+    /// compared to the current comparison.
+    pub async fn delta(&mut self) -> Result<Option<Duration>> {
+        parse_optional_time(&self.command("getdelta").await?)
+    }
+
+    /// Get the predicted final time of the current run.
+    pub async fn predicted_time(&mut self) -> Result<Option<Duration>> {
+        parse_optional_time(&self.command("getpredictedtime").await?)
+    }
+
+    /// Send a single command and read its reply.
+    async fn command(&mut self, command: &str) -> Result<String> {
+        let result = self.command_inner(command).await;
+
+        // NB: drop any broken connection so that we reconnect next time.
+        if result.is_err() {
+            self.stream = None;
+        }
+
+        result
+    }
+
+    async fn command_inner(&mut self, command: &str) -> Result<String> {
+        if self.stream.is_none() {
+            let stream =
+                tokio::time::timeout(TIMEOUT, TcpStream::connect(self.address.as_str())).await??;
+            self.stream = Some(BufReader::new(stream));
+        }
+
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| anyhow!("not connected"))?;
+
+        let line = format!("{}\r\n", command);
+        stream.get_mut().write_all(line.as_bytes()).await?;
+
+        let mut reply = String::new();
+
+        if tokio::time::timeout(TIMEOUT, stream.read_line(&mut reply)).await?? == 0 {
+            bail!("connection closed by LiveSplit Server");
+        }
+
+        Ok(reply.trim_end_matches(&['\r', '\n'][..]).to_string())
+    }
+}
+
+/// Treat empty names and `-` as missing.
+fn name(name: String) -> Option<String> {
+    match name.trim() {
+        "" | "-" => None,
+        _ => Some(name),
+    }
+}
+
+/// Parse a time which might be missing, which LiveSplit indicates with `-`.
+fn parse_optional_time(s: &str) -> Result<Option<Duration>> {
+    match s.trim() {
+        "" | "-" => Ok(None),
+        s => match parse_time(s) {
+            Some(time) => Ok(Some(time)),
+            None => bail!("bad time `{}`", s),
+        },
+    }
+}
+
+/// Parse a time as formatted by LiveSplit, like `1:23:45.67` or `−0.53`.
+///
+/// Note that LiveSplit uses the unicode minus sign (`−`) for negative deltas.
+pub fn parse_time(s: &str) -> Option<Duration> {
+    let s = s.trim();
+
+    let (negative, s) = if let Some(rest) = s.strip_prefix('−') {
+        (true, rest)
+    } else if let Some(rest) = s.strip_prefix('-') {
+        (true, rest)
+    } else if let Some(rest) = s.strip_prefix('+') {
+        (false, rest)
+    } else {
+        (false, s)
+    };
+
+    let mut parts = s.rsplit(':');
+    let seconds = str::parse::<f64>(parts.next()?).ok()?;
+
+    if !seconds.is_finite() || seconds < 0f64 {
+        return None;
+    }
+
+    let mut millis = (seconds * 1000f64).round() as i64;
+    let mut factor = 60_000i64;
+
+    for part in parts.take(2) {
+        millis += str::parse::<i64>(part).ok()? * factor;
+        factor *= 60;
+    }
+
+    let duration = Duration::milliseconds(millis);
+
+    if negative {
+        Some(-duration)
+    } else {
+        Some(duration)
+    }
+}
+
+/// Format a time the way LiveSplit does, like `1:23:45.67`.
+pub fn format_time(duration: Duration) -> String {
+    let mut millis = duration.num_milliseconds();
+    let sign = if millis < 0 { "-" } else { "" };
+    millis = millis.abs();
+
+    let hundredths = (millis % 1000) / 10;
+    let seconds = (millis / 1000) % 60;
+    let minutes = (millis / 60_000) % 60;
+    let hours = millis / 3_600_000;
+
+    if hours > 0 {
+        format!(
+            "{}{}:{:02}:{:02}.{:02}",
+            sign, hours, minutes, seconds, hundredths
+        )
+    } else if minutes > 0 {
+        format!("{}{}:{:02}.{:02}", sign, minutes, seconds, hundredths)
+    } else {
+        format!("{}{}.{:02}", sign, seconds, hundredths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_time, parse_time, LiveSplit, TimerPhase};
+    use chrono::Duration;
+    use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::stream::StreamExt as _;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(Some(Duration::milliseconds(5_670)), parse_time("5.67"));
+        assert_eq!(
+            Some(Duration::milliseconds(23 * 60_000 + 45_670)),
+            parse_time("23:45.67")
+        );
+        assert_eq!(
+            Some(Duration::milliseconds(3_600_000 + 23 * 60_000 + 45_670)),
+            parse_time("1:23:45.67")
+        );
+        assert_eq!(Some(Duration::milliseconds(-530)), parse_time("−0.53"));
+        assert_eq!(Some(Duration::milliseconds(-530)), parse_time("-0.53"));
+        assert_eq!(Some(Duration::milliseconds(83_450)), parse_time("+1:23.45"));
+        assert_eq!(None, parse_time("-"));
+        assert_eq!(None, parse_time("abc"));
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!("5.67", format_time(Duration::milliseconds(5_670)));
+        assert_eq!(
+            "1:23:45.67",
+            format_time(Duration::milliseconds(3_600_000 + 23 * 60_000 + 45_670))
+        );
+        assert_eq!("-1:03.50", format_time(Duration::milliseconds(-63_500)));
+    }
+
+    /// Test the client against a stub speaking the LiveSplit Server protocol.
+    #[tokio::test]
+    async fn test_client() -> Result<(), anyhow::Error> {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accept");
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut lines = BufReader::new(reader).lines();
+
+            while let Some(Ok(line)) = lines.next().await {
+                let reply = match line.as_str() {
+                    "getcurrenttimerphase" => "Running",
+                    "getsplitindex" => "2",
+                    "getcurrentsplitname" => "Bowser",
+                    "getprevioussplitname" => "Castle",
+                    "getlastsplittime" => "12:34.56",
+                    "getdelta" => "−1.23",
+                    "getpredictedtime" => "1:02:03.04",
+                    _ => "-",
+                };
+
+                writer
+                    .write_all(format!("{}\r\n", reply).as_bytes())
+                    .await
+                    .expect("write");
+            }
+        });
+
+        let mut client = LiveSplit::new(address.to_string());
+
+        assert_eq!(TimerPhase::Running, client.timer_phase().await?);
+        assert_eq!(Some(2), client.split_index().await?);
+        assert_eq!(
+            Some(String::from("Bowser")),
+            client.current_split_name().await?
+        );
+        assert_eq!(
+            Some(String::from("Castle")),
+            client.previous_split_name().await?
+        );
+        assert_eq!(
+            Some(Duration::milliseconds(12 * 60_000 + 34_560)),
+            client.last_split_time().await?
+        );
+        assert_eq!(Some(Duration::milliseconds(-1_230)), client.delta().await?);
+        assert_eq!(None, client.current_time().await?);
+        Ok(())
+    }
+}
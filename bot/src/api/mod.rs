@@ -2,6 +2,7 @@ mod base;
 pub mod bttv;
 pub mod ffz;
 pub mod github;
+pub mod livesplit;
 pub mod nightbot;
 pub mod open_weather_map;
 pub mod setbac;
@@ -15,6 +16,7 @@ pub use self::base::RequestBuilder;
 pub use self::bttv::BetterTTV;
 pub use self::ffz::FrankerFaceZ;
 pub use self::github::GitHub;
+pub use self::livesplit::LiveSplit;
 pub use self::nightbot::NightBot;
 pub use self::open_weather_map::OpenWeatherMap;
 pub use self::setbac::Setbac;
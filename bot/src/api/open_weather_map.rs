@@ -69,11 +69,16 @@ pub async fn setup(
 impl OpenWeatherMap {
     /// Create a new API integration.
     pub fn new(api_key: String) -> Result<OpenWeatherMap> {
-        Ok(OpenWeatherMap {
+        Ok(Self::with_url(api_key, str::parse::<Url>(V2_URL)?))
+    }
+
+    /// Create a new API integration against the given v2 URL.
+    pub fn with_url(api_key: String, v2_url: Url) -> OpenWeatherMap {
+        OpenWeatherMap {
             client: Client::new(),
-            v2_url: str::parse::<Url>(V2_URL)?,
+            v2_url,
             api_key: Arc::new(api_key),
-        })
+        }
     }
 
     /// Build request against v2 URL.
@@ -94,6 +99,12 @@ impl OpenWeatherMap {
         let req = self.v2(Method::GET, &["weather"]).query_param("q", &q);
         Ok(req.execute().await?.not_found().json()?)
     }
+
+    /// Get the forecast for the next five days, in steps of three hours.
+    pub async fn forecast(&self, q: String) -> Result<Option<Forecast>> {
+        let req = self.v2(Method::GET, &["forecast"]).query_param("q", &q);
+        Ok(req.execute().await?.not_found().json()?)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -202,3 +213,77 @@ pub struct Precipitation {
 pub struct Clouds {
     pub all: u64,
 }
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Forecast {
+    pub list: Vec<ForecastEntry>,
+    pub city: City,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ForecastEntry {
+    pub dt: i64,
+    pub main: Main,
+    pub weather: Vec<Weather>,
+    #[serde(default)]
+    pub rain: Option<Precipitation>,
+    #[serde(default)]
+    pub snow: Option<Precipitation>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct City {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub country: Option<String>,
+    /// Offset from UTC in seconds.
+    #[serde(default)]
+    pub timezone: Option<i32>,
+}
+
+/// A mock of the OpenWeatherMap API, serving fixtures for `Stockholm`.
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::OpenWeatherMap;
+    use reqwest::Url;
+    use std::collections::HashMap;
+    use warp::http::StatusCode;
+    use warp::Filter as _;
+
+    const WEATHER: &str = include_str!("../../tests/fixtures/open_weather_map/weather.json");
+    const FORECAST: &str = include_str!("../../tests/fixtures/open_weather_map/forecast.json");
+    const NOT_FOUND: &str = r#"{"cod":"404","message":"city not found"}"#;
+
+    /// Start a mock server and construct a client for it.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub(crate) fn server() -> OpenWeatherMap {
+        let weather = warp::path("weather")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|query: HashMap<String, String>| reply(&query, WEATHER));
+
+        let forecast = warp::path("forecast")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|query: HashMap<String, String>| reply(&query, FORECAST));
+
+        let routes = warp::path("data")
+            .and(warp::path("2.5"))
+            .and(weather.or(forecast));
+
+        let (address, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let url = format!("http://{}/data/2.5", address);
+        let url = str::parse::<Url>(&url).expect("valid url");
+        OpenWeatherMap::with_url(String::from("secret"), url)
+    }
+
+    /// Reply with the fixture if the location is known.
+    fn reply(query: &HashMap<String, String>, fixture: &'static str) -> impl warp::Reply {
+        match query.get("q").map(String::as_str) {
+            Some("Stockholm") => warp::reply::with_status(fixture, StatusCode::OK),
+            _ => warp::reply::with_status(NOT_FOUND, StatusCode::NOT_FOUND),
+        }
+    }
+}
@@ -139,6 +139,33 @@ impl Speedrun {
             .json()?)
     }
 
+    /// Get the most recently verified runs matching the given filter, newest
+    /// first.
+    pub async fn verified_runs(
+        &self,
+        filter: &RunsFilter,
+        max: u32,
+    ) -> Result<Option<Vec<RunInfo>>> {
+        let mut request = self
+            .v1(Method::GET, &["runs"])
+            .query_param("status", "verified")
+            .query_param("orderby", "verify-date")
+            .query_param("direction", "desc")
+            .query_param("max", max.to_string().as_str());
+
+        request = match filter {
+            RunsFilter::User(user_id) => request.query_param("user", user_id),
+            RunsFilter::Game(game_id) => request.query_param("game", game_id),
+        };
+
+        let data: Option<Page<RunInfo>> = request
+            .execute()
+            .await?
+            .empty_on_status(StatusCode::NO_CONTENT)
+            .json()?;
+        Ok(data.map(|d| d.data))
+    }
+
     /// Get all records associated with a category.
     pub async fn leaderboard(
         &self,
@@ -446,6 +473,16 @@ pub struct RelatedGuest {
     pub uri: String,
 }
 
+/// Filter used when listing runs.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", content = "id", rename_all = "kebab-case")]
+pub enum RunsFilter {
+    /// Runs by the user with the given id.
+    User(String),
+    /// Runs in the game with the given id.
+    Game(String),
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Times {
     pub primary: PtDuration,
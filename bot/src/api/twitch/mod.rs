@@ -153,6 +153,16 @@ impl Twitch {
         }
     }
 
+    /// Get the most recent followers of the given user.
+    pub async fn new_followers(&self, to_id: &str) -> Result<Vec<Follow>> {
+        let req = self
+            .new_api(Method::GET, &["users", "follows"])
+            .query_param("to_id", to_id);
+
+        let res = req.execute().await?.json::<Page<Follow>>()?;
+        Ok(res.data)
+    }
+
     /// Create a clip for the given broadcaster.
     pub async fn create_clip(&self, broadcaster_id: &str) -> Result<Option<Clip>> {
         let req = self
@@ -408,6 +418,15 @@ pub struct Subscription {
     pub user_name: String,
 }
 
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Follow {
+    pub from_id: String,
+    pub from_name: String,
+    pub to_id: String,
+    pub to_name: String,
+    pub followed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct StreamInfo {
     pub started_at: DateTime<Utc>,
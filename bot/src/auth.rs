@@ -3,14 +3,18 @@ use crate::utils::{Cooldown, Duration};
 use anyhow::{Context as _, Error};
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::iter;
 use std::sync::Arc;
+use std::time;
 use tokio::sync::RwLock;
 
 const SCHEMA: &[u8] = include_bytes!("auth.yaml");
 
+/// How frequently expired temporary grants are purged.
+const PURGE_INTERVAL: time::Duration = time::Duration::from_secs(30);
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Schema {
     roles: HashMap<Role, RoleData>,
@@ -35,7 +39,7 @@ impl fmt::Display for RoleOrUser {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             RoleOrUser::User(ref user) => user.fmt(fmt),
-            RoleOrUser::Role(role) => role.fmt(fmt),
+            RoleOrUser::Role(ref role) => role.fmt(fmt),
         }
     }
 }
@@ -45,7 +49,7 @@ impl std::str::FromStr for RoleOrUser {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.starts_with('@') {
-            let role = Role::from_str(&s[1..])?;
+            let role = Role::from_str(s)?;
             return Ok(RoleOrUser::Role(role));
         }
 
@@ -53,8 +57,18 @@ impl std::str::FromStr for RoleOrUser {
     }
 }
 
+impl serde::Serialize for RoleOrUser {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 /// A grant that has been temporarily given.
 struct TemporaryGrant {
+    pub id: i32,
     pub scope: Scope,
     pub principal: RoleOrUser,
     pub expires_at: DateTime<Utc>,
@@ -67,6 +81,32 @@ impl TemporaryGrant {
     }
 }
 
+/// Information on a temporary grant which hasn't expired yet.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TemporaryGrantInfo {
+    /// Identifier of the grant.
+    pub id: i32,
+    /// The scope granted.
+    pub scope: Scope,
+    /// The role or user the scope is granted to.
+    pub principal: RoleOrUser,
+    /// When the grant expires.
+    pub expires_at: DateTime<Utc>,
+    /// Time remaining until the grant expires.
+    pub remaining: Duration,
+}
+
+/// A role defined by the streamer.
+#[derive(Debug, Clone, Default)]
+struct CustomRole {
+    /// Documentation for this role.
+    doc: String,
+    /// Roles whose grants this role inherits.
+    inherits: Vec<Role>,
+    /// Users which are members of this role.
+    members: HashSet<String>,
+}
+
 /// A container for scopes and their grants.
 #[derive(Clone)]
 pub struct Auth {
@@ -77,20 +117,83 @@ pub struct Auth {
     pub grants: Arc<RwLock<HashSet<(Scope, Role)>>>,
     /// Temporary grants.
     temporary_grants: Arc<RwLock<Vec<TemporaryGrant>>>,
+    /// Custom roles, and their members.
+    custom_roles: Arc<RwLock<HashMap<Role, CustomRole>>>,
 }
 
 impl Auth {
     pub async fn new(db: db::Database, schema: Schema) -> Result<Self, Error> {
-        use db::schema::grants::dsl;
+        use db::schema::{custom_roles, grants::dsl, role_members, temporary_grants};
+
+        let now = Utc::now().naive_utc();
 
-        let grants = db
+        let (grants, custom, temporary) = db
             .asyncify(move |c| {
                 let grants = dsl::grants
                     .select((dsl::scope, dsl::role))
                     .load::<(Scope, Role)>(c)?
                     .into_iter()
                     .collect::<HashSet<_>>();
-                Ok::<_, Error>(grants)
+
+                let mut custom = HashMap::<Role, CustomRole>::new();
+
+                let roles = custom_roles::table
+                    .select((
+                        custom_roles::role,
+                        custom_roles::doc,
+                        custom_roles::inherits,
+                    ))
+                    .load::<(Role, String, String)>(c)?;
+
+                for (role, doc, inherits) in roles {
+                    let inherits = serde_json::from_str(&inherits)?;
+                    custom.insert(
+                        role,
+                        CustomRole {
+                            doc,
+                            inherits,
+                            members: HashSet::new(),
+                        },
+                    );
+                }
+
+                let members = role_members::table
+                    .select((role_members::role, role_members::user))
+                    .load::<(Role, String)>(c)?;
+
+                for (role, user) in members {
+                    custom.entry(role).or_default().members.insert(user);
+                }
+
+                let mut temporary = Vec::new();
+
+                let rows = temporary_grants::table
+                    .filter(temporary_grants::expires_at.gt(now))
+                    .order(temporary_grants::id)
+                    .load::<(i32, Scope, String, chrono::NaiveDateTime)>(c)?;
+
+                for (id, scope, principal, expires_at) in rows {
+                    let principal = match str::parse(&principal) {
+                        Ok(principal) => principal,
+                        Err(e) => {
+                            log_warn!(
+                                e,
+                                "Ignoring temporary grant with bad principal: {}",
+                                principal
+                            );
+                            continue;
+                        }
+                    };
+
+                    temporary.push(TemporaryGrant {
+                        id,
+                        scope,
+                        principal,
+                        expires_at: DateTime::from_utc(expires_at, Utc),
+                    });
+                }
+
+                Ok::<_, Error>((grants, custom, temporary))
             })
             .await?;
 
@@ -98,7 +201,8 @@ impl Auth {
             db,
             schema: Arc::new(schema),
             grants: Arc::new(RwLock::new(grants)),
-            temporary_grants: Default::default(),
+            temporary_grants: Arc::new(RwLock::new(temporary)),
+            custom_roles: Arc::new(RwLock::new(custom)),
         };
 
         // perform default initialization based on auth.yaml
@@ -131,7 +235,9 @@ impl Auth {
     /// Return all temporary scopes belonging to the specified user.
     pub async fn scopes_for_role(&self, needle: Role) -> Vec<Scope> {
         let now = Utc::now();
-        let mut out = self.temporary_scopes(&now, RoleOrUser::Role(needle)).await;
+        let mut out = self
+            .temporary_scopes(&now, RoleOrUser::Role(needle.clone()))
+            .await;
 
         let grants = self.grants.read().await;
 
@@ -191,7 +297,7 @@ impl Auth {
 
         for (key, data) in to_insert {
             for allow in &data.allow {
-                self.insert(key, *allow).await?;
+                self.insert(key, allow.clone()).await?;
             }
 
             let version = data.version.clone();
@@ -209,33 +315,143 @@ impl Auth {
         Ok(())
     }
 
-    /// Insert a temporary grant.
+    /// Insert a temporary grant, which is stored until it expires.
     pub async fn insert_temporary(
         &self,
         scope: Scope,
         principal: RoleOrUser,
         expires_at: DateTime<Utc>,
-    ) {
+    ) -> Result<(), Error> {
+        use db::schema::temporary_grants::dsl;
+
+        let values = (
+            dsl::scope.eq(scope),
+            dsl::principal.eq(principal.to_string()),
+            dsl::expires_at.eq(expires_at.naive_utc()),
+        );
+
+        let id = self
+            .db
+            .asyncify(move |c| {
+                c.transaction(|| {
+                    diesel::insert_into(dsl::temporary_grants)
+                        .values(values)
+                        .execute(c)?;
+
+                    let id = dsl::temporary_grants
+                        .select(dsl::id)
+                        .order(dsl::id.desc())
+                        .first::<i32>(c)?;
+
+                    Ok::<_, Error>(id)
+                })
+            })
+            .await?;
+
         self.temporary_grants.write().await.push(TemporaryGrant {
+            id,
             scope,
             principal,
             expires_at,
-        })
+        });
+
+        Ok(())
     }
 
-    /// Insert an assignment.
-    pub async fn insert(&self, scope: Scope, role: Role) -> Result<(), Error> {
-        use db::schema::grants::dsl;
+    /// List all temporary grants which haven't expired yet.
+    pub async fn temporary_grants(&self) -> Vec<TemporaryGrantInfo> {
+        let now = Utc::now();
+        let grants = self.temporary_grants.read().await;
+
+        let mut out = grants
+            .iter()
+            .filter(|g| !g.is_expired(&now))
+            .map(|g| TemporaryGrantInfo {
+                id: g.id,
+                scope: g.scope,
+                principal: g.principal.clone(),
+                expires_at: g.expires_at,
+                remaining: Duration::seconds((g.expires_at - now).num_seconds().max(0) as u64),
+            })
+            .collect::<Vec<_>>();
+
+        out.sort_by_key(|g| g.expires_at);
+        out
+    }
+
+    /// Revoke the temporary grant with the given id.
+    ///
+    /// Returns `false` if there is no such grant.
+    pub async fn delete_temporary(&self, id: i32) -> Result<bool, Error> {
+        use db::schema::temporary_grants::dsl;
+
+        let removed = {
+            let mut grants = self.temporary_grants.write().await;
+            let len = grants.len();
+            grants.retain(|g| g.id != id);
+            grants.len() != len
+        };
 
         self.db
             .asyncify(move |c| {
-                diesel::insert_into(dsl::grants)
-                    .values((dsl::scope.eq(scope), dsl::role.eq(role)))
-                    .execute(c)?;
+                diesel::delete(dsl::temporary_grants.filter(dsl::id.eq(id))).execute(c)?;
                 Ok::<_, Error>(())
             })
             .await?;
 
+        Ok(removed)
+    }
+
+    /// Periodically purge temporary grants which have expired.
+    pub async fn purge_expired_grants(self) -> Result<(), Error> {
+        use db::schema::temporary_grants::dsl;
+
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let now = Utc::now();
+
+            self.temporary_grants
+                .write()
+                .await
+                .retain(|g| !g.is_expired(&now));
+
+            let now = now.naive_utc();
+
+            let result = self
+                .db
+                .asyncify(move |c| {
+                    diesel::delete(dsl::temporary_grants.filter(dsl::expires_at.le(now)))
+                        .execute(c)?;
+                    Ok::<_, Error>(())
+                })
+                .await;
+
+            if let Err(e) = result {
+                log_error!(e, "Failed to purge expired temporary grants");
+            }
+        }
+    }
+
+    /// Insert an assignment.
+    pub async fn insert(&self, scope: Scope, role: Role) -> Result<(), Error> {
+        use db::schema::grants::dsl;
+
+        {
+            let role = role.clone();
+
+            self.db
+                .asyncify(move |c| {
+                    diesel::insert_into(dsl::grants)
+                        .values((dsl::scope.eq(scope), dsl::role.eq(role)))
+                        .execute(c)?;
+                    Ok::<_, Error>(())
+                })
+                .await?;
+        }
+
         self.grants.write().await.insert((scope, role));
         Ok(())
     }
@@ -244,7 +460,7 @@ impl Auth {
     pub async fn delete(&self, scope: Scope, role: Role) -> Result<(), Error> {
         use db::schema::grants::dsl;
 
-        if self.grants.write().await.remove(&(scope, role)) {
+        if self.grants.write().await.remove(&(scope, role.clone())) {
             self.db
                 .asyncify(move |c| {
                     let _ = diesel::delete(
@@ -296,14 +512,33 @@ impl Auth {
 
     /// Test if the given assignment exists.
     pub async fn test(&self, scope: Scope, user: &str, role: Role) -> bool {
-        if self.grants.read().await.contains(&(scope, role)) {
-            return true;
+        self.test_any(scope, user, iter::once(role)).await
+    }
+
+    /// Test if the given assignment exists.
+    ///
+    /// The given roles are resolved to include the custom roles the user is a
+    /// member of, and every role they inherit.
+    pub async fn test_any(
+        &self,
+        scope: Scope,
+        user: &str,
+        roles: impl IntoIterator<Item = Role>,
+    ) -> bool {
+        let roles = self.resolve_roles(user, roles).await;
+
+        {
+            let grants = self.grants.read().await;
+
+            if roles.iter().any(|r| grants.contains(&(scope, r.clone()))) {
+                return true;
+            }
         }
 
         let now = Utc::now();
 
         let against = iter::once(RoleOrUser::User(user.to_string()))
-            .chain(iter::once(RoleOrUser::Role(role)));
+            .chain(roles.into_iter().map(RoleOrUser::Role));
 
         let (granted, expired) = self.test_temporary(&now, scope, against).await;
 
@@ -318,39 +553,61 @@ impl Auth {
         granted
     }
 
-    /// Test if the given assignment exists.
-    pub async fn test_any(
+    /// Explain why the given user is allowed or denied the given scope.
+    ///
+    /// `roles` are the built-in roles of the user, which are resolved the same
+    /// way as in `test_any`.
+    pub async fn explain(
         &self,
         scope: Scope,
         user: &str,
         roles: impl IntoIterator<Item = Role>,
-    ) -> bool {
-        let roles = roles.into_iter().collect::<HashSet<_>>();
+    ) -> Explanation {
+        let user = db::user_id(user);
+        let roles = self.resolve_roles(&user, roles).await;
+
+        let mut grants = Vec::new();
 
         {
-            let grants = self.grants.read().await;
+            let permanent = self.grants.read().await;
 
-            if roles.iter().any(|r| grants.contains(&(scope, *r))) {
-                return true;
+            for role in &roles {
+                if permanent.contains(&(scope, role.clone())) {
+                    grants.push(Grant::Permanent { role: role.clone() });
+                }
             }
         }
 
         let now = Utc::now();
 
-        let against = iter::once(RoleOrUser::User(user.to_string()))
-            .chain(roles.into_iter().map(RoleOrUser::Role));
+        let against = iter::once(RoleOrUser::User(user.clone()))
+            .chain(roles.iter().cloned().map(RoleOrUser::Role))
+            .collect::<Vec<_>>();
 
-        let (granted, expired) = self.test_temporary(&now, scope, against).await;
-
-        // Delete temporary grants that has expired.
-        if expired {
-            self.temporary_grants
-                .write()
-                .await
-                .retain(|g| !g.is_expired(&now));
+        for t in self.temporary_grants.read().await.iter() {
+            if t.scope == scope && !t.is_expired(&now) && against.contains(&t.principal) {
+                grants.push(Grant::Temporary {
+                    principal: t.principal.clone(),
+                    expires_at: t.expires_at,
+                });
+            }
         }
 
-        granted
+        let risk = self
+            .schema
+            .scopes
+            .get(&scope)
+            .map(|data| data.risk)
+            .unwrap_or_default();
+
+        Explanation {
+            user,
+            scope,
+            risk,
+            allowed: !grants.is_empty(),
+            roles,
+            grants,
+        }
     }
 
     /// Get a list of scopes and extra information associated with them.
@@ -372,8 +629,8 @@ impl Auth {
         out
     }
 
-    /// Get a list of roles.
-    pub fn roles(&self) -> Vec<RoleInfo> {
+    /// Get a list of roles, including custom roles.
+    pub async fn roles(&self) -> Vec<RoleInfo> {
         let mut out = Vec::new();
 
         for role in Role::list() {
@@ -385,18 +642,280 @@ impl Auth {
             out.push(RoleInfo {
                 role,
                 data: data.clone(),
+                custom: false,
+                members: Vec::new(),
             });
         }
 
+        let custom_roles = self.custom_roles.read().await;
+
+        let mut custom = custom_roles
+            .iter()
+            .map(|(role, custom)| {
+                let data = match self.schema.roles.get(role) {
+                    Some(data) => data.clone(),
+                    None => RoleData {
+                        doc: custom.doc.clone(),
+                        inherits: custom.inherits.clone(),
+                    },
+                };
+
+                let mut members = custom.members.iter().cloned().collect::<Vec<_>>();
+                members.sort();
+
+                RoleInfo {
+                    role: role.clone(),
+                    data,
+                    custom: true,
+                    members,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        custom.sort_by(|a, b| a.role.cmp(&b.role));
+        out.extend(custom);
         out
     }
 
+    /// Resolve the roles of the given user, expanding them with the custom
+    /// roles they are a member of and every role they inherit.
+    pub async fn resolve_roles(
+        &self,
+        user: &str,
+        roles: impl IntoIterator<Item = Role>,
+    ) -> Vec<Role> {
+        let custom = self.custom_roles.read().await;
+        resolve_roles(&self.schema, &*custom, user, roles)
+    }
+
+    /// Create or update the given custom role.
+    ///
+    /// If `doc` is `None`, the existing documentation of the role is kept.
+    pub async fn put_role(
+        &self,
+        role: Role,
+        doc: Option<String>,
+        inherits: Vec<Role>,
+    ) -> Result<(), Error> {
+        use db::schema::custom_roles::dsl;
+
+        check_custom(&role)?;
+
+        if let Some(role) = inherits.iter().find(|r| !is_inheritable(r)) {
+            anyhow::bail!("cannot inherit role: {}", role);
+        }
+
+        let doc = match doc {
+            Some(doc) => doc,
+            None => self
+                .custom_roles
+                .read()
+                .await
+                .get(&role)
+                .map(|c| c.doc.clone())
+                .unwrap_or_default(),
+        };
+
+        {
+            let role = role.clone();
+            let doc = doc.clone();
+            let inherits = serde_json::to_string(&inherits)?;
+
+            self.db
+                .asyncify(move |c| {
+                    diesel::replace_into(dsl::custom_roles)
+                        .values((
+                            dsl::role.eq(role),
+                            dsl::doc.eq(doc),
+                            dsl::inherits.eq(inherits),
+                        ))
+                        .execute(c)?;
+                    Ok::<_, Error>(())
+                })
+                .await?;
+        }
+
+        let mut custom = self.custom_roles.write().await;
+        let custom = custom.entry(role).or_default();
+        custom.doc = doc;
+        custom.inherits = inherits;
+        Ok(())
+    }
+
+    /// Delete the given custom role, along with its members and grants.
+    ///
+    /// Returns `false` if there is no such role.
+    pub async fn delete_role(&self, role: Role) -> Result<bool, Error> {
+        use db::schema::{custom_roles, grants, role_members};
+
+        if self.custom_roles.write().await.remove(&role).is_none() {
+            return Ok(false);
+        }
+
+        self.grants.write().await.retain(|(_, r)| *r != role);
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction(|| {
+                    diesel::delete(custom_roles::table.filter(custom_roles::role.eq(&role)))
+                        .execute(c)?;
+                    diesel::delete(role_members::table.filter(role_members::role.eq(&role)))
+                        .execute(c)?;
+                    diesel::delete(grants::table.filter(grants::role.eq(&role))).execute(c)?;
+                    Ok::<_, Error>(())
+                })
+            })
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Add the given user as a member of a custom role, creating the role if
+    /// it doesn't exist.
+    pub async fn insert_member(&self, role: Role, user: &str) -> Result<(), Error> {
+        use db::schema::{custom_roles, role_members};
+
+        check_custom(&role)?;
+
+        let user = db::user_id(user);
+
+        {
+            let role = role.clone();
+            let user = user.clone();
+
+            self.db
+                .asyncify(move |c| {
+                    c.transaction(|| {
+                        diesel::insert_or_ignore_into(custom_roles::table)
+                            .values(custom_roles::role.eq(&role))
+                            .execute(c)?;
+
+                        diesel::insert_or_ignore_into(role_members::table)
+                            .values((role_members::role.eq(&role), role_members::user.eq(&user)))
+                            .execute(c)?;
+
+                        Ok::<_, Error>(())
+                    })
+                })
+                .await?;
+        }
+
+        self.custom_roles
+            .write()
+            .await
+            .entry(role)
+            .or_default()
+            .members
+            .insert(user);
+
+        Ok(())
+    }
+
+    /// Remove the given user from a custom role.
+    ///
+    /// Returns `false` if the user isn't a member of the role.
+    pub async fn delete_member(&self, role: Role, user: &str) -> Result<bool, Error> {
+        use db::schema::role_members::dsl;
+
+        let user = db::user_id(user);
+
+        let removed = match self.custom_roles.write().await.get_mut(&role) {
+            Some(custom) => custom.members.remove(&user),
+            None => false,
+        };
+
+        if !removed {
+            return Ok(false);
+        }
+
+        self.db
+            .asyncify(move |c| {
+                diesel::delete(
+                    dsl::role_members.filter(dsl::role.eq(role).and(dsl::user.eq(user))),
+                )
+                .execute(c)?;
+                Ok::<_, Error>(())
+            })
+            .await?;
+
+        Ok(true)
+    }
+
     /// Get a list of all grants.
     pub async fn list(&self) -> Vec<(Scope, Role)> {
         self.grants.read().await.iter().cloned().collect()
     }
 }
 
+/// Check that the given role is a custom role.
+fn check_custom(role: &Role) -> Result<(), Error> {
+    match role {
+        Role::Custom(..) => Ok(()),
+        Role::Unknown => anyhow::bail!("not a valid role name"),
+        role => anyhow::bail!("{} is a built-in role and can't be changed", role),
+    }
+}
+
+/// Test if the given role can be inherited by a custom role.
+///
+/// Inheriting `@streamer` or `@owner` would give members the same privileges
+/// as the streamer.
+fn is_inheritable(role: &Role) -> bool {
+    match role {
+        Role::Streamer | Role::Owner | Role::Unknown => false,
+        _ => true,
+    }
+}
+
+/// Resolve the given roles for a user, expanding them with the custom roles
+/// they are a member of and every role they inherit.
+///
+/// Roles declared in the schema take their inheritance from the schema, other
+/// roles from the custom role definitions.
+fn resolve_roles(
+    schema: &Schema,
+    custom: &HashMap<Role, CustomRole>,
+    user: &str,
+    roles: impl IntoIterator<Item = Role>,
+) -> Vec<Role> {
+    let mut queue = roles.into_iter().collect::<VecDeque<_>>();
+
+    let mut members = custom
+        .iter()
+        .filter(|(_, c)| c.members.contains(user))
+        .map(|(role, _)| role.clone())
+        .collect::<Vec<_>>();
+
+    members.sort();
+    queue.extend(members);
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+
+    while let Some(role) = queue.pop_front() {
+        if !seen.insert(role.clone()) {
+            continue;
+        }
+
+        match schema.roles.get(&role) {
+            Some(data) => {
+                queue.extend(data.inherits.iter().cloned());
+            }
+            None => {
+                // NB: also filter roles which were stored before they were
+                // rejected.
+                if let Some(c) = custom.get(&role) {
+                    queue.extend(c.inherits.iter().filter(|r| is_inheritable(r)).cloned());
+                }
+            }
+        }
+
+        out.push(role);
+    }
+
+    out
+}
+
 macro_rules! scopes {
     ($(($variant:ident, $scope:expr),)*) => {
     #[derive(
@@ -479,20 +998,19 @@ macro_rules! roles {
     #[derive(
         Debug,
         Clone,
-        Copy,
         PartialEq,
         Eq,
         PartialOrd,
         Ord,
         Hash,
-        serde::Serialize,
-        serde::Deserialize,
         diesel::FromSqlRow,
         diesel::AsExpression,
     )]
     #[sql_type = "diesel::sql_types::Text"]
     pub enum Role {
-        $(#[serde(rename = $role)] $variant,)*
+        $($variant,)*
+        /// A role defined by the streamer, named without the leading `@`.
+        Custom(String),
         Unknown,
     }
 
@@ -509,6 +1027,7 @@ macro_rules! roles {
         fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
             match *self {
                 $(Role::$variant => $role.fmt(fmt),)*
+                Role::Custom(ref name) => write!(fmt, "@{}", name),
                 Role::Unknown => "unknown".fmt(fmt),
             }
         }
@@ -520,11 +1039,33 @@ macro_rules! roles {
         fn from_str(s: &str) -> Result<Self, Self::Err> {
             match s {
                 $($role => Ok(Role::$variant),)*
+                _ if s.starts_with('@') && is_custom_role_name(&s[1..]) => {
+                    Ok(Role::Custom(s[1..].to_lowercase()))
+                }
                 _ => Ok(Role::Unknown),
             }
         }
     }
 
+    impl serde::Serialize for Role {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Role {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let s = String::deserialize(deserializer)?;
+            str::parse(&s).map_err(serde::de::Error::custom)
+        }
+    }
+
     impl<DB> diesel::serialize::ToSql<diesel::sql_types::Text, DB> for Role
     where
         DB: diesel::backend::Backend,
@@ -551,6 +1092,14 @@ macro_rules! roles {
     }
 }
 
+/// Test if the given string is a valid name for a custom role.
+fn is_custom_role_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
 /// The risk of a given scope.
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum Risk {
@@ -596,16 +1145,64 @@ scopes! {
     (Countdown, "countdown"),
     (GtavBypassCooldown, "gtav/bypass-cooldown"),
     (GtavRaw, "gtav/raw"),
+    (GameBridge, "game-bridge"),
+    (GameBridgeBypassCooldown, "game-bridge/bypass-cooldown"),
     (Speedrun, "speedrun"),
+    (LiveSplit, "livesplit"),
     (CurrencyShow, "currency/show"),
     (CurrencyBoost, "currency/boost"),
     (CurrencyWindfall, "currency/windfall"),
     (WaterUndo, "water/undo"),
     (AuthPermit, "auth/permit"),
+    (AuthRoles, "auth/roles"),
+    (AuthExplain, "auth/explain"),
     (ChatBypassUrlWhitelist, "chat/bypass-url-whitelist"),
     (Time, "time"),
     (Poll, "poll"),
+    (Script, "script"),
     (Weather, "weather"),
+    (WeatherAlias, "weather/alias"),
+    (WebRead, "web/read"),
+    (WebCommand, "web/command"),
+    (WebAdmin, "web/admin"),
+    (WebAfterStreams, "web/after-streams"),
+    (WebAlerts, "web/alerts"),
+    (WebClips, "web/clips"),
+    (WebCommands, "web/commands"),
+    (WebCurrency, "web/currency"),
+    (WebGtav, "web/gtav"),
+    (WebProfiles, "web/profiles"),
+    (WebSong, "web/song"),
+}
+
+/// Explanation of why a user is allowed or denied a scope.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Explanation {
+    /// The user being explained.
+    pub user: String,
+    /// The scope being explained.
+    pub scope: Scope,
+    /// How risky the scope is to grant.
+    pub risk: Risk,
+    /// If the user is allowed the scope.
+    pub allowed: bool,
+    /// The roles the user resolves to, including custom and inherited roles.
+    pub roles: Vec<Role>,
+    /// The grants which allow the scope. If empty, the scope is denied.
+    pub grants: Vec<Grant>,
+}
+
+/// A grant which allows a scope.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Grant {
+    /// A permanent grant to a role.
+    Permanent { role: Role },
+    /// A temporary grant to a role or a user.
+    Temporary {
+        principal: RoleOrUser,
+        expires_at: DateTime<Utc>,
+    },
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -634,6 +1231,7 @@ pub struct ScopeData {
 
 roles! {
     (Streamer, "@streamer"),
+    (Owner, "@owner"),
     (Moderator, "@moderator"),
     (Subscriber, "@subscriber"),
     (Vip, "@vip"),
@@ -645,10 +1243,93 @@ pub struct RoleInfo {
     role: Role,
     #[serde(flatten)]
     data: RoleData,
+    /// If this is a custom role.
+    custom: bool,
+    /// Members of the role, only used for custom roles.
+    members: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RoleData {
     /// Documentation for this role.
     pub doc: String,
+    /// Roles whose grants this role inherits.
+    #[serde(default)]
+    pub inherits: Vec<Role>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_roles, CustomRole, Role, RoleData, Schema};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_resolve_roles() {
+        let custom_role = |name: &str| Role::Custom(name.to_string());
+
+        let mut roles = HashMap::new();
+
+        roles.insert(
+            Role::Moderator,
+            RoleData {
+                doc: String::new(),
+                inherits: vec![Role::Vip],
+            },
+        );
+
+        let schema = Schema {
+            roles,
+            scopes: HashMap::new(),
+        };
+
+        let mut custom = HashMap::new();
+
+        custom.insert(
+            custom_role("editor"),
+            CustomRole {
+                doc: String::new(),
+                inherits: vec![Role::Moderator, custom_role("trusted")],
+                members: vec![String::from("setbac")].into_iter().collect(),
+            },
+        );
+
+        custom.insert(
+            custom_role("trusted"),
+            CustomRole {
+                doc: String::new(),
+                inherits: vec![custom_role("editor")],
+                members: Default::default(),
+            },
+        );
+
+        assert_eq!(
+            resolve_roles(&schema, &custom, "setbac", vec![Role::Everyone]),
+            vec![
+                Role::Everyone,
+                custom_role("editor"),
+                Role::Moderator,
+                custom_role("trusted"),
+                Role::Vip,
+            ]
+        );
+
+        assert_eq!(
+            resolve_roles(&schema, &custom, "other", vec![Role::Everyone]),
+            vec![Role::Everyone]
+        );
+
+        custom.insert(
+            custom_role("sneaky"),
+            CustomRole {
+                doc: String::new(),
+                inherits: vec![Role::Streamer, Role::Owner, Role::Vip],
+                members: vec![String::from("other")].into_iter().collect(),
+            },
+        );
+
+        assert_eq!(
+            resolve_roles(&schema, &custom, "other", vec![Role::Everyone]),
+            vec![Role::Everyone, custom_role("sneaky"), Role::Vip]
+        );
+    }
 }
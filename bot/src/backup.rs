@@ -0,0 +1,586 @@
+//! Export and import of the full bot configuration as a single archive.
+//!
+//! The archive is a versioned JSON document, where secret settings can
+//! optionally be encrypted with a passphrase.
+
+use crate::db::{self, models, schema};
+use crate::settings;
+use crate::track_id::TrackId;
+use anyhow::{anyhow, bail, Context as _, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use ring::rand::SecureRandom as _;
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+
+/// The current version of the archive format.
+pub const VERSION: u32 = 1;
+
+/// Prefix of settings which are considered secret.
+const SECRETS_PREFIX: &str = "secrets/";
+/// Number of PBKDF2 iterations used to derive the encryption key.
+const ITERATIONS: u32 = 100_000;
+
+/// A full backup of the bot.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Archive {
+    /// The version of the archive format.
+    pub version: u32,
+    /// The version of the bot which created the archive.
+    pub bot_version: String,
+    /// When the archive was created.
+    pub created_at: DateTime<Utc>,
+    /// All non-secret settings.
+    #[serde(default)]
+    pub settings: BTreeMap<String, serde_json::Value>,
+    /// Secret settings, if included.
+    #[serde(default)]
+    pub secrets: Option<Secrets>,
+    #[serde(default)]
+    pub commands: Vec<models::Command>,
+    #[serde(default)]
+    pub aliases: Vec<models::Alias>,
+    #[serde(default)]
+    pub promotions: Vec<models::Promotion>,
+    #[serde(default)]
+    pub themes: Vec<models::Theme>,
+    #[serde(default)]
+    pub bad_words: Vec<models::BadWord>,
+    #[serde(default)]
+    pub after_streams: Vec<models::AfterStream>,
+    #[serde(default)]
+    pub grants: Vec<Grant>,
+    #[serde(default)]
+    pub initialized_grants: Vec<InitializedGrant>,
+    #[serde(default)]
+    pub custom_roles: Vec<CustomRole>,
+    #[serde(default)]
+    pub role_members: Vec<RoleMember>,
+    #[serde(default)]
+    pub temporary_grants: Vec<TemporaryGrant>,
+    #[serde(default)]
+    pub songs: Vec<Song>,
+    #[serde(default)]
+    pub balances: Vec<models::Balance>,
+}
+
+/// Secret settings in an archive.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum Secrets {
+    /// Secrets stored in plain text.
+    #[serde(rename = "plain")]
+    Plain {
+        settings: BTreeMap<String, serde_json::Value>,
+    },
+    /// Secrets encrypted with a passphrase.
+    #[serde(rename = "encrypted")]
+    Encrypted {
+        salt: String,
+        nonce: String,
+        data: String,
+    },
+}
+
+impl Secrets {
+    /// Encrypt the given settings with the given passphrase.
+    fn encrypt(settings: &BTreeMap<String, serde_json::Value>, passphrase: &str) -> Result<Self> {
+        let rng = ring::rand::SystemRandom::new();
+
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rng.fill(&mut salt)
+            .map_err(|_| anyhow!("failed to generate salt"))?;
+        rng.fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate nonce"))?;
+
+        let key = derive_key(passphrase, &salt)?;
+        let mut data = serde_json::to_vec(settings)?;
+
+        key.seal_in_place_append_tag(
+            ring::aead::Nonce::assume_unique_for_key(nonce),
+            ring::aead::Aad::empty(),
+            &mut data,
+        )
+        .map_err(|_| anyhow!("failed to encrypt secrets"))?;
+
+        Ok(Secrets::Encrypted {
+            salt: base64::encode(&salt),
+            nonce: base64::encode(&nonce),
+            data: base64::encode(&data),
+        })
+    }
+
+    /// Decode the secrets, decrypting them if necessary.
+    fn decode(self, passphrase: Option<&str>) -> Result<BTreeMap<String, serde_json::Value>> {
+        let (salt, nonce, data) = match self {
+            Secrets::Plain { settings } => return Ok(settings),
+            Secrets::Encrypted { salt, nonce, data } => (salt, nonce, data),
+        };
+
+        let passphrase = match passphrase {
+            Some(passphrase) => passphrase,
+            None => bail!("archive contains encrypted secrets, but no passphrase was provided"),
+        };
+
+        let salt = base64::decode(&salt)?;
+        let mut data = base64::decode(&data)?;
+
+        let mut nonce_bytes = [0u8; 12];
+        let nonce = base64::decode(&nonce)?;
+
+        if nonce.len() != nonce_bytes.len() {
+            bail!("bad nonce in archive");
+        }
+
+        nonce_bytes.copy_from_slice(&nonce);
+
+        let key = derive_key(passphrase, &salt)?;
+
+        let plain = key
+            .open_in_place(
+                ring::aead::Nonce::assume_unique_for_key(nonce_bytes),
+                ring::aead::Aad::empty(),
+                &mut data,
+            )
+            .map_err(|_| anyhow!("failed to decrypt secrets, is the passphrase correct?"))?;
+
+        Ok(serde_json::from_slice(plain)?)
+    }
+}
+
+/// Derive an encryption key from the given passphrase.
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<ring::aead::LessSafeKey> {
+    let mut key = [0u8; 32];
+
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(ITERATIONS).expect("iterations must be non-zero"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+
+    let key = ring::aead::UnboundKey::new(&ring::aead::CHACHA20_POLY1305, &key)
+        .map_err(|_| anyhow!("failed to construct encryption key"))?;
+
+    Ok(ring::aead::LessSafeKey::new(key))
+}
+
+/// A permanent grant.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Grant {
+    pub scope: String,
+    pub role: String,
+}
+
+/// A scope whose grants have been initialized from the defaults.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct InitializedGrant {
+    pub scope: String,
+    pub version: String,
+}
+
+/// A custom role.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CustomRole {
+    pub role: String,
+    pub doc: String,
+    /// JSON-encoded list of roles that are inherited.
+    pub inherits: String,
+}
+
+/// A member of a custom role.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct RoleMember {
+    pub role: String,
+    pub user: String,
+}
+
+/// A grant which expires after some time.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TemporaryGrant {
+    pub scope: String,
+    pub principal: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// A song in the queue.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Song {
+    pub track_id: TrackId,
+    pub added_at: NaiveDateTime,
+    #[serde(default)]
+    pub promoted_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub promoted_by: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// Options for exporting an archive.
+#[derive(Debug, Default)]
+pub struct ExportOptions<'a> {
+    /// Include secrets in the archive.
+    pub secrets: bool,
+    /// Encrypt secrets with the given passphrase.
+    pub passphrase: Option<&'a str>,
+}
+
+/// Export everything into an archive.
+pub async fn export(
+    db: &db::Database,
+    settings: &settings::Settings,
+    options: ExportOptions<'_>,
+) -> Result<Archive> {
+    let mut plain = BTreeMap::new();
+    let mut secrets = BTreeMap::new();
+
+    for setting in settings.list().await? {
+        if setting.value.is_null() {
+            continue;
+        }
+
+        if setting.key.starts_with(SECRETS_PREFIX) || setting.schema.secret {
+            secrets.insert(setting.key, setting.value);
+        } else {
+            plain.insert(setting.key, setting.value);
+        }
+    }
+
+    let secrets = match (options.secrets, options.passphrase) {
+        (false, _) => None,
+        (true, Some(passphrase)) => Some(Secrets::encrypt(&secrets, passphrase)?),
+        (true, None) => Some(Secrets::Plain { settings: secrets }),
+    };
+
+    let mut archive = db
+        .asyncify(move |c| {
+            let grants = schema::grants::table
+                .load::<(String, String)>(c)?
+                .into_iter()
+                .map(|(scope, role)| Grant { scope, role })
+                .collect();
+
+            let initialized_grants = schema::initialized_grants::table
+                .load::<(String, String)>(c)?
+                .into_iter()
+                .map(|(scope, version)| InitializedGrant { scope, version })
+                .collect();
+
+            let custom_roles = {
+                use self::schema::custom_roles::dsl;
+
+                dsl::custom_roles
+                    .select((dsl::role, dsl::doc, dsl::inherits))
+                    .load::<(String, String, String)>(c)?
+                    .into_iter()
+                    .map(|(role, doc, inherits)| CustomRole {
+                        role,
+                        doc,
+                        inherits,
+                    })
+                    .collect()
+            };
+
+            let role_members = {
+                use self::schema::role_members::dsl;
+
+                dsl::role_members
+                    .select((dsl::role, dsl::user))
+                    .load::<(String, String)>(c)?
+                    .into_iter()
+                    .map(|(role, user)| RoleMember { role, user })
+                    .collect()
+            };
+
+            let temporary_grants = {
+                use self::schema::temporary_grants::dsl;
+
+                // NB: expired grants are never used again, so leave them out.
+                let now = Utc::now().naive_utc();
+
+                dsl::temporary_grants
+                    .filter(dsl::expires_at.gt(now))
+                    .order(dsl::id.asc())
+                    .select((dsl::scope, dsl::principal, dsl::expires_at))
+                    .load::<(String, String, NaiveDateTime)>(c)?
+                    .into_iter()
+                    .map(|(scope, principal, expires_at)| TemporaryGrant {
+                        scope,
+                        principal,
+                        expires_at,
+                    })
+                    .collect()
+            };
+
+            let songs = {
+                use self::schema::songs::dsl;
+
+                dsl::songs
+                    .filter(dsl::deleted.eq(false))
+                    .order(dsl::added_at.asc())
+                    .load::<models::Song>(c)?
+                    .into_iter()
+                    .map(|s| Song {
+                        track_id: s.track_id,
+                        added_at: s.added_at,
+                        promoted_at: s.promoted_at,
+                        promoted_by: s.promoted_by,
+                        user: s.user,
+                    })
+                    .collect()
+            };
+
+            Ok::<_, anyhow::Error>(Archive {
+                version: VERSION,
+                bot_version: crate::VERSION.to_string(),
+                created_at: Utc::now(),
+                settings: BTreeMap::new(),
+                secrets: None,
+                commands: schema::commands::table.load(c)?,
+                aliases: schema::aliases::table.load(c)?,
+                promotions: schema::promotions::table.load(c)?,
+                themes: schema::themes::table.load(c)?,
+                bad_words: schema::bad_words::table.load(c)?,
+                after_streams: schema::after_streams::table.load(c)?,
+                grants,
+                initialized_grants,
+                custom_roles,
+                role_members,
+                temporary_grants,
+                songs,
+                balances: schema::balances::table.load(c)?,
+            })
+        })
+        .await?;
+
+    archive.settings = plain;
+    archive.secrets = secrets;
+    Ok(archive)
+}
+
+/// Validate the given archive and import it, replacing the current
+/// configuration.
+///
+/// Settings are updated through `Settings`, so that anything subscribed to
+/// them is updated immediately. Settings which are not part of the archive
+/// are left as they are.
+pub async fn import(
+    db: &db::Database,
+    settings: &settings::Settings,
+    archive: Archive,
+    passphrase: Option<&str>,
+    actor: settings::Actor,
+) -> Result<()> {
+    if archive.version > VERSION {
+        bail!(
+            "archive version {} is not supported, expected {} or lower",
+            archive.version,
+            VERSION
+        );
+    }
+
+    let mut values = archive.settings;
+
+    if let Some(secrets) = archive.secrets {
+        values.extend(secrets.decode(passphrase)?);
+    }
+
+    let mut errors = Vec::new();
+
+    for (key, value) in &values {
+        match settings.lookup(key) {
+            Some(schema) if !schema.ty.is_compatible_with_json(value) => {
+                errors.push(format!("`{}` is not a valid {}", key, schema.ty));
+            }
+            Some(..) => (),
+            None => {
+                errors.push(format!("`{}` is not a known setting", key));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("invalid settings in archive: {}", errors.join(", "));
+    }
+
+    let commands = archive.commands;
+    let aliases = archive.aliases;
+    let promotions = archive.promotions;
+    let themes = archive.themes;
+    let bad_words = archive.bad_words;
+    let after_streams = archive.after_streams;
+    let grants = archive.grants;
+    let initialized_grants = archive.initialized_grants;
+    let custom_roles = archive.custom_roles;
+    let role_members = archive.role_members;
+    let temporary_grants = archive.temporary_grants;
+    let songs = archive.songs;
+    let balances = archive.balances;
+
+    db.asyncify(move |c| {
+        c.transaction::<_, anyhow::Error, _>(|| {
+            diesel::delete(schema::commands::table).execute(c)?;
+            diesel::insert_into(schema::commands::table)
+                .values(&commands)
+                .execute(c)?;
+
+            diesel::delete(schema::aliases::table).execute(c)?;
+            diesel::insert_into(schema::aliases::table)
+                .values(&aliases)
+                .execute(c)?;
+
+            diesel::delete(schema::promotions::table).execute(c)?;
+            diesel::insert_into(schema::promotions::table)
+                .values(&promotions)
+                .execute(c)?;
+
+            diesel::delete(schema::themes::table).execute(c)?;
+            diesel::insert_into(schema::themes::table)
+                .values(&themes)
+                .execute(c)?;
+
+            diesel::delete(schema::bad_words::table).execute(c)?;
+            diesel::insert_into(schema::bad_words::table)
+                .values(&bad_words)
+                .execute(c)?;
+
+            diesel::delete(schema::after_streams::table).execute(c)?;
+            diesel::insert_into(schema::after_streams::table)
+                .values(&after_streams)
+                .execute(c)?;
+
+            {
+                use self::schema::grants::dsl;
+
+                diesel::delete(dsl::grants).execute(c)?;
+
+                for g in &grants {
+                    diesel::insert_into(dsl::grants)
+                        .values((dsl::scope.eq(&g.scope), dsl::role.eq(&g.role)))
+                        .execute(c)?;
+                }
+            }
+
+            {
+                use self::schema::initialized_grants::dsl;
+
+                diesel::delete(dsl::initialized_grants).execute(c)?;
+
+                for g in &initialized_grants {
+                    diesel::insert_into(dsl::initialized_grants)
+                        .values((dsl::scope.eq(&g.scope), dsl::version.eq(&g.version)))
+                        .execute(c)?;
+                }
+            }
+
+            {
+                use self::schema::custom_roles::dsl;
+
+                diesel::delete(dsl::custom_roles).execute(c)?;
+
+                for r in &custom_roles {
+                    diesel::insert_into(dsl::custom_roles)
+                        .values((
+                            dsl::role.eq(&r.role),
+                            dsl::doc.eq(&r.doc),
+                            dsl::inherits.eq(&r.inherits),
+                        ))
+                        .execute(c)?;
+                }
+            }
+
+            {
+                use self::schema::role_members::dsl;
+
+                diesel::delete(dsl::role_members).execute(c)?;
+
+                for m in &role_members {
+                    diesel::insert_into(dsl::role_members)
+                        .values((dsl::role.eq(&m.role), dsl::user.eq(&m.user)))
+                        .execute(c)?;
+                }
+            }
+
+            {
+                use self::schema::temporary_grants::dsl;
+
+                diesel::delete(dsl::temporary_grants).execute(c)?;
+
+                for g in &temporary_grants {
+                    diesel::insert_into(dsl::temporary_grants)
+                        .values((
+                            dsl::scope.eq(&g.scope),
+                            dsl::principal.eq(&g.principal),
+                            dsl::expires_at.eq(&g.expires_at),
+                        ))
+                        .execute(c)?;
+                }
+            }
+
+            {
+                use self::schema::songs::dsl;
+
+                diesel::update(dsl::songs.filter(dsl::deleted.eq(false)))
+                    .set(dsl::deleted.eq(true))
+                    .execute(c)?;
+
+                for s in &songs {
+                    diesel::insert_into(dsl::songs)
+                        .values((
+                            dsl::track_id.eq(&s.track_id),
+                            dsl::added_at.eq(&s.added_at),
+                            dsl::promoted_at.eq(&s.promoted_at),
+                            dsl::promoted_by.eq(&s.promoted_by),
+                            dsl::user.eq(&s.user),
+                        ))
+                        .execute(c)?;
+                }
+            }
+
+            diesel::delete(schema::balances::table).execute(c)?;
+            diesel::insert_into(schema::balances::table)
+                .values(&balances)
+                .execute(c)?;
+
+            Ok(())
+        })
+    })
+    .await
+    .context("failed to import tables")?;
+
+    settings
+        .set_all_json_by(values.into_iter().collect(), actor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secrets;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_secrets_roundtrip() {
+        let mut settings = BTreeMap::new();
+        settings.insert(
+            String::from("secrets/oauth2/twitch/connection"),
+            serde_json::json!({"token": "hunter2"}),
+        );
+
+        let secrets = Secrets::encrypt(&settings, "correct horse").unwrap();
+
+        let encoded = serde_json::to_string(&secrets).unwrap();
+        assert!(!encoded.contains("hunter2"));
+
+        let secrets = serde_json::from_str::<Secrets>(&encoded).unwrap();
+        assert_eq!(settings, secrets.decode(Some("correct horse")).unwrap());
+
+        let secrets = serde_json::from_str::<Secrets>(&encoded).unwrap();
+        assert!(secrets.decode(Some("battery staple")).is_err());
+
+        let secrets = serde_json::from_str::<Secrets>(&encoded).unwrap();
+        assert!(secrets.decode(None).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use crate::alerts;
+use crate::api;
+use crate::irc;
+use crate::module;
 use crate::player;
 use crate::track_id::TrackId;
 use std::collections::HashMap;
@@ -145,6 +149,105 @@ pub enum Global {
     },
     #[serde(rename = "song/modified")]
     SongModified,
+    /// Something happened in the channel, like a subscription or a raid.
+    #[serde(rename = "channel/event")]
+    ChannelEvent { event: irc::ChannelEvent },
+    /// An alert to show on the overlay for `duration` seconds.
+    #[serde(rename = "alert")]
+    Alert {
+        kind: alerts::AlertKind,
+        text: String,
+        name: String,
+        amount: Option<u64>,
+        message: Option<String>,
+        duration: u64,
+        sound: Option<String>,
+        image: Option<String>,
+    },
+    /// Pending and active GTA V effects.
+    #[serde(rename = "gtav/queue")]
+    GtavQueue {
+        pending: Vec<GtavEffect>,
+        active: Vec<GtavEffect>,
+    },
+    /// All running countdowns and stopwatches.
+    #[serde(rename = "countdown/timers")]
+    Countdowns { timers: Vec<Countdown> },
+    /// The state of the LiveSplit timer changed, like when a split happens.
+    #[serde(rename = "livesplit/split")]
+    LiveSplit {
+        phase: api::livesplit::TimerPhase,
+        split_index: Option<usize>,
+        split_name: Option<String>,
+        previous_split_name: Option<String>,
+        last_split_time: Option<String>,
+        delta: Option<String>,
+    },
+    /// A new personal best or world record on speedrun.com, to show on the
+    /// overlay for `duration` seconds.
+    #[serde(rename = "speedrun/record")]
+    SpeedrunRecord {
+        kind: module::speedrun::RecordKind,
+        text: String,
+        runner: String,
+        game: String,
+        category: String,
+        time: String,
+        place: u32,
+        weblink: String,
+        duration: u64,
+    },
+    /// A reminder to drink water or take a break, to show on the overlay for
+    /// `duration` seconds.
+    #[serde(rename = "water/reminder")]
+    Reminder {
+        kind: module::water::ReminderKind,
+        text: String,
+        duration: u64,
+    },
+}
+
+/// A single GTA V effect, as shown on the overlay.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GtavEffect {
+    pub id: usize,
+    /// Display name of the user who bought the effect.
+    pub user: String,
+    /// The name of the effect, like `SuperSpeed`.
+    pub effect: String,
+    /// Human readable description of the effect.
+    pub description: String,
+    pub cost: u32,
+    /// How long the effect lasts in seconds, if it's timed.
+    pub duration: Option<u64>,
+    /// How many seconds remain of an active effect.
+    pub remaining: Option<u64>,
+}
+
+/// The kind of a timer shown on the overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CountdownKind {
+    Countdown,
+    Stopwatch,
+}
+
+/// A single countdown or stopwatch, as shown on the overlay.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Countdown {
+    pub name: String,
+    pub kind: CountdownKind,
+    /// The rendered template of the timer, or the remaining or elapsed time
+    /// if it doesn't have one.
+    pub text: String,
+    /// Elapsed time in seconds.
+    pub elapsed: u64,
+    /// Duration of a countdown in seconds.
+    pub duration: Option<u64>,
+    /// Remaining time of a countdown in seconds.
+    pub remaining: Option<u64>,
+    /// If the timer is running, stopwatches can be stopped.
+    pub running: bool,
 }
 
 impl Message for Global {
@@ -155,6 +258,9 @@ impl Message for Global {
         match *self {
             SongProgress { .. } => Some("song/progress"),
             SongCurrent { .. } => Some("song/current"),
+            GtavQueue { .. } => Some("gtav/queue"),
+            LiveSplit { .. } => Some("livesplit/split"),
+            Countdowns { .. } => Some("countdown/timers"),
             _ => None,
         }
     }
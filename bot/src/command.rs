@@ -3,6 +3,7 @@
 use crate::auth::Scope;
 use crate::irc;
 use crate::prelude::*;
+use crate::settings;
 use crate::utils;
 use anyhow::{bail, Result};
 use std::borrow::Cow;
@@ -89,6 +90,14 @@ impl Context {
         self.inner.sender.channel()
     }
 
+    /// The settings actor corresponding to the user of the command.
+    pub fn actor(&self) -> settings::Actor {
+        match self.user.name() {
+            Some(name) => settings::Actor::Chat(name.to_string()),
+            None => settings::Actor::Bot,
+        }
+    }
+
     /// Signal that the bot should try to shut down.
     pub async fn shutdown(&self) -> bool {
         self.inner.shutdown.shutdown().await
@@ -172,7 +181,7 @@ impl Context {
 
     /// Send a privmsg to the channel.
     pub async fn privmsg(&self, m: impl fmt::Display) {
-        self.inner.sender.privmsg(m).await;
+        self.user.sender().privmsg(m).await;
     }
 
     /// Get the next argument.
@@ -0,0 +1,223 @@
+//! Headless configuration through a settings file.
+//!
+//! The file is an overlay of settings in TOML or YAML, which is applied on top
+//! of the settings stored in the database. Keys can either be nested tables, or
+//! full setting keys like `song/enabled`:
+//!
+//! ```toml
+//! [song]
+//! enabled = true
+//! "spotify/max-duration" = "10m"
+//! ```
+//!
+//! Settings provided by the file are read-only everywhere else, and the file is
+//! watched for changes.
+
+use crate::settings;
+use anyhow::{anyhow, bail, Context as _, Result};
+use std::path::{Path, PathBuf};
+use std::time;
+
+/// How frequently the configuration file is checked for modifications.
+const POLL_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
+/// Load all settings from the given configuration file, validating them
+/// against the settings schema.
+pub fn load(
+    path: &Path,
+    settings: &settings::Settings,
+) -> Result<Vec<(String, serde_json::Value)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| anyhow!("failed to read configuration file: {}", path.display()))?;
+
+    let toml = path.extension().and_then(|e| e.to_str()) == Some("toml");
+    load_str(&content, toml, settings.schema())
+}
+
+/// Load all settings from the given content of a configuration file, which is
+/// either TOML or YAML.
+fn load_str(
+    content: &str,
+    toml: bool,
+    schema: &settings::Schema,
+) -> Result<Vec<(String, serde_json::Value)>> {
+    // NB: an empty YAML document is an error, but should provide no settings.
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let value = if toml {
+        toml::from_str::<serde_json::Value>(content)?
+    } else {
+        serde_yaml::from_str::<serde_json::Value>(content)?
+    };
+
+    let mut out = Vec::new();
+
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                flatten(schema, key, value, &mut out)?;
+            }
+        }
+        serde_json::Value::Null => (),
+        _ => bail!("expected a table of settings"),
+    }
+
+    Ok(out)
+}
+
+/// Flatten the given value into settings, treating nested tables as key
+/// prefixes unless they match a setting.
+fn flatten(
+    schema: &settings::Schema,
+    key: String,
+    value: serde_json::Value,
+    out: &mut Vec<(String, serde_json::Value)>,
+) -> Result<()> {
+    let key = key.trim_matches('/').to_string();
+
+    if let Some(schema) = schema.lookup(&key) {
+        let value = parse(&schema.ty, &value)
+            .with_context(|| anyhow!("bad value for `{}`, expected a {}", key, schema.ty))?;
+        out.push((key, value));
+        return Ok(());
+    }
+
+    match value {
+        serde_json::Value::Object(object) => {
+            for (child, value) in object {
+                flatten(schema, format!("{}/{}", key, child), value, out)?;
+            }
+
+            Ok(())
+        }
+        _ => bail!("no such setting: {}", key),
+    }
+}
+
+/// Parse the given value from the configuration file as the given type.
+///
+/// Scalars are parsed the same way as values provided in chat, so durations
+/// like `"10m"` work. Anything else must already be compatible with the type.
+fn parse(ty: &settings::Type, value: &serde_json::Value) -> Result<serde_json::Value> {
+    let text = match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => serde_json::to_string(other)?,
+    };
+
+    match ty.parse_as_json(&text) {
+        Ok(value) => Ok(value),
+        Err(_) if ty.is_compatible_with_json(value) => Ok(value.clone()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Watch the configuration file for modifications, applying it to settings
+/// whenever it changes.
+///
+/// A file which fails to load or apply is reported, and leaves the settings as
+/// they were.
+pub async fn watch(path: PathBuf, settings: settings::Settings) -> Result<()> {
+    let mut last_modified = modified(&path);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current = modified(&path);
+
+        if current == last_modified {
+            continue;
+        }
+
+        last_modified = current;
+
+        let values = match load(&path, &settings) {
+            Ok(values) => values,
+            Err(e) => {
+                log_error!(e, "Failed to reload configuration file: {}", path.display());
+                continue;
+            }
+        };
+
+        if let Err(e) = settings.set_config_file(values).await {
+            log_error!(e, "Failed to apply configuration file: {}", path.display());
+            continue;
+        }
+
+        log::info!("Reloaded configuration file: {}", path.display());
+    }
+}
+
+/// Get the modification time of the given file.
+fn modified(path: &Path) -> Option<time::SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_str;
+    use crate::settings::Schema;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_toml() {
+        let schema = Schema::load_static().unwrap();
+
+        let mut values = load_str(
+            r#"
+            "song/request-reward" = 10
+
+            [song]
+            enabled = true
+            "spotify/max-duration" = "10m"
+            "#,
+            true,
+            &schema,
+        )
+        .unwrap();
+
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            vec![
+                (String::from("song/enabled"), json!(true)),
+                (String::from("song/request-reward"), json!(10)),
+                (String::from("song/spotify/max-duration"), json!("10m")),
+            ],
+            values
+        );
+    }
+
+    #[test]
+    fn test_load_yaml() {
+        let schema = Schema::load_static().unwrap();
+
+        let values = load_str(
+            "song:\n  enabled: \"true\"\n  request-reward: \"10\"\n",
+            false,
+            &schema,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                (String::from("song/enabled"), json!(true)),
+                (String::from("song/request-reward"), json!(10)),
+            ],
+            values
+        );
+
+        assert!(load_str("", false, &schema).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let schema = Schema::load_static().unwrap();
+
+        assert!(load_str("[song]\ndoes-not-exist = true\n", true, &schema).is_err());
+        assert!(load_str("[song]\nenabled = \"maybe\"\n", true, &schema).is_err());
+        assert!(load_str("- song/enabled\n", false, &schema).is_err());
+    }
+}
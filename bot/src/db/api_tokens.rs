@@ -0,0 +1,199 @@
+use crate::auth::Scope;
+use crate::db::{self, schema::api_tokens};
+use anyhow::{bail, Error};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Number of random bytes in a generated token.
+const TOKEN_BYTES: usize = 32;
+
+/// A token which grants access to the web API.
+///
+/// The token itself is only available when it's created, after that only a
+/// hash of it is stored.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiToken {
+    /// The name of the token.
+    pub name: String,
+    /// The scopes the token grants access to.
+    pub scopes: Vec<Scope>,
+    /// When the token was created.
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(diesel::Queryable)]
+struct Row {
+    name: String,
+    token_hash: String,
+    scopes: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(diesel::Insertable)]
+#[table_name = "api_tokens"]
+struct InsertRow {
+    name: String,
+    token_hash: String,
+    scopes: String,
+}
+
+/// Tokens used to access the web API.
+#[derive(Clone)]
+pub struct ApiTokens {
+    db: db::Database,
+    /// Scopes of every token, by the hash of the token.
+    scopes: Arc<RwLock<HashMap<String, Vec<Scope>>>>,
+}
+
+impl ApiTokens {
+    /// Load all tokens from the database.
+    pub async fn load(db: db::Database) -> Result<Self, Error> {
+        let tokens = ApiTokens {
+            db,
+            scopes: Default::default(),
+        };
+
+        let mut scopes = HashMap::new();
+
+        for row in tokens.rows().await? {
+            scopes.insert(row.token_hash, serde_json::from_str(&row.scopes)?);
+        }
+
+        *tokens.scopes.write().await = scopes;
+        Ok(tokens)
+    }
+
+    /// List all tokens.
+    pub async fn list(&self) -> Result<Vec<ApiToken>, Error> {
+        let mut out = Vec::new();
+
+        for row in self.rows().await? {
+            out.push(ApiToken {
+                name: row.name,
+                scopes: serde_json::from_str(&row.scopes)?,
+                created_at: DateTime::from_utc(row.created_at, Utc),
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Create a new token with the given name and scopes, returning the
+    /// token.
+    pub async fn create(&self, name: &str, scopes: Vec<Scope>) -> Result<String, Error> {
+        use self::api_tokens::dsl;
+
+        if name.trim().is_empty() {
+            bail!("token name must not be empty");
+        }
+
+        let token = generate()?;
+        let token_hash = hash(&token);
+
+        let row = InsertRow {
+            name: name.to_string(),
+            token_hash: token_hash.clone(),
+            scopes: serde_json::to_string(&scopes)?,
+        };
+
+        self.db
+            .asyncify(move |c| {
+                let existing = dsl::api_tokens
+                    .filter(dsl::name.eq(&row.name))
+                    .count()
+                    .get_result::<i64>(c)?;
+
+                if existing > 0 {
+                    bail!("a token named `{}` already exists", row.name);
+                }
+
+                diesel::insert_into(dsl::api_tokens)
+                    .values(&row)
+                    .execute(c)?;
+                Ok(())
+            })
+            .await?;
+
+        self.scopes.write().await.insert(token_hash, scopes);
+        Ok(token)
+    }
+
+    /// Delete the token with the given name.
+    ///
+    /// Returns `false` if there is no such token.
+    pub async fn delete(&self, name: &str) -> Result<bool, Error> {
+        use self::api_tokens::dsl;
+
+        let name = name.to_string();
+
+        let token_hash = self
+            .db
+            .asyncify(move |c| {
+                let filter = dsl::api_tokens.filter(dsl::name.eq(&name));
+
+                let token_hash = filter
+                    .clone()
+                    .select(dsl::token_hash)
+                    .first::<String>(c)
+                    .optional()?;
+
+                diesel::delete(filter).execute(c)?;
+                Ok::<_, Error>(token_hash)
+            })
+            .await?;
+
+        match token_hash {
+            Some(token_hash) => {
+                self.scopes.write().await.remove(&token_hash);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Get the scopes granted by the given token, or `None` if it isn't a
+    /// valid token.
+    pub async fn scopes(&self, token: &str) -> Option<Vec<Scope>> {
+        self.scopes.read().await.get(&hash(token)).cloned()
+    }
+
+    /// Test if any token grants the given scope.
+    pub async fn any_with_scope(&self, scope: Scope) -> bool {
+        self.scopes
+            .read()
+            .await
+            .values()
+            .any(|s| s.contains(&scope))
+    }
+
+    /// Load all rows from the database.
+    async fn rows(&self) -> Result<Vec<Row>, Error> {
+        use self::api_tokens::dsl;
+
+        self.db
+            .asyncify(move |c| Ok(dsl::api_tokens.order(dsl::name).load::<Row>(c)?))
+            .await
+    }
+}
+
+/// Generate a new random token.
+fn generate() -> Result<String, Error> {
+    use ring::rand::SecureRandom as _;
+
+    let mut bytes = [0u8; TOKEN_BYTES];
+
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow::anyhow!("failed to generate token"))?;
+
+    Ok(base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD))
+}
+
+/// Hash the given token for storage.
+fn hash(token: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, token.as_bytes());
+    base64::encode(digest.as_ref())
+}
@@ -0,0 +1,194 @@
+use crate::db::{self, models, schema};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+pub use self::models::ChatMessage;
+
+/// Durable log of chat messages.
+#[derive(Clone)]
+pub struct ChatMessages {
+    db: db::Database,
+}
+
+impl ChatMessages {
+    /// Open the chat messages database.
+    pub async fn load(db: db::Database) -> Result<Self, anyhow::Error> {
+        Ok(ChatMessages { db })
+    }
+
+    /// Store the given message.
+    pub async fn push(
+        &self,
+        id: &str,
+        channel: &str,
+        user: &str,
+        display_name: &str,
+        text: &str,
+    ) -> Result<(), anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let message = models::InsertChatMessage {
+            id: id.to_string(),
+            channel: channel.to_string(),
+            user: user.to_string(),
+            display_name: display_name.to_string(),
+            text: text.to_string(),
+        };
+
+        self.db
+            .asyncify(move |c| {
+                diesel::insert_or_ignore_into(dsl::chat_messages)
+                    .values(&message)
+                    .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Mark the message with the given id as deleted.
+    pub async fn delete_by_id(&self, id: &str) -> Result<(), anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let id = id.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(dsl::chat_messages.filter(dsl::id.eq(&id)))
+                    .set(dsl::deleted.eq(true))
+                    .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Mark all messages by the given user in the given channel as deleted.
+    pub async fn delete_by_user(&self, channel: &str, user: &str) -> Result<(), anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let channel = channel.to_string();
+        let user = db::user_id(user);
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(
+                    dsl::chat_messages.filter(dsl::channel.eq(&channel).and(dsl::user.eq(&user))),
+                )
+                .set(dsl::deleted.eq(true))
+                .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Mark all messages in the given channel as deleted.
+    pub async fn delete_by_channel(&self, channel: &str) -> Result<(), anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let channel = channel.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::update(dsl::chat_messages.filter(dsl::channel.eq(&channel)))
+                    .set(dsl::deleted.eq(true))
+                    .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Search for messages, optionally by channel, by user, and by a phrase
+    /// contained in the message.
+    ///
+    /// The newest `limit` matching messages are returned, oldest first.
+    pub async fn search(
+        &self,
+        channel: Option<&str>,
+        user: Option<&str>,
+        phrase: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ChatMessage>, anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let channel = channel.map(String::from);
+        let user = user.map(db::user_id);
+        let phrase = phrase.map(|p| format!("%{}%", escape_like(p)));
+
+        self.db
+            .asyncify(move |c| {
+                let mut query = dsl::chat_messages.into_boxed();
+
+                if let Some(channel) = channel {
+                    query = query.filter(dsl::channel.eq(channel));
+                }
+
+                if let Some(user) = user {
+                    query = query.filter(dsl::user.eq(user));
+                }
+
+                if let Some(phrase) = phrase {
+                    query = query.filter(dsl::text.like(phrase).escape('\\'));
+                }
+
+                let mut messages = query
+                    .order(dsl::added_at.desc())
+                    .limit(limit)
+                    .load::<models::ChatMessage>(c)?;
+
+                messages.reverse();
+                Ok(messages)
+            })
+            .await
+    }
+
+    /// Get the last `limit` messages by the given user, oldest first.
+    pub async fn last_by_user(
+        &self,
+        user: &str,
+        limit: i64,
+    ) -> Result<Vec<ChatMessage>, anyhow::Error> {
+        self.search(None, Some(user), None, limit).await
+    }
+
+    /// Remove all messages older than the given timestamp, returning the
+    /// number of messages removed.
+    pub async fn purge_before(&self, before: DateTime<Utc>) -> Result<usize, anyhow::Error> {
+        use self::schema::chat_messages::dsl;
+
+        let before = before.naive_utc();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(
+                    diesel::delete(dsl::chat_messages.filter(dsl::added_at.lt(&before)))
+                        .execute(c)?,
+                )
+            })
+            .await
+    }
+}
+
+/// Escape the special characters of a LIKE pattern.
+fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        if let '%' | '_' | '\\' = c {
+            out.push('\\');
+        }
+
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_like;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!("hello", escape_like("hello"));
+        assert_eq!("100\\% \\_ \\\\", escape_like("100% _ \\"));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::db::{self, models, schema};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+pub use self::models::Clip;
+
+/// Clips created through the `!clip` command.
+#[derive(Clone)]
+pub struct Clips {
+    db: db::Database,
+}
+
+impl Clips {
+    /// Open the clips database.
+    pub async fn load(db: db::Database) -> Result<Self, anyhow::Error> {
+        Ok(Clips { db })
+    }
+
+    /// Record the given clip.
+    pub async fn push(
+        &self,
+        channel: &str,
+        clip_id: &str,
+        user: &str,
+        title: Option<&str>,
+        game: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        use self::schema::clips::dsl;
+
+        let clip = models::InsertClip {
+            channel: channel.to_string(),
+            clip_id: clip_id.to_string(),
+            user: user.to_string(),
+            title: title.map(str::to_string),
+            game: game.map(str::to_string),
+        };
+
+        self.db
+            .asyncify(move |c| {
+                diesel::insert_into(dsl::clips).values(&clip).execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Delete the clip with the given id.
+    pub async fn delete(&self, id: i32) -> Result<bool, anyhow::Error> {
+        use self::schema::clips::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(dsl::clips.filter(dsl::id.eq(id))).execute(c)?;
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// List the last `limit` clips, newest first.
+    pub async fn list(&self, limit: i64) -> Result<Vec<Clip>, anyhow::Error> {
+        use self::schema::clips::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::clips
+                    .order(dsl::created_at.desc())
+                    .limit(limit)
+                    .load::<models::Clip>(c)?)
+            })
+            .await
+    }
+
+    /// List the last `limit` clips in the given channel, newest first.
+    pub async fn list_by_channel(
+        &self,
+        channel: &str,
+        limit: i64,
+    ) -> Result<Vec<Clip>, anyhow::Error> {
+        use self::schema::clips::dsl;
+
+        let channel = channel.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::clips
+                    .filter(dsl::channel.eq(&channel))
+                    .order(dsl::created_at.desc())
+                    .limit(limit)
+                    .load::<models::Clip>(c)?)
+            })
+            .await
+    }
+
+    /// List all clips in the given channel created since the given time,
+    /// oldest first.
+    pub async fn list_since(
+        &self,
+        channel: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Clip>, anyhow::Error> {
+        use self::schema::clips::dsl;
+
+        let channel = channel.to_string();
+        let since = since.naive_utc();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::clips
+                    .filter(dsl::channel.eq(&channel).and(dsl::created_at.ge(&since)))
+                    .order(dsl::created_at.asc())
+                    .load::<models::Clip>(c)?)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Clips;
+    use crate::db;
+    use chrono::{Duration, Utc};
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_clips() -> Result<(), anyhow::Error> {
+        let db = db::Database::open(Path::new(":memory:"))?;
+        let clips = Clips::load(db).await?;
+
+        clips
+            .push("#setbac", "First", "udoprog", Some("Nice jump"), None)
+            .await?;
+        clips
+            .push("#setbac", "Second", "setbac", None, Some("Minecraft"))
+            .await?;
+        clips.push("#other", "Third", "udoprog", None, None).await?;
+
+        assert_eq!(clips.list(10).await?.len(), 3);
+        assert_eq!(clips.list(2).await?.len(), 2);
+
+        let setbac = clips.list_by_channel("#setbac", 10).await?;
+        assert_eq!(setbac.len(), 2);
+        assert!(setbac.iter().all(|c| c.channel == "#setbac"));
+
+        let first = setbac.iter().find(|c| c.clip_id == "First").unwrap();
+        assert_eq!(first.title.as_deref(), Some("Nice jump"));
+        assert_eq!(first.game, None);
+
+        let since = clips
+            .list_since("#setbac", Utc::now() - Duration::minutes(1))
+            .await?;
+        assert_eq!(since.len(), 2);
+
+        let since = clips
+            .list_since("#setbac", Utc::now() + Duration::minutes(1))
+            .await?;
+        assert!(since.is_empty());
+
+        assert!(clips.delete(first.id).await?);
+        assert!(!clips.delete(first.id).await?);
+        assert_eq!(clips.list_by_channel("#setbac", 10).await?.len(), 1);
+        Ok(())
+    }
+}
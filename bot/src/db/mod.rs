@@ -2,12 +2,16 @@
 mod macros;
 mod after_streams;
 mod aliases;
+mod api_tokens;
+mod chat_messages;
+mod clips;
 pub(crate) mod commands;
 mod matcher;
 pub(crate) mod models;
 mod promotions;
 pub(crate) mod schema;
 mod themes;
+mod user_time_zones;
 mod words;
 
 use crate::task;
@@ -19,10 +23,14 @@ use thiserror::Error;
 
 pub use self::after_streams::{AfterStream, AfterStreams};
 pub use self::aliases::{Alias, Aliases};
+pub use self::api_tokens::{ApiToken, ApiTokens};
+pub use self::chat_messages::{ChatMessage, ChatMessages};
+pub use self::clips::{Clip, Clips};
 pub use self::commands::{Command, Commands};
 pub use self::matcher::Captures;
 pub use self::promotions::{Promotion, Promotions};
 pub use self::themes::{Theme, Themes};
+pub use self::user_time_zones::UserTimeZones;
 pub use self::words::{Word, Words};
 
 pub use self::matcher::Key;
@@ -1,5 +1,6 @@
 use super::schema::{
-    after_streams, aliases, bad_words, balances, commands, promotions, songs, themes,
+    after_streams, aliases, bad_words, balances, chat_messages, clips, commands, promotions,
+    settings_history, songs, themes,
 };
 use crate::track_id::TrackId;
 use chrono::NaiveDateTime;
@@ -26,7 +27,17 @@ impl Balance {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, diesel::Queryable, diesel::Insertable)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    diesel::Queryable,
+    diesel::Insertable,
+)]
 pub struct Command {
     /// The channel the command belongs to.
     pub channel: String,
@@ -53,7 +64,17 @@ pub struct UpdateCommand<'a> {
     pub disabled: Option<bool>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, diesel::Queryable, diesel::Insertable)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    diesel::Queryable,
+    diesel::Insertable,
+)]
 #[table_name = "aliases"]
 pub struct Alias {
     /// The channel the alias belongs to.
@@ -86,7 +107,10 @@ pub struct UpdateAlias<'a> {
     pub disabled: Option<bool>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, diesel::Queryable)]
+#[derive(
+    Debug, Clone, serde::Serialize, serde::Deserialize, diesel::Queryable, diesel::Insertable,
+)]
+#[table_name = "after_streams"]
 pub struct AfterStream {
     /// The unique identifier of the afterstream message.
     pub id: i32,
@@ -109,7 +133,101 @@ pub struct InsertAfterStream {
     pub text: String,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, diesel::Queryable, diesel::Insertable)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, diesel::Queryable)]
+pub struct ChatMessage {
+    /// The unique identifier of the message, as assigned by Twitch.
+    pub id: String,
+    /// The channel the message was sent in.
+    pub channel: String,
+    /// When the message was received.
+    pub added_at: NaiveDateTime,
+    /// The login name of the user who sent the message.
+    pub user: String,
+    /// The display name of the user who sent the message.
+    pub display_name: String,
+    /// The text of the message.
+    pub text: String,
+    /// If the message has been deleted by a moderator.
+    pub deleted: bool,
+}
+
+#[derive(diesel::Insertable)]
+#[table_name = "chat_messages"]
+pub struct InsertChatMessage {
+    pub id: String,
+    pub channel: String,
+    pub user: String,
+    pub display_name: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, diesel::Queryable)]
+pub struct Clip {
+    /// The unique identifier of the clip in the database.
+    pub id: i32,
+    /// The channel the clip was created in.
+    pub channel: String,
+    /// The identifier of the clip, as assigned by Twitch.
+    pub clip_id: String,
+    /// The user that requested the clip.
+    pub user: String,
+    /// The title requested for the clip, if any.
+    pub title: Option<String>,
+    /// The game being played when the clip was created.
+    pub game: Option<String>,
+    /// When the clip was created.
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(diesel::Insertable)]
+#[table_name = "clips"]
+pub struct InsertClip {
+    pub channel: String,
+    pub clip_id: String,
+    pub user: String,
+    pub title: Option<String>,
+    pub game: Option<String>,
+}
+
+#[derive(Debug, Clone, diesel::Queryable)]
+pub struct SettingsChange {
+    /// The unique identifier of the change.
+    pub id: i32,
+    /// The key of the setting that was changed.
+    pub key: String,
+    /// The JSON value before the change, if the setting was set.
+    pub old_value: Option<String>,
+    /// The JSON value after the change, if the setting is still set.
+    pub new_value: Option<String>,
+    /// When the change happened.
+    pub changed_at: NaiveDateTime,
+    /// The kind of actor that performed the change.
+    pub actor: String,
+    /// The name of the actor that performed the change, if known.
+    pub actor_name: Option<String>,
+}
+
+#[derive(diesel::Insertable)]
+#[table_name = "settings_history"]
+pub struct InsertSettingsChange {
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub actor: String,
+    pub actor_name: Option<String>,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    diesel::Queryable,
+    diesel::Insertable,
+)]
 pub struct BadWord {
     pub word: String,
     pub why: Option<String>,
@@ -144,7 +262,17 @@ pub struct AddSong {
     pub user: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, diesel::Queryable, diesel::Insertable)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    diesel::Queryable,
+    diesel::Insertable,
+)]
 pub struct Promotion {
     /// The channel the promotion belongs to.
     pub channel: String,
@@ -172,7 +300,17 @@ pub struct UpdatePromotion<'a> {
     pub disabled: Option<bool>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, diesel::Queryable, diesel::Insertable)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    diesel::Queryable,
+    diesel::Insertable,
+)]
 pub struct Theme {
     /// The channel the theme belongs to.
     pub channel: String,
@@ -29,6 +29,18 @@ table! {
     }
 }
 
+table! {
+    chat_messages (id) {
+        id -> Text,
+        channel -> Text,
+        added_at -> Timestamp,
+        user -> Text,
+        display_name -> Text,
+        text -> Text,
+        deleted -> Bool,
+    }
+}
+
 table! {
     bad_words (word) {
         word -> Text,
@@ -90,6 +102,47 @@ table! {
     }
 }
 
+table! {
+    api_tokens (name) {
+        name -> Text,
+        token_hash -> Text,
+        scopes -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    scheduled_settings (id) {
+        id -> Integer,
+        key -> Text,
+        value -> Nullable<Text>,
+        run_at -> Timestamp,
+        revert_after -> Nullable<Text>,
+        actor -> Text,
+        is_revert -> Bool,
+    }
+}
+
+table! {
+    settings_history (id) {
+        id -> Integer,
+        key -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        changed_at -> Timestamp,
+        actor -> Text,
+        actor_name -> Nullable<Text>,
+    }
+}
+
+table! {
+    settings_profiles (name) {
+        name -> Text,
+        game -> Nullable<Text>,
+        settings -> Text,
+    }
+}
+
 // Grants that have been initialized from their default configuration.
 table! {
     initialized_grants (scope) {
@@ -105,3 +158,52 @@ table! {
         role -> Text,
     }
 }
+
+// Roles defined by the streamer, in addition to the ones in auth.yaml.
+table! {
+    custom_roles (role) {
+        role -> Text,
+        doc -> Text,
+        inherits -> Text,
+    }
+}
+
+// Members of custom roles.
+table! {
+    role_members (role, user) {
+        role -> Text,
+        user -> Text,
+    }
+}
+
+// Grants which expire after some time.
+table! {
+    temporary_grants (id) {
+        id -> Integer,
+        scope -> Text,
+        principal -> Text,
+        expires_at -> Timestamp,
+    }
+}
+
+// Clips created through the `!clip` command.
+table! {
+    clips (id) {
+        id -> Integer,
+        channel -> Text,
+        clip_id -> Text,
+        user -> Text,
+        title -> Nullable<Text>,
+        game -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+// Time zones set by viewers through `!time set`.
+table! {
+    user_time_zones (channel, user) {
+        channel -> Text,
+        user -> Text,
+        time_zone -> Text,
+    }
+}
@@ -0,0 +1,79 @@
+use crate::db::{self, schema};
+use diesel::prelude::*;
+
+/// Time zones set by viewers through `!time set`.
+#[derive(Clone)]
+pub struct UserTimeZones {
+    db: db::Database,
+}
+
+impl UserTimeZones {
+    /// Open the user time zones database.
+    pub async fn load(db: db::Database) -> Result<Self, anyhow::Error> {
+        Ok(UserTimeZones { db })
+    }
+
+    /// Get the time zone of the given user.
+    pub async fn get(&self, channel: &str, user: &str) -> Result<Option<String>, anyhow::Error> {
+        use self::schema::user_time_zones::dsl;
+
+        let channel = channel.to_string();
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                Ok(dsl::user_time_zones
+                    .select(dsl::time_zone)
+                    .filter(dsl::channel.eq(&channel).and(dsl::user.eq(&user)))
+                    .first::<String>(c)
+                    .optional()?)
+            })
+            .await
+    }
+
+    /// Set the time zone of the given user.
+    pub async fn set(
+        &self,
+        channel: &str,
+        user: &str,
+        time_zone: &str,
+    ) -> Result<(), anyhow::Error> {
+        use self::schema::user_time_zones::dsl;
+
+        let channel = channel.to_string();
+        let user = user.to_string();
+        let time_zone = time_zone.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                diesel::replace_into(dsl::user_time_zones)
+                    .values((
+                        dsl::channel.eq(channel),
+                        dsl::user.eq(user),
+                        dsl::time_zone.eq(time_zone),
+                    ))
+                    .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Clear the time zone of the given user.
+    ///
+    /// Returns `true` if the user had a time zone set.
+    pub async fn clear(&self, channel: &str, user: &str) -> Result<bool, anyhow::Error> {
+        use self::schema::user_time_zones::dsl;
+
+        let channel = channel.to_string();
+        let user = user.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                let filter =
+                    dsl::user_time_zones.filter(dsl::channel.eq(&channel).and(dsl::user.eq(&user)));
+                let count = diesel::delete(filter).execute(c)?;
+                Ok(count == 1)
+            })
+            .await
+    }
+}
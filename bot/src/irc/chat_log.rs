@@ -1,4 +1,5 @@
 use crate::api::{twitch::Channel, Twitch};
+use crate::db;
 use crate::emotes;
 use crate::injector;
 use crate::irc;
@@ -9,6 +10,7 @@ use anyhow::Result;
 
 pub struct Builder {
     twitch: Twitch,
+    chat_channel: String,
     pub(crate) message_log: message_log::MessageLog,
     pub(crate) cache_stream: injector::Stream<Cache>,
     pub(crate) cache: Option<Cache>,
@@ -16,11 +18,16 @@ pub struct Builder {
     pub(crate) enabled: bool,
     pub(crate) emotes_enabled_stream: settings::Stream<bool>,
     pub(crate) emotes_enabled: bool,
+    pub(crate) persist_stream: settings::Stream<bool>,
+    pub(crate) persist: bool,
+    pub(crate) chat_messages_stream: injector::Stream<db::ChatMessages>,
+    pub(crate) chat_messages: Option<db::ChatMessages>,
 }
 
 impl Builder {
     pub async fn new(
         twitch: Twitch,
+        chat_channel: String,
         injector: &injector::Injector,
         message_log: message_log::MessageLog,
         settings: settings::Settings,
@@ -32,10 +39,14 @@ impl Builder {
         let (emotes_enabled_stream, emotes_enabled) =
             settings.stream("emotes-enabled").or_default().await?;
 
+        let (persist_stream, persist) = settings.stream("persist").or_default().await?;
+        let (chat_messages_stream, chat_messages) = injector.stream::<db::ChatMessages>().await;
+
         message_log.enabled(enabled).await;
 
         Ok(Self {
             twitch,
+            chat_channel,
             message_log,
             cache_stream,
             cache,
@@ -43,6 +54,10 @@ impl Builder {
             enabled,
             emotes_enabled_stream,
             emotes_enabled,
+            persist_stream,
+            persist,
+            chat_messages_stream,
+            chat_messages,
         })
     }
 
@@ -57,9 +72,16 @@ impl Builder {
             _ => None,
         };
 
+        let chat_messages = match self.persist {
+            true => self.chat_messages.clone(),
+            false => None,
+        };
+
         Ok(Some(ChatLog {
+            chat_channel: self.chat_channel.clone(),
             message_log: self.message_log.clone(),
             emotes,
+            chat_messages,
         }))
     }
 }
@@ -70,10 +92,25 @@ pub struct ChatLog {
     pub message_log: message_log::MessageLog,
     /// Handler of emotes.
     emotes: Option<emotes::Emotes>,
+    /// The streamer's channel.
+    chat_channel: String,
+    /// Durable log of messages, if persisting messages is enabled.
+    chat_messages: Option<db::ChatMessages>,
 }
 
 impl ChatLog {
-    pub async fn observe(&self, tags: &irc::Tags, channel: &Channel, name: &str, message: &str) {
+    /// Observe a message in chat.
+    ///
+    /// `source` is the name of the channel the message was relayed from, if
+    /// it's not from the streamer's channel.
+    pub async fn observe(
+        &self,
+        tags: &irc::Tags,
+        channel: &Channel,
+        source: Option<&str>,
+        name: &str,
+        message: &str,
+    ) {
         let rendered = match self.emotes.as_ref() {
             Some(emotes) => match emotes.render(&tags, channel, name, message).await {
                 Ok(rendered) => Some(rendered),
@@ -86,7 +123,66 @@ impl ChatLog {
         };
 
         self.message_log
-            .push_back(&tags, &name, message, rendered)
+            .push_back(&tags, source, &name, message, rendered)
             .await;
+
+        if let Some(chat_messages) = self.chat_messages.as_ref() {
+            let (id, display_name) = match (tags.id.as_ref(), tags.display_name.as_ref()) {
+                (Some(id), Some(display_name)) => (id, display_name),
+                _ => return,
+            };
+
+            let channel = source.unwrap_or(&self.chat_channel);
+
+            if let Err(e) = chat_messages
+                .push(id, channel, name, display_name, message)
+                .await
+            {
+                log_error!(e, "Failed to store chat message");
+            }
+        }
+    }
+
+    /// Mark the message with the given id as deleted.
+    pub async fn delete_by_id(&self, id: &str) {
+        self.message_log.delete_by_id(id).await;
+
+        if let Some(chat_messages) = self.chat_messages.as_ref() {
+            if let Err(e) = chat_messages.delete_by_id(id).await {
+                log_error!(e, "Failed to delete stored chat message");
+            }
+        }
+    }
+
+    /// Mark all messages by the given user as deleted.
+    ///
+    /// `source` is the name of the channel the messages were relayed from, if
+    /// it's not the streamer's channel.
+    pub async fn delete_by_user(&self, source: Option<&str>, name: &str) {
+        self.message_log.delete_by_user(source, name).await;
+
+        if let Some(chat_messages) = self.chat_messages.as_ref() {
+            let channel = source.unwrap_or(&self.chat_channel);
+
+            if let Err(e) = chat_messages.delete_by_user(channel, name).await {
+                log_error!(e, "Failed to delete stored chat messages");
+            }
+        }
+    }
+
+    /// Mark all messages as deleted.
+    ///
+    /// `source` is the name of the channel the messages were relayed from, if
+    /// it's not the streamer's channel.
+    pub async fn delete_all(&self, source: Option<&str>) {
+        self.message_log.delete_all(source).await;
+
+        if let Some(chat_messages) = self.chat_messages.as_ref() {
+            let channel = source.unwrap_or(&self.chat_channel);
+
+            if let Err(e) = chat_messages.delete_by_channel(channel).await {
+                log_error!(e, "Failed to delete stored chat messages");
+            }
+        }
     }
 }
@@ -0,0 +1,624 @@
+//! Typed channel events, like subscriptions, raids and cheers.
+
+use crate::bus;
+use crate::currency::Currency;
+use crate::injector;
+use crate::irc::{Sender, Tags};
+use crate::prelude::*;
+use crate::template::Template;
+use anyhow::Result;
+use irc::proto::message::Tag;
+
+/// The user name Twitch uses for anonymous gifts.
+const ANONYMOUS_GIFTER: &str = "ananonymousgifter";
+
+/// The plan that was subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum SubPlan {
+    #[serde(rename = "Prime")]
+    Prime,
+    #[serde(rename = "Tier 1")]
+    Tier1,
+    #[serde(rename = "Tier 2")]
+    Tier2,
+    #[serde(rename = "Tier 3")]
+    Tier3,
+}
+
+impl SubPlan {
+    /// Parse the plan from the `msg-param-sub-plan` tag.
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Prime" => Some(SubPlan::Prime),
+            "1000" => Some(SubPlan::Tier1),
+            "2000" => Some(SubPlan::Tier2),
+            "3000" => Some(SubPlan::Tier3),
+            _ => None,
+        }
+    }
+}
+
+/// An event that happened in the channel.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "type")]
+pub enum ChannelEvent {
+    /// A user subscribed for the first time.
+    #[serde(rename = "sub")]
+    Sub {
+        user: String,
+        name: String,
+        plan: SubPlan,
+        message: Option<String>,
+    },
+    /// A user resubscribed.
+    #[serde(rename = "resub")]
+    Resub {
+        user: String,
+        name: String,
+        plan: SubPlan,
+        months: u32,
+        streak: Option<u32>,
+        message: Option<String>,
+    },
+    /// A user gifted a subscription to another user.
+    #[serde(rename = "subgift")]
+    SubGift {
+        user: String,
+        name: String,
+        plan: SubPlan,
+        recipient: String,
+        recipient_name: String,
+        months: u32,
+        anonymous: bool,
+    },
+    /// Another channel raided us.
+    #[serde(rename = "raid")]
+    Raid {
+        user: String,
+        name: String,
+        viewers: u32,
+    },
+    /// Another channel is hosting us.
+    #[serde(rename = "host")]
+    Host {
+        user: String,
+        name: String,
+        viewers: Option<u32>,
+    },
+    /// A user cheered with bits.
+    #[serde(rename = "cheer")]
+    Cheer {
+        user: String,
+        name: String,
+        bits: u32,
+        message: String,
+    },
+}
+
+impl ChannelEvent {
+    /// The kind of the event, which is also the name of its settings group.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            ChannelEvent::Sub { .. } => "sub",
+            ChannelEvent::Resub { .. } => "resub",
+            ChannelEvent::SubGift { .. } => "subgift",
+            ChannelEvent::Raid { .. } => "raid",
+            ChannelEvent::Host { .. } => "host",
+            ChannelEvent::Cheer { .. } => "cheer",
+        }
+    }
+
+    /// The user responsible for the event, if it's someone we can reward.
+    pub fn user(&self) -> Option<&str> {
+        match self {
+            ChannelEvent::SubGift {
+                anonymous: true, ..
+            } => None,
+            ChannelEvent::Sub { user, .. }
+            | ChannelEvent::Resub { user, .. }
+            | ChannelEvent::SubGift { user, .. }
+            | ChannelEvent::Raid { user, .. }
+            | ChannelEvent::Host { user, .. }
+            | ChannelEvent::Cheer { user, .. } => Some(user),
+        }
+    }
+
+    /// Parse an event out of a USERNOTICE.
+    ///
+    /// The message is the optional text the user attached to the notice.
+    pub fn from_user_notice(tags: Option<Vec<Tag>>, message: Option<&str>) -> Option<Self> {
+        let tags = UserNoticeTags::from_tags(tags);
+        let message = message.map(String::from);
+
+        let user = tags.login?;
+        let name = tags.display_name.unwrap_or_else(|| user.clone());
+
+        let event = match tags.msg_id?.as_str() {
+            "sub" => ChannelEvent::Sub {
+                user,
+                name,
+                plan: tags.sub_plan?,
+                message,
+            },
+            "resub" => ChannelEvent::Resub {
+                user,
+                name,
+                plan: tags.sub_plan?,
+                months: tags.cumulative_months.unwrap_or(1),
+                streak: tags.streak_months,
+                message,
+            },
+            "subgift" | "anonsubgift" => {
+                let recipient = tags.recipient_user_name?;
+                let recipient_name = tags
+                    .recipient_display_name
+                    .unwrap_or_else(|| recipient.clone());
+                let anonymous = user == ANONYMOUS_GIFTER;
+
+                ChannelEvent::SubGift {
+                    user,
+                    name,
+                    plan: tags.sub_plan?,
+                    recipient,
+                    recipient_name,
+                    months: tags.months.unwrap_or(1),
+                    anonymous,
+                }
+            }
+            "raid" => ChannelEvent::Raid {
+                user,
+                name,
+                viewers: tags.viewer_count.unwrap_or_default(),
+            },
+            _ => return None,
+        };
+
+        Some(event)
+    }
+
+    /// Parse a cheer out of a regular chat message.
+    pub fn from_cheer(user: &str, tags: &Tags, message: &str) -> Option<Self> {
+        let bits = tags.bits?;
+
+        Some(ChannelEvent::Cheer {
+            user: user.to_string(),
+            name: tags
+                .display_name
+                .clone()
+                .unwrap_or_else(|| user.to_string()),
+            bits,
+            message: message.to_string(),
+        })
+    }
+
+    /// Parse a host notification sent by `jtv`, like:
+    /// `Foo is now hosting you for up to 12 viewers.`
+    pub fn from_host_message(message: &str) -> Option<Self> {
+        const HOSTING: &str = " is now hosting you";
+
+        let index = message.find(HOSTING)?;
+        let name = &message[..index];
+        let rest = &message[(index + HOSTING.len())..];
+
+        if name.is_empty() || name.contains(' ') {
+            return None;
+        }
+
+        let viewers = rest
+            .trim_start_matches(" for up to ")
+            .split(' ')
+            .next()
+            .and_then(|n| str::parse::<u32>(n).ok());
+
+        Some(ChannelEvent::Host {
+            user: name.to_lowercase(),
+            name: name.to_string(),
+            viewers,
+        })
+    }
+}
+
+/// Tags associated with a USERNOTICE.
+#[derive(Default)]
+struct UserNoticeTags {
+    msg_id: Option<String>,
+    login: Option<String>,
+    display_name: Option<String>,
+    sub_plan: Option<SubPlan>,
+    cumulative_months: Option<u32>,
+    streak_months: Option<u32>,
+    months: Option<u32>,
+    recipient_user_name: Option<String>,
+    recipient_display_name: Option<String>,
+    viewer_count: Option<u32>,
+}
+
+impl UserNoticeTags {
+    /// Extract tags from message.
+    #[allow(clippy::single_match)]
+    fn from_tags(tags: Option<Vec<Tag>>) -> UserNoticeTags {
+        let mut out = UserNoticeTags::default();
+        let mut should_share_streak = false;
+
+        if let Some(tags) = tags {
+            for t in tags {
+                match t {
+                    Tag(name, Some(value)) => match name.as_str() {
+                        "msg-id" => out.msg_id = Some(value),
+                        "login" => out.login = Some(value),
+                        "display-name" => out.display_name = Some(value),
+                        "msg-param-sub-plan" => out.sub_plan = SubPlan::parse(&value),
+                        "msg-param-cumulative-months" => {
+                            out.cumulative_months = str::parse(&value).ok()
+                        }
+                        "msg-param-streak-months" => out.streak_months = str::parse(&value).ok(),
+                        "msg-param-should-share-streak" => should_share_streak = value == "1",
+                        "msg-param-months" => out.months = str::parse(&value).ok(),
+                        "msg-param-recipient-user-name" => out.recipient_user_name = Some(value),
+                        "msg-param-recipient-display-name" => {
+                            out.recipient_display_name = Some(value)
+                        }
+                        "msg-param-viewerCount" => out.viewer_count = str::parse(&value).ok(),
+                        _ => (),
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        if !should_share_streak {
+            out.streak_months = None;
+        }
+
+        out
+    }
+}
+
+/// Configuration for how to respond to a single kind of event.
+struct Response {
+    enabled: settings::Var<bool>,
+    template: settings::Var<Template>,
+    reward: settings::Var<i64>,
+}
+
+impl Response {
+    async fn new(settings: &settings::Settings, kind: &str, template: &str) -> Result<Self> {
+        let settings = settings.scoped(kind);
+
+        Ok(Response {
+            enabled: settings.var("enabled", true).await?,
+            template: settings
+                .var("template", Template::compile(template)?)
+                .await?,
+            reward: settings.var("reward", 0).await?,
+        })
+    }
+}
+
+/// Handler for channel events.
+///
+/// Responds to them in chat, rewards the user responsible, and notifies the
+/// global bus.
+pub struct Events {
+    enabled: settings::Var<bool>,
+    sub: Response,
+    resub: Response,
+    subgift: Response,
+    raid: Response,
+    host: Response,
+    cheer: Response,
+    currency: injector::Var<Option<Currency>>,
+    sender: Sender,
+    /// Bus to notify of events, which is only set for the streamer's channel.
+    global_bus: Option<Arc<bus::Bus<bus::Global>>>,
+}
+
+impl Events {
+    /// Set up a new handler for channel events.
+    pub async fn new(
+        settings: &settings::Settings,
+        injector: &injector::Injector,
+        sender: Sender,
+        global_bus: Option<Arc<bus::Bus<bus::Global>>>,
+    ) -> Result<Self> {
+        Ok(Events {
+            enabled: settings.var("enabled", false).await?,
+            sub: Response::new(
+                settings,
+                "sub",
+                "{{name}} just subscribed with {{plan}}! Welcome to the club PogChamp",
+            )
+            .await?,
+            resub: Response::new(
+                settings,
+                "resub",
+                "{{name}} resubscribed with {{plan}} for {{months}} months! PogChamp",
+            )
+            .await?,
+            subgift: Response::new(
+                settings,
+                "subgift",
+                "{{name}} gifted a {{plan}} sub to {{recipient_name}}! <3",
+            )
+            .await?,
+            raid: Response::new(
+                settings,
+                "raid",
+                "{{name}} is raiding with {{viewers}} viewers! Welcome raiders VoHiYo",
+            )
+            .await?,
+            host: Response::new(settings, "host", "Thank you for the host {{name}}! VoHiYo")
+                .await?,
+            cheer: Response::new(
+                settings,
+                "cheer",
+                "Thank you for the {{bits}} bits {{name}}! PogChamp",
+            )
+            .await?,
+            currency: injector.var().await?,
+            sender,
+            global_bus,
+        })
+    }
+
+    /// Handle the given channel event.
+    pub async fn handle(&self, event: ChannelEvent) -> Result<()> {
+        log::trace!("Channel event: {:?}", event);
+
+        if let Some(global_bus) = self.global_bus.as_ref() {
+            global_bus
+                .send(bus::Global::ChannelEvent {
+                    event: event.clone(),
+                })
+                .await;
+        }
+
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let response = match event {
+            ChannelEvent::Sub { .. } => &self.sub,
+            ChannelEvent::Resub { .. } => &self.resub,
+            ChannelEvent::SubGift { .. } => &self.subgift,
+            ChannelEvent::Raid { .. } => &self.raid,
+            ChannelEvent::Host { .. } => &self.host,
+            ChannelEvent::Cheer { .. } => &self.cheer,
+        };
+
+        if !response.enabled.load().await {
+            return Ok(());
+        }
+
+        let mut reward = response.reward.load().await;
+
+        // NB: cheer rewards are configured per 100 bits.
+        if let ChannelEvent::Cheer { bits, .. } = event {
+            reward = reward.saturating_mul(i64::from(bits)) / 100;
+        }
+
+        let currency = self.currency.load().await;
+        let mut currency_name = None;
+
+        if let (Some(currency), Some(user)) = (currency.as_ref(), event.user()) {
+            if reward > 0 {
+                currency
+                    .balance_add(self.sender.channel(), user, reward)
+                    .await?;
+                currency_name = Some(currency.name.clone());
+            }
+        }
+
+        let template = response.template.load().await;
+
+        let message = template.render_to_string(Vars {
+            target: self.sender.channel(),
+            reward: currency_name.as_ref().map(|_| reward),
+            currency: currency_name.as_deref().map(String::as_str),
+            event: &event,
+        })?;
+
+        self.sender.privmsg(message).await;
+        return Ok(());
+
+        #[derive(serde::Serialize)]
+        struct Vars<'a> {
+            target: &'a str,
+            reward: Option<i64>,
+            currency: Option<&'a str>,
+            #[serde(flatten)]
+            event: &'a ChannelEvent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelEvent, SubPlan};
+    use irc::proto::message::Tag;
+
+    fn tags(tags: &[(&str, &str)]) -> Option<Vec<Tag>> {
+        Some(
+            tags.iter()
+                .map(|(k, v)| Tag(k.to_string(), Some(v.to_string())))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_sub() {
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "sub"),
+                ("login", "setbac"),
+                ("display-name", "Setbac"),
+                ("msg-param-sub-plan", "Prime"),
+            ]),
+            None,
+        );
+
+        assert_eq!(
+            Some(ChannelEvent::Sub {
+                user: String::from("setbac"),
+                name: String::from("Setbac"),
+                plan: SubPlan::Prime,
+                message: None,
+            }),
+            event
+        );
+
+        // A sub without a known plan is ignored.
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "sub"),
+                ("login", "setbac"),
+                ("msg-param-sub-plan", "4000"),
+            ]),
+            None,
+        );
+
+        assert_eq!(None, event);
+    }
+
+    #[test]
+    fn test_resub() {
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "resub"),
+                ("login", "setbac"),
+                ("display-name", "Setbac"),
+                ("msg-param-sub-plan", "2000"),
+                ("msg-param-cumulative-months", "6"),
+                ("msg-param-streak-months", "2"),
+                ("msg-param-should-share-streak", "1"),
+            ]),
+            Some("Great stream!"),
+        );
+
+        assert_eq!(
+            Some(ChannelEvent::Resub {
+                user: String::from("setbac"),
+                name: String::from("Setbac"),
+                plan: SubPlan::Tier2,
+                months: 6,
+                streak: Some(2),
+                message: Some(String::from("Great stream!")),
+            }),
+            event
+        );
+    }
+
+    #[test]
+    fn test_subgift() {
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "subgift"),
+                ("login", "setbac"),
+                ("display-name", "Setbac"),
+                ("msg-param-sub-plan", "1000"),
+                ("msg-param-recipient-user-name", "udoprog"),
+                ("msg-param-recipient-display-name", "UdoProg"),
+                ("msg-param-months", "3"),
+            ]),
+            None,
+        );
+
+        let event = event.expect("expected event");
+        assert_eq!(Some("setbac"), event.user());
+
+        assert_eq!(
+            ChannelEvent::SubGift {
+                user: String::from("setbac"),
+                name: String::from("Setbac"),
+                plan: SubPlan::Tier1,
+                recipient: String::from("udoprog"),
+                recipient_name: String::from("UdoProg"),
+                months: 3,
+                anonymous: false,
+            },
+            event
+        );
+    }
+
+    #[test]
+    fn test_anonsubgift() {
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "anonsubgift"),
+                ("login", "ananonymousgifter"),
+                ("display-name", "AnAnonymousGifter"),
+                ("msg-param-sub-plan", "3000"),
+                ("msg-param-recipient-user-name", "udoprog"),
+            ]),
+            None,
+        );
+
+        let event = event.expect("expected event");
+        // Anonymous gifters can't be rewarded.
+        assert_eq!(None, event.user());
+
+        assert_eq!(
+            ChannelEvent::SubGift {
+                user: String::from("ananonymousgifter"),
+                name: String::from("AnAnonymousGifter"),
+                plan: SubPlan::Tier3,
+                recipient: String::from("udoprog"),
+                recipient_name: String::from("udoprog"),
+                months: 1,
+                anonymous: true,
+            },
+            event
+        );
+    }
+
+    #[test]
+    fn test_raid() {
+        let event = ChannelEvent::from_user_notice(
+            tags(&[
+                ("msg-id", "raid"),
+                ("login", "setbac"),
+                ("msg-param-viewerCount", "42"),
+            ]),
+            None,
+        );
+
+        assert_eq!(
+            Some(ChannelEvent::Raid {
+                user: String::from("setbac"),
+                name: String::from("setbac"),
+                viewers: 42,
+            }),
+            event
+        );
+
+        let event = ChannelEvent::from_user_notice(
+            tags(&[("msg-id", "ritual"), ("login", "setbac")]),
+            None,
+        );
+
+        assert_eq!(None, event);
+    }
+
+    #[test]
+    fn test_host_message() {
+        assert_eq!(
+            Some(ChannelEvent::Host {
+                user: String::from("setbac"),
+                name: String::from("Setbac"),
+                viewers: Some(12),
+            }),
+            ChannelEvent::from_host_message("Setbac is now hosting you for up to 12 viewers.")
+        );
+
+        assert_eq!(
+            Some(ChannelEvent::Host {
+                user: String::from("setbac"),
+                name: String::from("Setbac"),
+                viewers: None,
+            }),
+            ChannelEvent::from_host_message("Setbac is now hosting you.")
+        );
+
+        assert_eq!(None, ChannelEvent::from_host_message("hello world"));
+    }
+}
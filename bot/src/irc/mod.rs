@@ -19,7 +19,7 @@ use irc::proto::command::{CapSubCommand, Command};
 use irc::proto::message::{Message, Tag};
 use leaky_bucket::LeakyBuckets;
 use parking_lot::RwLock;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::mem;
 use std::sync::Arc;
@@ -29,10 +29,12 @@ use tracing::trace_span;
 use tracing_futures::Instrument as _;
 
 // re-exports
+pub use self::events::ChannelEvent;
 pub use self::sender::Sender;
 
 mod chat_log;
 mod currency_admin;
+mod events;
 mod sender;
 
 const SERVER: &str = "irc.chat.twitch.tv";
@@ -222,11 +224,50 @@ impl Irc {
             let chat_channel = format!("#{}", channel.name);
             *global_channel.write().await = Some(chat_channel.clone());
 
+            let chat_settings = settings.scoped("chat");
+
+            let (mut extra_channels_stream, extra_channels) = chat_settings
+                .stream::<HashSet<String>>("channels")
+                .or_default()
+                .await?;
+
+            let extra_channels = extra_channels
+                .into_iter()
+                .map(|c| format!("#{}", c.trim_start_matches('#').to_lowercase()))
+                .filter(|c| *c != chat_channel)
+                .collect::<Vec<_>>();
+
+            let shared_settings = chat_settings.scoped("shared");
+
+            let (mut shared_channels_stream, shared_channels) = shared_settings
+                .stream::<HashSet<String>>("channels")
+                .or_default()
+                .await?;
+
+            let shared = Shared {
+                enabled: shared_settings.var("enabled", false).await?,
+                channels: shared_channels
+                    .into_iter()
+                    .map(|c| format!("#{}", c.trim_start_matches('#').to_lowercase()))
+                    .filter(|c| *c != chat_channel)
+                    .collect(),
+                commands: shared_settings.var("commands", HashSet::new()).await?,
+            };
+
+            let mut all_channels = vec![chat_channel.clone()];
+            all_channels.extend(extra_channels.iter().cloned());
+
+            for c in &shared.channels {
+                if !all_channels.contains(c) {
+                    all_channels.push(c.clone());
+                }
+            }
+
             let access_token = bot_twitch.token.read().await?.access_token().to_string();
 
             let irc_client_config = client::data::config::Config {
                 nickname: Some(bot.name.to_string()),
-                channels: vec![chat_channel.clone()],
+                channels: all_channels,
                 password: Some(format!("oauth:{}", access_token)),
                 server: Some(String::from(SERVER)),
                 port: Some(6697),
@@ -237,13 +278,11 @@ impl Irc {
             let mut client = Client::from_config(irc_client_config).await?;
             client.identify()?;
 
-            let chat_settings = settings.scoped("chat");
-
             let url_whitelist_enabled = chat_settings.var("url-whitelist/enabled", true).await?;
             let bad_words_enabled = chat_settings.var("bad-words/enabled", false).await?;
             let sender_ty = chat_settings.var("sender-type", sender::Type::Chat).await?;
             let threshold = chat_settings.var("idle-detection/threshold", 5).await?;
-            let idle = idle::Idle::new(threshold);
+            let idle = idle::Idle::new(threshold.clone());
 
             let nightbot = injector.var::<api::NightBot>().await?;
 
@@ -302,35 +341,106 @@ impl Irc {
                 stream_info
             };
 
-            futures.push(
-                refresh_mods_future(sender.clone())
-                    .instrument(trace_span!(target: "futures", "refresh-mods",))
-                    .boxed(),
-            );
+            let mut channels = HashMap::new();
 
-            let mut handlers = module::Handlers::default();
+            // NB: the streamer's channel uses the root settings scope, while
+            // other channels have their own scope under `channels/<name>`.
+            let channel_setups = std::iter::once((sender.clone(), idle.clone(), settings.clone()))
+                .chain(extra_channels.iter().map(|c| {
+                    let settings = settings.scoped(&format!("channels/{}", &c[1..]));
+                    (
+                        sender.with_target(c.clone()),
+                        idle::Idle::new(threshold.clone()),
+                        settings,
+                    )
+                }));
 
-            for module in modules.iter() {
-                if log::log_enabled!(log::Level::Trace) {
-                    log::trace!("initializing module: {}", module.ty());
+            for (sender, idle, settings) in channel_setups {
+                let primary = sender.channel() == chat_channel;
+
+                futures.push(
+                    refresh_mods_future(sender.clone())
+                        .instrument(trace_span!(target: "futures", "refresh-mods",))
+                        .boxed(),
+                );
+
+                let mut handlers = module::Handlers::default();
+                let mut message_hooks = Vec::new();
+
+                for module in modules.iter() {
+                    if !primary && module.primary_only() {
+                        continue;
+                    }
+
+                    if log::log_enabled!(log::Level::Trace) {
+                        log::trace!(
+                            "initializing module: {} (channel: {})",
+                            module.ty(),
+                            sender.channel()
+                        );
+                    }
+
+                    let result = module
+                        .hook(module::HookContext {
+                            handlers: &mut handlers,
+                            futures: &mut futures,
+                            stream_info: &stream_info,
+                            idle: &idle,
+                            twitch: &bot_twitch,
+                            streamer_twitch: &streamer_twitch,
+                            sender: &sender,
+                            settings: &settings,
+                            injector: &injector,
+                            auth: &auth,
+                            message_hooks: &mut message_hooks,
+                            global_bus: &global_bus,
+                            primary,
+                        })
+                        .await;
+
+                    result
+                        .with_context(|| anyhow!("failed to initialize module: {}", module.ty()))?;
                 }
 
-                let result = module
-                    .hook(module::HookContext {
-                        handlers: &mut handlers,
-                        futures: &mut futures,
-                        stream_info: &stream_info,
-                        idle: &idle,
-                        twitch: &bot_twitch,
-                        streamer_twitch: &streamer_twitch,
-                        sender: &sender,
-                        settings: &settings,
-                        injector: &injector,
-                        auth: &auth,
-                    })
-                    .await;
+                let mut hooks = slab::Slab::new();
+
+                for hook in message_hooks {
+                    hooks.insert(hook);
+                }
+
+                // NB: only events in the streamer's channel are shown on the
+                // overlay.
+                let events = events::Events::new(
+                    &settings.scoped("chat/events"),
+                    &injector,
+                    sender.clone(),
+                    if primary {
+                        Some(global_bus.clone())
+                    } else {
+                        None
+                    },
+                )
+                .await?;
+
+                let context_inner = Arc::new(command::ContextInner {
+                    sender: sender.clone(),
+                    scope_cooldowns: sync::Mutex::new(auth.scope_cooldowns()),
+                    message_hooks: sync::RwLock::new(hooks),
+                    shutdown: shutdown.clone(),
+                });
 
-                result.with_context(|| anyhow!("failed to initialize module: {}", module.ty()))?;
+                channels.insert(
+                    sender.channel().to_string(),
+                    ChannelState {
+                        sender,
+                        idle,
+                        moderators: Default::default(),
+                        vips: Default::default(),
+                        handlers,
+                        context_inner,
+                        events: Arc::new(events),
+                    },
+                );
             }
 
             let currency_handler = currency_admin::setup(&injector).await?;
@@ -374,6 +484,7 @@ impl Irc {
 
             let mut chat_log_builder = chat_log::Builder::new(
                 bot_twitch.clone(),
+                chat_channel.clone(),
                 &injector,
                 message_log.clone(),
                 settings.scoped("chat-log"),
@@ -388,8 +499,9 @@ impl Irc {
             let mut handler = Handler {
                 streamer,
                 sender: sender.clone(),
-                moderators: Default::default(),
-                vips: Default::default(),
+                chat_channel: chat_channel.clone(),
+                channels,
+                shared,
                 whitelisted_hosts,
                 commands,
                 bad_words: &bad_words,
@@ -397,8 +509,6 @@ impl Irc {
                 aliases,
                 api_url: Arc::new(api_url),
                 moderator_cooldown,
-                handlers,
-                idle: &idle,
                 pong_timeout: &mut pong_timeout,
                 token: &bot_twitch.token,
                 handler_shutdown: false,
@@ -409,12 +519,6 @@ impl Irc {
                 bad_words_enabled,
                 chat_log: chat_log_builder.build()?,
                 channel,
-                context_inner: Arc::new(command::ContextInner {
-                    sender: sender.clone(),
-                    scope_cooldowns: sync::Mutex::new(auth.scope_cooldowns()),
-                    message_hooks: sync::RwLock::new(Default::default()),
-                    shutdown: shutdown.clone(),
-                }),
             };
 
             let mut outgoing = client
@@ -483,6 +587,14 @@ impl Irc {
                             leave = Some(tokio::time::delay_for(time::Duration::from_secs(1)));
                         }
                     },
+                    _ = extra_channels_stream.select_next_some() => {
+                        log::info!("Channels to join changed, reconnecting");
+                        leave = Some(tokio::time::delay_for(time::Duration::from_secs(1)));
+                    }
+                    _ = shared_channels_stream.select_next_some() => {
+                        log::info!("Shared channels changed, reconnecting");
+                        leave = Some(tokio::time::delay_for(time::Duration::from_secs(1)));
+                    }
                     update = commands_stream.select_next_some() => {
                         handler.commands = update;
                     }
@@ -502,6 +614,14 @@ impl Irc {
                         chat_log_builder.emotes_enabled = update;
                         handler.chat_log = chat_log_builder.build()?;
                     }
+                    update = chat_log_builder.persist_stream.select_next_some() => {
+                        chat_log_builder.persist = update;
+                        handler.chat_log = chat_log_builder.build()?;
+                    }
+                    update = chat_log_builder.chat_messages_stream.select_next_some() => {
+                        chat_log_builder.chat_messages = update;
+                        handler.chat_log = chat_log_builder.build()?;
+                    }
                     update = api_url_stream.select_next_some() => {
                         handler.api_url = Arc::new(update);
                     }
@@ -696,12 +816,14 @@ async fn currency_loop(
 struct Handler<'a> {
     /// Current Streamer.
     streamer: Arc<twitch::User>,
-    /// Queue for sending messages.
+    /// Queue for sending messages to the streamer's channel.
     sender: Sender,
-    /// Moderators.
-    moderators: Arc<RwLock<HashSet<String>>>,
-    /// VIPs.
-    vips: Arc<RwLock<HashSet<String>>>,
+    /// The streamer's channel.
+    chat_channel: String,
+    /// State for every joined channel.
+    channels: HashMap<String, ChannelState>,
+    /// Configuration for shared chat.
+    shared: Shared,
     /// Whitelisted hosts for links.
     whitelisted_hosts: HashSet<String>,
     /// All registered commands.
@@ -716,10 +838,6 @@ struct Handler<'a> {
     api_url: Arc<Option<String>>,
     /// Active moderator cooldown.
     moderator_cooldown: Option<Cooldown>,
-    /// Handlers for specific commands like `!skip`.
-    handlers: module::Handlers,
-    /// Build idle detection.
-    idle: &'a idle::Idle,
     /// Pong timeout currently running.
     pong_timeout: &'a mut Option<tokio::time::Delay>,
     /// OAuth 2.0 Token used to authenticate with IRC.
@@ -738,8 +856,60 @@ struct Handler<'a> {
     chat_log: Option<chat_log::ChatLog>,
     /// Information on the current channel.
     channel: Arc<twitch::Channel>,
+}
+
+/// Configuration for shared chat, where messages from partner channels are
+/// relayed into the chat log, and selected commands respond in all channels.
+struct Shared {
+    /// If shared chat is enabled.
+    enabled: settings::Var<bool>,
+    /// Partner channels.
+    channels: Vec<String>,
+    /// Commands which respond in partner channels as well.
+    commands: settings::Var<HashSet<String>>,
+}
+
+/// State for a single joined channel.
+struct ChannelState {
+    /// Queue for sending messages to the channel.
+    sender: Sender,
+    /// Idle detection for the channel.
+    idle: idle::Idle,
+    /// Moderators.
+    moderators: Arc<RwLock<HashSet<String>>>,
+    /// VIPs.
+    vips: Arc<RwLock<HashSet<String>>>,
+    /// Handlers for specific commands like `!skip`.
+    handlers: module::Handlers,
     /// Shared context paramters.
     context_inner: Arc<command::ContextInner>,
+    /// Handler for channel events.
+    events: Arc<events::Events>,
+}
+
+impl ChannelState {
+    /// Construct a user in the current channel.
+    fn user(
+        &self,
+        tags: Tags,
+        principal: Principal,
+        streamer: &Arc<twitch::User>,
+        stream_info: &stream_info::StreamInfo,
+        auth: &Auth,
+    ) -> User {
+        User {
+            inner: Arc::new(UserInner {
+                tags,
+                sender: self.sender.clone(),
+                principal,
+                streamer: streamer.clone(),
+                moderators: self.moderators.clone(),
+                vips: self.vips.clone(),
+                stream_info: stream_info.clone(),
+                auth: auth.clone(),
+            }),
+        }
+    }
 }
 
 /// Handle a command.
@@ -772,7 +942,7 @@ pub async fn process_command(
                 let scope = handler.scope();
 
                 if log::log_enabled!(log::Level::Trace) {
-                    log::trace!("Auth: {:?} against {:?}", scope, ctx.user.roles());
+                    log::trace!("Auth: {:?} against {:?}", scope, ctx.user.roles().await);
                 }
 
                 // Test if user has the required scope to run the given
@@ -842,7 +1012,7 @@ impl<'a> Handler<'a> {
 
                     match why {
                         Ok(why) => {
-                            self.sender.privmsg(&why).await;
+                            user.sender().privmsg(&why).await;
                         }
                         Err(e) => {
                             log_error!(e, "failed to render response");
@@ -906,10 +1076,18 @@ impl<'a> Handler<'a> {
 
     /// Process the given command.
     pub async fn process_message(&mut self, user: &User, mut message: Arc<String>) -> Result<()> {
+        let state = match self.channels.get(user.channel()) {
+            Some(state) => state,
+            None => {
+                log::warn!("Message for unknown channel: {}", user.channel());
+                return Ok(());
+            }
+        };
+
         // Run message hooks.
         let _ = task::spawn({
             let user = user.clone();
-            let context_inner = self.context_inner.clone();
+            let context_inner = state.context_inner.clone();
             let message = message.clone();
 
             async move {
@@ -925,7 +1103,7 @@ impl<'a> Handler<'a> {
 
         // only non-moderators and non-streamer bumps the idle counter.
         if !user.is_streamer() {
-            self.idle.seen();
+            state.idle.seen();
         }
 
         // NB: declared here to be in scope.
@@ -969,7 +1147,7 @@ impl<'a> Handler<'a> {
                 };
 
                 let response = command.render(&vars)?;
-                self.sender.privmsg(response).await;
+                user.sender().privmsg(response).await;
             }
         }
 
@@ -979,9 +1157,9 @@ impl<'a> Handler<'a> {
 
                 let ctx = command::Context {
                     api_url: self.api_url.clone(),
-                    user: user.clone(),
+                    user: self.shared_user(user, &message).await,
                     it,
-                    inner: self.context_inner.clone(),
+                    inner: state.context_inner.clone(),
                 };
 
                 let result = process_command(
@@ -989,7 +1167,7 @@ impl<'a> Handler<'a> {
                     ctx,
                     &self.global_bus,
                     &self.currency_handler,
-                    &self.handlers,
+                    &state.handlers,
                 );
 
                 if let Err(e) = result.await {
@@ -1005,21 +1183,62 @@ impl<'a> Handler<'a> {
         Ok(())
     }
 
+    /// Get the user to use when running the command in the given message.
+    ///
+    /// Shared commands in the streamer's channel respond in partner channels
+    /// as well.
+    async fn shared_user(&self, user: &User, message: &str) -> User {
+        if user.channel() != self.chat_channel || self.shared.channels.is_empty() {
+            return user.clone();
+        }
+
+        if !self.shared.enabled.load().await {
+            return user.clone();
+        }
+
+        let shared = self
+            .shared
+            .commands
+            .read()
+            .await
+            .iter()
+            .any(|c| is_command_path(c, message));
+
+        if !shared {
+            return user.clone();
+        }
+
+        user.with_sender(user.sender().with_mirrors(self.shared.channels.clone()))
+    }
+
+    /// Handle a channel event for the given channel in the background.
+    fn channel_event(&self, target: &str, event: ChannelEvent) {
+        let events = match self.channels.get(target) {
+            Some(state) => state.events.clone(),
+            None => {
+                log::trace!("Event for channel without handlers: {}", target);
+                return;
+            }
+        };
+
+        task::spawn(async move {
+            if let Err(e) = events.handle(event).await {
+                log_error!(e, "Failed to handle channel event");
+            }
+        });
+    }
+
     /// Run the given raw command.
     pub async fn raw(&mut self, message: String) -> Result<()> {
-        let tags = Tags::default();
-
-        let user = User {
-            inner: Arc::new(UserInner {
-                tags,
-                sender: self.sender.clone(),
-                principal: Principal::Injected,
-                streamer: self.streamer.clone(),
-                moderators: self.moderators.clone(),
-                vips: self.vips.clone(),
-                stream_info: self.stream_info.clone(),
-                auth: self.auth.clone(),
-            }),
+        let user = match self.channels.get(&self.chat_channel) {
+            Some(state) => state.user(
+                Tags::default(),
+                Principal::Injected,
+                &self.streamer,
+                self.stream_info,
+                self.auth,
+            ),
+            None => return Ok(()),
         };
 
         self.process_message(&user, Arc::new(message)).await
@@ -1028,7 +1247,7 @@ impl<'a> Handler<'a> {
     /// Handle the given command.
     pub async fn handle(&mut self, mut m: Message) -> Result<()> {
         match m.command {
-            Command::PRIVMSG(_, ref mut message) => {
+            Command::PRIVMSG(ref target, ref mut message) => {
                 let message = Arc::new(mem::replace(message, String::new()));
                 let tags = Tags::from_tags(m.tags.take());
 
@@ -1037,28 +1256,49 @@ impl<'a> Handler<'a> {
                     .ok_or_else(|| anyhow!("expected user info"))?
                     .to_string();
 
-                if let Some(chat_log) = self.chat_log.as_ref().cloned() {
+                let primary = *target == self.chat_channel;
+
+                let relay = primary
+                    || self.shared.channels.contains(target) && self.shared.enabled.load().await;
+
+                if let (true, Some(chat_log)) = (relay, self.chat_log.as_ref().cloned()) {
                     let tags = tags.clone();
                     let channel = self.channel.clone();
+                    let source = if primary { None } else { Some(target.clone()) };
                     let name = name.clone();
                     let message = message.clone();
 
                     task::spawn(Box::pin(async move {
-                        chat_log.observe(&tags, &*channel, &name, &*message).await;
+                        chat_log
+                            .observe(&tags, &*channel, source.as_deref(), &name, &*message)
+                            .await;
                     }));
                 }
 
-                let user = User {
-                    inner: Arc::new(UserInner {
+                if let Some(event) = ChannelEvent::from_cheer(&name, &tags, &message) {
+                    self.channel_event(target, event);
+                }
+
+                // NB: host notifications are sent as regular messages from jtv.
+                if name == "jtv" {
+                    if let Some(event) = ChannelEvent::from_host_message(&message) {
+                        self.channel_event(target, event);
+                    }
+                }
+
+                let user = match self.channels.get(target) {
+                    Some(state) => state.user(
                         tags,
-                        sender: self.sender.clone(),
-                        principal: Principal::User { name },
-                        streamer: self.streamer.clone(),
-                        moderators: self.moderators.clone(),
-                        vips: self.vips.clone(),
-                        stream_info: self.stream_info.clone(),
-                        auth: self.auth.clone(),
-                    }),
+                        Principal::User { name },
+                        &self.streamer,
+                        self.stream_info,
+                        self.auth,
+                    ),
+                    // NB: partner channels in shared chat are only relayed.
+                    None => {
+                        log::trace!("Message for channel without handlers: {}", target);
+                        return Ok(());
+                    }
                 };
 
                 self.process_message(&user, message).await?;
@@ -1070,8 +1310,10 @@ impl<'a> Handler<'a> {
                     // do what needs to happen with them (like `/mods`).
                     Some(TWITCH_COMMANDS_CAP) => {
                         // request to get a list of moderators and vips.
-                        self.sender.mods();
-                        self.sender.vips();
+                        for state in self.channels.values() {
+                            state.sender.mods();
+                            state.sender.vips();
+                        }
                     }
                     _ => {}
                 }
@@ -1097,60 +1339,97 @@ impl<'a> Handler<'a> {
                 log::trace!("Received PONG, clearing PING timeout");
                 *self.pong_timeout = None;
             }
-            Command::NOTICE(_, ref message) => {
+            Command::NOTICE(ref target, ref message) => {
                 let tags = Tags::from_tags(m.tags.take());
+                let state = self.channels.get(target);
 
-                match tags.msg_id.as_deref() {
+                match (tags.msg_id.as_deref(), state) {
                     _ if message == "Login authentication failed" => {
                         self.token.force_refresh().await?;
                         self.handler_shutdown = true;
                     }
-                    Some("no_mods") => {
-                        self.moderators.write().clear();
+                    (Some("no_mods"), Some(state)) => {
+                        state.moderators.write().clear();
                     }
                     // Response to /mods request.
-                    Some("room_mods") => {
-                        *self.moderators.write() = parse_room_members(message);
+                    (Some("room_mods"), Some(state)) => {
+                        *state.moderators.write() = parse_room_members(message);
                     }
-                    Some("no_vips") => {
-                        self.vips.write().clear();
+                    (Some("no_vips"), Some(state)) => {
+                        state.vips.write().clear();
                     }
                     // Response to /vips request.
-                    Some("vips_success") => {
-                        *self.vips.write() = parse_room_members(message);
+                    (Some("vips_success"), Some(state)) => {
+                        *state.vips.write() = parse_room_members(message);
                     }
-                    Some(msg_id) => {
+                    (Some(msg_id), _) => {
                         log::info!("unhandled notice w/ msg_id: {:?}: {:?}", msg_id, m);
                     }
-                    None => {
+                    (None, _) => {
                         log::info!("unhandled notice: {:?}", m);
                     }
                 }
             }
-            Command::Raw(ref command, ref tail) => match command.as_str() {
-                "CLEARMSG" => {
-                    if let Some(chat_log) = self.chat_log.as_ref() {
-                        if let Some(tags) = ClearMsgTags::from_tags(m.tags) {
-                            chat_log.message_log.delete_by_id(&tags.target_msg_id).await;
-                        }
+            Command::Raw(ref command, ref tail) => {
+                // NB: first argument is always the channel.
+                let target = match tail.first() {
+                    Some(target) => target.as_str(),
+                    None => {
+                        log::trace!("Raw: {:?}", m);
+                        return Ok(());
                     }
-                }
-                "CLEARCHAT" => {
-                    if let Some(chat_log) = self.chat_log.as_ref() {
-                        match tail.first() {
-                            Some(user) => {
-                                chat_log.message_log.delete_by_user(user).await;
+                };
+
+                let primary = target == self.chat_channel;
+
+                // NB: messages from partner channels in shared chat are
+                // relayed into the chat log, so deletions are too.
+                let relay = primary
+                    || self.shared.channels.iter().any(|c| c == target)
+                        && self.shared.enabled.load().await;
+
+                let chat_log = match (relay, self.chat_log.as_ref()) {
+                    (true, Some(chat_log)) => Some(chat_log),
+                    _ => None,
+                };
+
+                let source = if primary { None } else { Some(target) };
+
+                match command.as_str() {
+                    "CLEARMSG" => {
+                        if let Some(chat_log) = chat_log {
+                            if let Some(tags) = ClearMsgTags::from_tags(m.tags) {
+                                chat_log.delete_by_id(&tags.target_msg_id).await;
                             }
-                            None => {
-                                chat_log.message_log.delete_all().await;
+                        }
+                    }
+                    "USERNOTICE" => {
+                        // NB: second argument is the optional message attached by
+                        // the user.
+                        let message = tail.get(1).map(String::as_str);
+
+                        if let Some(event) = ChannelEvent::from_user_notice(m.tags.take(), message)
+                        {
+                            self.channel_event(target, event);
+                        }
+                    }
+                    "CLEARCHAT" => {
+                        if let Some(chat_log) = chat_log {
+                            match tail.get(1) {
+                                Some(user) => {
+                                    chat_log.delete_by_user(source, user).await;
+                                }
+                                None => {
+                                    chat_log.delete_all(source).await;
+                                }
                             }
                         }
                     }
+                    _ => {
+                        log::trace!("Raw: {:?}", m);
+                    }
                 }
-                _ => {
-                    log::trace!("Raw: {:?}", m);
-                }
-            },
+            }
             _ => {
                 log::info!("unhandled: {:?}", m);
             }
@@ -1210,6 +1489,13 @@ impl<'a> RealUser<'a> {
         self.name == self.streamer.name
     }
 
+    /// Test if the owner of the channel the user is in.
+    ///
+    /// This is only distinct from the streamer in other joined channels.
+    fn is_owner(&self) -> bool {
+        self.channel().trim_start_matches('#') == self.name
+    }
+
     /// Test if moderator.
     fn is_moderator(&self) -> bool {
         self.moderators.read().contains(self.name)
@@ -1225,12 +1511,26 @@ impl<'a> RealUser<'a> {
         self.vips.read().contains(self.name)
     }
 
-    /// Get a list of all roles the current requester belongs to.
-    pub fn roles(&self) -> smallvec::SmallVec<[Role; 4]> {
+    /// Get a list of all roles the current requester belongs to, including
+    /// custom roles and inherited roles.
+    pub async fn roles(&self) -> Vec<Role> {
+        self.auth.resolve_roles(self.name, self.base_roles()).await
+    }
+
+    /// Get a list of the built-in roles the given user in the same channel as
+    /// the current requester belongs to.
+    pub fn base_roles_of(&self, name: &str) -> smallvec::SmallVec<[Role; 4]> {
+        RealUser { name, ..*self }.base_roles()
+    }
+
+    /// Get a list of the built-in roles the current requester belongs to.
+    fn base_roles(&self) -> smallvec::SmallVec<[Role; 4]> {
         let mut roles = smallvec::SmallVec::new();
 
         if self.is_streamer() {
             roles.push(Role::Streamer);
+        } else if self.is_owner() {
+            roles.push(Role::Owner);
         }
 
         if self.is_moderator() {
@@ -1251,11 +1551,14 @@ impl<'a> RealUser<'a> {
 
     /// Test if the current user has the given scope.
     pub async fn has_scope(&self, scope: Scope) -> bool {
-        self.auth.test_any(scope, self.name, self.roles()).await
+        self.auth
+            .test_any(scope, self.name, self.base_roles())
+            .await
     }
 }
 
 /// Information about the user.
+#[derive(Clone)]
 pub enum Principal {
     User { name: String },
     Injected,
@@ -1301,6 +1604,22 @@ impl User {
         self.inner.sender.channel()
     }
 
+    /// Construct a copy of the user which responds through the given sender.
+    fn with_sender(&self, sender: Sender) -> User {
+        User {
+            inner: Arc::new(UserInner {
+                tags: self.inner.tags.clone(),
+                sender,
+                principal: self.inner.principal.clone(),
+                streamer: self.inner.streamer.clone(),
+                moderators: self.inner.moderators.clone(),
+                vips: self.inner.vips.clone(),
+                stream_info: self.inner.stream_info.clone(),
+                auth: self.inner.auth.clone(),
+            }),
+        }
+    }
+
     /// Get the name of the user.
     pub fn name(&self) -> Option<&str> {
         match self.inner.principal {
@@ -1386,17 +1705,10 @@ impl User {
     }
 
     /// Get a list of all roles the current requester belongs to.
-    pub fn roles(&self) -> smallvec::SmallVec<[Role; 4]> {
-        match self.real().map(|u| u.roles()) {
-            Some(roles) => roles,
-            None => {
-                let mut roles = smallvec::SmallVec::<[Role; 4]>::default();
-                roles.push(Role::Streamer);
-                roles.push(Role::Moderator);
-                roles.push(Role::Subscriber);
-                roles.push(Role::Vip);
-                roles
-            }
+    pub async fn roles(&self) -> Vec<Role> {
+        match self.real() {
+            Some(user) => user.roles().await,
+            None => vec![Role::Streamer, Role::Moderator, Role::Subscriber, Role::Vip],
         }
     }
 
@@ -1512,6 +1824,8 @@ pub struct Tags {
     pub emotes: Option<String>,
     /// Badges part of the message.
     pub badges: Option<String>,
+    /// Bits cheered with the message.
+    pub bits: Option<u32>,
 }
 
 impl Tags {
@@ -1525,6 +1839,7 @@ impl Tags {
         let mut color = None;
         let mut emotes = None;
         let mut badges = None;
+        let mut bits = None;
 
         if let Some(tags) = tags {
             for t in tags {
@@ -1537,6 +1852,7 @@ impl Tags {
                         "color" => color = Some(value),
                         "emotes" => emotes = Some(value),
                         "badges" => badges = Some(value),
+                        "bits" => bits = str::parse(&value).ok(),
                         _ => (),
                     },
                     _ => (),
@@ -1552,6 +1868,7 @@ impl Tags {
             color,
             emotes,
             badges,
+            bits,
         }
     }
 }
@@ -1619,6 +1936,33 @@ async fn refresh_mods_future(sender: Sender) -> Result<()> {
     Ok(())
 }
 
+/// Test if the message invokes the given command path, like `song current`.
+///
+/// Only the words in the path are compared, so arguments are allowed to follow
+/// it.
+fn is_command_path(path: &str, message: &str) -> bool {
+    let mut words = message.split_whitespace();
+
+    let first = match words.next() {
+        Some(first) if first.starts_with('!') => &first[1..],
+        _ => return false,
+    };
+
+    let mut path = path.split_whitespace();
+
+    match path.next() {
+        Some(c) if c.eq_ignore_ascii_case(first) => (),
+        _ => return false,
+    }
+
+    path.all(|c| {
+        words
+            .next()
+            .map(|w| c.eq_ignore_ascii_case(w))
+            .unwrap_or(false)
+    })
+}
+
 /// Parse the `room_mods` message.
 fn parse_room_members(message: &str) -> HashSet<String> {
     let mut out = HashSet::default();
@@ -1641,9 +1985,20 @@ fn parse_room_members(message: &str) -> HashSet<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_room_members;
+    use super::{is_command_path, parse_room_members};
     use std::collections::HashSet;
 
+    #[test]
+    fn test_is_command_path() {
+        assert!(is_command_path("song current", "!song current"));
+        assert!(is_command_path("song current", "!SONG current extra"));
+        assert!(is_command_path("song", "!song purge"));
+        assert!(!is_command_path("song current", "!song purge"));
+        assert!(!is_command_path("song current", "!song"));
+        assert!(!is_command_path("song", "song"));
+        assert!(!is_command_path("song", "!songs"));
+    }
+
     #[test]
     fn test_parse_room_mods() {
         assert_eq!(
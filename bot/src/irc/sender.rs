@@ -25,7 +25,6 @@ impl Default for Type {
 }
 
 struct Inner {
-    target: String,
     sender: client::Sender,
     limiter: LeakyBucket,
     nightbot_limiter: LeakyBucket,
@@ -35,6 +34,9 @@ struct Inner {
 #[derive(Clone)]
 pub struct Sender {
     ty: settings::Var<Type>,
+    target: Arc<String>,
+    /// Other channels which messages are mirrored to.
+    mirrors: Arc<Vec<String>>,
     inner: Arc<Inner>,
 }
 
@@ -63,8 +65,9 @@ impl Sender {
 
         Ok(Sender {
             ty,
+            target: Arc::new(target),
+            mirrors: Arc::new(Vec::new()),
             inner: Arc::new(Inner {
-                target,
                 sender,
                 limiter,
                 nightbot_limiter,
@@ -73,9 +76,34 @@ impl Sender {
         })
     }
 
+    /// Construct a sender for another channel.
+    ///
+    /// The new sender shares rate limiting with this one, but always sends
+    /// through chat since NightBot is only configured for the streamer's
+    /// channel.
+    pub fn with_target(&self, target: String) -> Sender {
+        Sender {
+            ty: settings::Var::new(Type::Chat),
+            target: Arc::new(target),
+            mirrors: Arc::new(Vec::new()),
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Construct a sender which mirrors every message sent through `privmsg`
+    /// to the given channels.
+    pub fn with_mirrors(&self, mirrors: Vec<String>) -> Sender {
+        Sender {
+            ty: self.ty.clone(),
+            target: self.target.clone(),
+            mirrors: Arc::new(mirrors),
+            inner: self.inner.clone(),
+        }
+    }
+
     /// Get the channel this sender is associated with.
     pub fn channel(&self) -> &str {
-        self.inner.target.as_str()
+        self.target.as_str()
     }
 
     /// Delete the given message by id.
@@ -116,20 +144,25 @@ impl Sender {
 
     /// Send a PRIVMSG.
     pub async fn privmsg(&self, f: impl fmt::Display) {
+        let m = f.to_string();
+
+        for mirror in self.mirrors.iter() {
+            self.send(Command::PRIVMSG(mirror.clone(), m.clone())).await;
+        }
+
         match self.ty.load().await {
             Type::NightBot => {
-                self.send_nightbot(&*self.inner, f.to_string()).await;
+                self.send_nightbot(&*self.inner, m).await;
             }
             Type::Chat => {
-                self.send(Command::PRIVMSG(self.inner.target.clone(), f.to_string()))
-                    .await;
+                self.send(Command::PRIVMSG((*self.target).clone(), m)).await;
             }
         }
     }
 
     /// Send a PRIVMSG without rate limiting.
     pub fn privmsg_immediate(&self, f: impl fmt::Display) {
-        self.send_immediate(Command::PRIVMSG(self.inner.target.clone(), f.to_string()))
+        self.send_immediate(Command::PRIVMSG((*self.target).clone(), f.to_string()))
     }
 
     /// Send a capability request.
@@ -1,4 +1,4 @@
-#![recursion_limit = "512"]
+#![recursion_limit = "1024"]
 #![cfg_attr(backtrace, feature(backtrace))]
 
 #[macro_use]
@@ -28,11 +28,14 @@ pub const VERSION: &str = version_str!();
 
 #[macro_use]
 mod macros;
+pub mod alerts;
 pub mod api;
 pub mod auth;
 mod backoff;
+pub mod backup;
 pub mod bus;
 mod command;
+pub mod config_file;
 pub mod currency;
 pub mod db;
 pub mod emotes;
@@ -44,6 +47,9 @@ pub mod oauth2;
 mod panic_logger;
 pub mod player;
 pub mod prelude;
+pub mod profiles;
+pub mod schedule;
+pub mod script;
 pub mod settings;
 mod song_file;
 mod spotify_id;
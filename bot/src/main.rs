@@ -4,9 +4,12 @@
 
 use anyhow::{anyhow, bail, Context, Result};
 use backoff::backoff::Backoff as _;
+use oxidize::alerts;
 use oxidize::api;
 use oxidize::auth;
+use oxidize::backup;
 use oxidize::bus;
+use oxidize::config_file;
 use oxidize::db;
 use oxidize::injector;
 use oxidize::irc;
@@ -15,6 +18,9 @@ use oxidize::module;
 use oxidize::oauth2;
 use oxidize::player;
 use oxidize::prelude::*;
+use oxidize::profiles;
+use oxidize::schedule;
+use oxidize::script;
 use oxidize::settings;
 use oxidize::storage;
 use oxidize::stream_info;
@@ -108,7 +114,7 @@ fn opts() -> clap::App<'static, 'static> {
             clap::Arg::with_name("config")
                 .long("config")
                 .value_name("file")
-                .help("Configuration file to use.")
+                .help("Configuration file with settings to apply, in TOML or YAML.")
                 .takes_value(true),
         )
         .arg(
@@ -135,6 +141,134 @@ fn opts() -> clap::App<'static, 'static> {
                 .long("silent")
                 .help("Start without sending a notification."),
         )
+        .subcommand(
+            clap::SubCommand::with_name("export")
+                .about("Export the configuration of the bot to an archive.")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .value_name("file")
+                        .help("File to write the archive to.")
+                        .required(true),
+                )
+                .arg(
+                    clap::Arg::with_name("secrets")
+                        .long("secrets")
+                        .help("Include secrets, like connection tokens, in the archive."),
+                )
+                .arg(
+                    clap::Arg::with_name("passphrase-stdin")
+                        .long("passphrase-stdin")
+                        .help("Read the passphrase to encrypt secrets with from stdin, instead of from the OXIDIZE_BACKUP_PASSPHRASE environment variable."),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("import")
+                .about("Import the configuration of the bot from an archive.")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .value_name("file")
+                        .help("File to read the archive from.")
+                        .required(true),
+                )
+                .arg(
+                    clap::Arg::with_name("passphrase-stdin")
+                        .long("passphrase-stdin")
+                        .help("Read the passphrase to decrypt secrets with from stdin, instead of from the OXIDIZE_BACKUP_PASSPHRASE environment variable."),
+                ),
+        )
+}
+
+/// Name of the API token created when no token can administer the bot.
+const ADMIN_TOKEN: &str = "admin";
+/// Environment variable to read the backup passphrase from.
+const PASSPHRASE_ENV: &str = "OXIDIZE_BACKUP_PASSPHRASE";
+
+/// Read the backup passphrase, either from the first line of stdin or from the
+/// environment.
+///
+/// NB: the passphrase is never accepted as an argument, since those are visible
+/// to other processes.
+fn backup_passphrase(m: &clap::ArgMatches<'_>) -> Result<Option<String>> {
+    let passphrase = if m.is_present("passphrase-stdin") {
+        let mut line = String::new();
+        std::io::stdin()
+            .read_line(&mut line)
+            .context("failed to read passphrase from stdin")?;
+        line.trim_end_matches(&['\r', '\n'][..]).to_string()
+    } else {
+        match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => passphrase,
+            Err(std::env::VarError::NotPresent) => return Ok(None),
+            Err(e) => bail!("bad {}: {}", PASSPHRASE_ENV, e),
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(passphrase))
+}
+
+/// Run a backup subcommand, like `export` or `import`.
+fn backup_command(db: &db::Database, m: &clap::ArgMatches<'_>) -> Result<()> {
+    let mut runtime = tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let settings = db.settings(settings::Schema::load_static()?)?;
+
+        settings
+            .run_migrations()
+            .await
+            .context("failed to run settings migrations")?;
+
+        match m.subcommand() {
+            ("export", Some(m)) => {
+                let path = m
+                    .value_of("file")
+                    .ok_or_else(|| anyhow!("missing <file>"))?;
+
+                let passphrase = backup_passphrase(m)?;
+
+                let options = backup::ExportOptions {
+                    secrets: m.is_present("secrets"),
+                    passphrase: passphrase.as_deref(),
+                };
+
+                let archive = backup::export(db, &settings, options).await?;
+                let f = std::fs::File::create(path)
+                    .with_context(|| anyhow!("failed to create archive: {}", path))?;
+                serde_json::to_writer_pretty(f, &archive)?;
+                log::info!("Exported configuration to: {}", path);
+            }
+            ("import", Some(m)) => {
+                let path = m
+                    .value_of("file")
+                    .ok_or_else(|| anyhow!("missing <file>"))?;
+
+                let f = std::fs::File::open(path)
+                    .with_context(|| anyhow!("failed to open archive: {}", path))?;
+                let archive = serde_json::from_reader(f)
+                    .with_context(|| anyhow!("failed to read archive: {}", path))?;
+                let passphrase = backup_passphrase(m)?;
+                backup::import(
+                    db,
+                    &settings,
+                    archive,
+                    passphrase.as_deref(),
+                    settings::Actor::Bot,
+                )
+                .await?;
+                log::info!("Imported configuration from: {}", path);
+            }
+            (other, _) => bail!("unsupported command: {}", other),
+        }
+
+        Ok::<_, anyhow::Error>(())
+    })
 }
 
 /// Setup tracing.
@@ -276,20 +410,6 @@ fn main() -> Result<()> {
         std::fs::create_dir_all(&root)?;
     }
 
-    let system = sys::setup(&root, &default_log_file)?;
-
-    let mut error_backoff = backoff::ExponentialBackoff::default();
-    error_backoff.current_interval = time::Duration::from_secs(5);
-    error_backoff.initial_interval = time::Duration::from_secs(5);
-    error_backoff.max_elapsed_time = None;
-
-    let is_silent = !m.is_present("silent");
-
-    if !is_silent {
-        let startup = sys::Notification::new(format!("Started Oxidize {}", oxidize::VERSION));
-        system.notification(startup);
-    }
-
     let database_path = {
         let new = root.join("oxidize.sql");
 
@@ -313,7 +433,26 @@ fn main() -> Result<()> {
     let db = db::Database::open(&database_path)
         .with_context(|| anyhow!("failed to open database at: {}", database_path.display()))?;
 
+    if m.subcommand_name().is_some() {
+        return backup_command(&db, &m);
+    }
+
+    let system = sys::setup(&root, &default_log_file)?;
+
+    let mut error_backoff = backoff::ExponentialBackoff::default();
+    error_backoff.current_interval = time::Duration::from_secs(5);
+    error_backoff.initial_interval = time::Duration::from_secs(5);
+    error_backoff.max_elapsed_time = None;
+
+    let is_silent = !m.is_present("silent");
+
+    if !is_silent {
+        let startup = sys::Notification::new(format!("Started Oxidize {}", oxidize::VERSION));
+        system.notification(startup);
+    }
+
     let storage = storage::Storage::open(&root.join("storage"))?;
+    let config = m.value_of("config").map(PathBuf::from);
 
     loop {
         let mut runtime = tokio::runtime::Builder::new()
@@ -322,7 +461,7 @@ fn main() -> Result<()> {
             .build()?;
 
         let future = {
-            try_main(&system, &root, &db, &storage)
+            try_main(&system, &root, config.as_deref(), &db, &storage)
                 .instrument(trace_span!(target: "futures", "main",))
         };
 
@@ -393,6 +532,7 @@ fn main() -> Result<()> {
 async fn try_main(
     system: &sys::System,
     root: &Path,
+    config: Option<&Path>,
     db: &db::Database,
     storage: &storage::Storage,
 ) -> Result<Intent> {
@@ -430,6 +570,18 @@ async fn try_main(
         .await
         .context("failed to run settings migrations")?;
 
+    if let Some(config) = config {
+        let values = config_file::load(config, &settings)
+            .with_context(|| anyhow!("failed to load configuration: {}", config.display()))?;
+        settings.set_config_file(values).await?;
+
+        futures.push(
+            config_file::watch(config.to_owned(), settings.clone())
+                .boxed()
+                .instrument(trace_span!(target: "futures", "config-file",)),
+        );
+    }
+
     injector.update(settings.clone()).await;
 
     let bad_words = db::Words::load(db.clone()).await?;
@@ -444,6 +596,42 @@ async fn try_main(
         .await;
     injector.update(db::Themes::load(db.clone()).await?).await;
 
+    injector
+        .update(profiles::Profiles::load(db.clone(), settings.clone()).await?)
+        .await;
+
+    let api_tokens = db::ApiTokens::load(db.clone()).await?;
+
+    // NB: make sure there's a token which can be used to administer the bot if
+    // `web/require-token` is enabled.
+    let admin_token = if !api_tokens.any_with_scope(auth::Scope::WebAdmin).await {
+        match api_tokens
+            .create(ADMIN_TOKEN, vec![auth::Scope::WebAdmin])
+            .await
+        {
+            Ok(token) => Some(token),
+            Err(e) => {
+                oxidize::log_error!(e, "Failed to create the `{}` API token", ADMIN_TOKEN);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    injector.update(api_tokens).await;
+
+    let schedule = schedule::Schedule::load(db.clone(), settings.clone()).await?;
+    injector.update(schedule.clone()).await;
+
+    let chat_messages = db::ChatMessages::load(db.clone()).await?;
+    injector.update(chat_messages.clone()).await;
+
+    injector.update(db::Clips::load(db.clone()).await?).await;
+    injector
+        .update(db::UserTimeZones::load(db.clone()).await?)
+        .await;
+
     let message_bus = Arc::new(bus::Bus::new());
     let global_bus = Arc::new(bus::Bus::new());
     let youtube_bus = Arc::new(bus::Bus::new());
@@ -473,6 +661,35 @@ async fn try_main(
             .instrument(trace_span!(target: "futures", "remote-updates",)),
     );
 
+    futures.push(
+        purge_chat_messages(settings.scoped("chat-log"), chat_messages)
+            .boxed()
+            .instrument(trace_span!(target: "futures", "purge-chat-messages",)),
+    );
+
+    futures.push(
+        auth.clone()
+            .purge_expired_grants()
+            .boxed()
+            .instrument(trace_span!(target: "futures", "purge-expired-grants",)),
+    );
+
+    futures.push(
+        schedule
+            .run()
+            .boxed()
+            .instrument(trace_span!(target: "futures", "schedule",)),
+    );
+
+    let (alerts, future) = alerts::setup(settings.scoped("alerts"), global_bus.clone()).await?;
+    injector.update(alerts).await;
+
+    futures.push(
+        future
+            .boxed()
+            .instrument(trace_span!(target: "futures", "alerts",)),
+    );
+
     let message_log = message_log::MessageLog::builder()
         .bus(message_bus.clone())
         .limit(512)
@@ -488,6 +705,7 @@ async fn try_main(
         auth.clone(),
         global_channel.clone(),
         latest.clone(),
+        settings.clone(),
     )
     .await?;
 
@@ -498,7 +716,23 @@ async fn try_main(
             .instrument(trace_span!(target: "futures", "web")),
     );
 
-    if settings.get::<bool>("first-run").await?.unwrap_or(true) {
+    if let Some(token) = admin_token {
+        // NB: the web interface stores the token and removes it from the URL.
+        let url = format!("{}/?api-token={}", web::URL, token);
+        log::info!(
+            "Created the `{}` API token, opening {}",
+            ADMIN_TOKEN,
+            web::URL
+        );
+
+        if let Err(e) = webbrowser::open(&url) {
+            log::error!("failed to open browser: {}", e);
+            // NB: only print the token to the terminal, never to the log.
+            eprintln!("Open the following URL to use the web interface: {}", url);
+        }
+
+        settings.set("first-run", false).await?;
+    } else if settings.get::<bool>("first-run").await?.unwrap_or(true) {
         log::info!("Opening {} for the first time", web::URL);
 
         if let Err(e) = webbrowser::open(web::URL) {
@@ -669,6 +903,16 @@ async fn try_main(
     // load the song module if we have a player configuration.
     injector.update(player).await;
 
+    let scripts = script::Scripts::load(root.join("scripts"), &injector, settings.clone()).await?;
+    injector.update(scripts.clone()).await;
+
+    futures.push(
+        scripts
+            .watch()
+            .boxed()
+            .instrument(trace_span!(target: "futures", "scripts",)),
+    );
+
     futures.push(
         api::setbac::run(&settings, &injector, global_bus.clone())
             .await?
@@ -686,16 +930,21 @@ async fn try_main(
     modules.push(Box::new(module::swearjar::Module));
     modules.push(Box::new(module::countdown::Module));
     modules.push(Box::new(module::gtav::Module));
+    modules.push(Box::new(module::game_bridge::Module));
     modules.push(Box::new(module::water::Module));
     modules.push(Box::new(module::misc::Module));
     modules.push(Box::new(module::after_stream::Module));
     modules.push(Box::new(module::clip::Module));
     modules.push(Box::new(module::eight_ball::Module));
     modules.push(Box::new(module::speedrun::Module));
+    modules.push(Box::new(module::livesplit::Module));
     modules.push(Box::new(module::auth::Module));
     modules.push(Box::new(module::poll::Module));
     modules.push(Box::new(module::weather::Module));
     modules.push(Box::new(module::help::Module));
+    modules.push(Box::new(module::alerts::Module));
+    modules.push(Box::new(module::profiles::Module));
+    modules.push(Box::new(module::script::Module));
 
     let (stream_state_tx, stream_state_rx) = mpsc::channel(64);
 
@@ -797,6 +1046,36 @@ async fn notify_after_streams(
     }
 }
 
+/// Periodically remove stored chat messages which are older than the
+/// configured retention.
+async fn purge_chat_messages(
+    settings: settings::Settings,
+    chat_messages: db::ChatMessages,
+) -> Result<()> {
+    let persist = settings.var("persist", false).await?;
+    let retention = settings
+        .var("persist-retention", utils::Duration::hours(24 * 30))
+        .await?;
+
+    let mut interval = tokio::time::interval(time::Duration::from_secs(3600));
+
+    loop {
+        interval.tick().await;
+
+        if !persist.load().await {
+            continue;
+        }
+
+        let before = chrono::Utc::now() - retention.load().await.as_chrono();
+
+        match chat_messages.purge_before(before).await {
+            Ok(0) => (),
+            Ok(count) => log::info!("Removed {} old chat messages", count),
+            Err(e) => oxidize::log_error!(e, "Failed to remove old chat messages"),
+        }
+    }
+}
+
 /// Run the loop that handles installing this as a service.
 async fn system_loop(settings: settings::Settings, system: sys::System) -> Result<()> {
     settings
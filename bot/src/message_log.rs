@@ -19,11 +19,23 @@ pub enum Event {
     #[serde(rename = "delete-by-id")]
     DeleteById { id: String },
     /// Indicates that all messages by the given user have been deleted.
+    ///
+    /// `channel` is set if the messages were relayed from another channel.
     #[serde(rename = "delete-by-user")]
-    DeleteByUser { name: String },
+    DeleteByUser {
+        name: String,
+        #[serde(default)]
+        channel: Option<String>,
+    },
     /// Delete all messages.
+    ///
+    /// `channel` is set if only messages relayed from another channel were
+    /// deleted.
     #[serde(rename = "delete-all")]
-    DeleteAll,
+    DeleteAll {
+        #[serde(default)]
+        channel: Option<String>,
+    },
 }
 
 impl bus::Message for Event {
@@ -120,11 +132,13 @@ impl MessageLog {
     }
 
     /// Mark all messages by the given user as deleted.
-    pub async fn delete_by_user(&self, name: &str) {
+    ///
+    /// `channel` is set to only delete messages relayed from another channel.
+    pub async fn delete_by_user(&self, channel: Option<&str>, name: &str) {
         let mut inner = self.inner.write().await;
 
         for m in &mut inner.messages {
-            if m.user.name == name {
+            if m.channel.as_deref() == channel && m.user.name == name {
                 m.deleted = true;
             }
         }
@@ -132,28 +146,39 @@ impl MessageLog {
         if let Some(bus) = inner.bus.as_ref() {
             bus.send(Event::DeleteByUser {
                 name: name.to_string(),
+                channel: channel.map(String::from),
             })
             .await;
         }
     }
 
     /// Delete all messages in chat.
-    pub async fn delete_all(&self) {
+    ///
+    /// `channel` is set to only delete messages relayed from another channel.
+    pub async fn delete_all(&self, channel: Option<&str>) {
         let mut inner = self.inner.write().await;
 
         for m in &mut inner.messages {
-            m.deleted = true;
+            if m.channel.as_deref() == channel {
+                m.deleted = true;
+            }
         }
 
         if let Some(bus) = inner.bus.as_ref() {
-            bus.send(Event::DeleteAll).await;
+            bus.send(Event::DeleteAll {
+                channel: channel.map(String::from),
+            })
+            .await;
         }
     }
 
     /// Push a message to the back of the log.
+    ///
+    /// `channel` is set if the message was relayed from another channel.
     pub async fn push_back(
         &self,
         tags: &irc::Tags,
+        channel: Option<&str>,
         name: &str,
         text: &str,
         rendered: Option<emotes::Rendered>,
@@ -195,6 +220,7 @@ impl MessageLog {
         let m = Message {
             timestamp: Utc::now(),
             id: id.to_string(),
+            channel: channel.map(String::from),
             user,
             text: text.to_string(),
             rendered,
@@ -221,6 +247,9 @@ pub struct User {
 pub struct Message {
     timestamp: DateTime<Utc>,
     id: String,
+    /// The channel the message was relayed from, if any.
+    #[serde(default)]
+    channel: Option<String>,
     user: User,
     text: String,
     rendered: Option<emotes::Rendered>,
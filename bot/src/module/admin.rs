@@ -3,8 +3,12 @@ use crate::command;
 use crate::db;
 use crate::module;
 use crate::prelude::*;
+use crate::profiles;
+use crate::schedule;
 use crate::settings;
+use crate::utils;
 use anyhow::Result;
+use chrono::{DateTime, NaiveTime, Utc};
 
 /// Handler for the !admin command.
 pub struct Handler {
@@ -13,6 +17,10 @@ pub struct Handler {
     commands: injector::Var<Option<db::Commands>>,
     promotions: injector::Var<Option<db::Promotions>>,
     themes: injector::Var<Option<db::Themes>>,
+    profiles: injector::Var<Option<profiles::Profiles>>,
+    schedule: injector::Var<Option<schedule::Schedule>>,
+    /// If this is the handler for the streamer's channel.
+    primary: bool,
 }
 
 impl Handler {
@@ -103,7 +111,9 @@ impl command::Handler for Handler {
                     .unwrap_or_default();
 
                 values.push(value);
-                self.settings.set(&key, values).await?;
+                self.settings
+                    .set_json_by(&key, serde_json::to_value(values)?, ctx.actor())
+                    .await?;
                 respond!(ctx, "Updated the {} setting", key);
             }
             // Delete a value from a setting.
@@ -118,12 +128,17 @@ impl command::Handler for Handler {
                     .unwrap_or_default();
 
                 values.retain(|v| v != &value);
-                self.settings.set(&key, values).await?;
+                self.settings
+                    .set_json_by(&key, serde_json::to_value(values)?, ctx.actor())
+                    .await?;
                 respond!(ctx, "Updated the {} setting", key);
             }
             Some("toggle") => {
                 self.toggle(ctx).await?;
             }
+            Some("schedule") => {
+                self.schedule(ctx).await?;
+            }
             Some("enable-group") => {
                 let group = ctx
                     .next()
@@ -170,6 +185,46 @@ impl command::Handler for Handler {
 
                 respond!(ctx, "Disabled group {}", group);
             }
+            // Activate a settings profile.
+            Some("profile") => {
+                // NB: profiles apply to the settings of all channels.
+                if !self.primary {
+                    respond_bail!(
+                        "Settings profiles can only be activated in the streamer's channel"
+                    );
+                }
+
+                let profiles = match self.profiles.load().await {
+                    Some(profiles) => profiles,
+                    None => {
+                        respond_bail!("Settings profiles are not available");
+                    }
+                };
+
+                let name = match ctx.next() {
+                    Some(name) => name,
+                    None => {
+                        let names = profiles
+                            .list()
+                            .await?
+                            .into_iter()
+                            .map(|p| p.name)
+                            .collect::<Vec<_>>();
+
+                        if names.is_empty() {
+                            respond_bail!("No settings profiles available");
+                        }
+
+                        respond_bail!("Expected one of: {}", names.join(", "));
+                    }
+                };
+
+                if !profiles.activate(&name, ctx.actor()).await? {
+                    respond_bail!("No settings profile named `{}`", name);
+                }
+
+                respond!(ctx, "Activated settings profile `{}`", name);
+            }
             // Get or set settings.
             Some("settings") => {
                 let key = key(ctx)?;
@@ -210,7 +265,7 @@ impl command::Handler for Handler {
                         }
 
                         let value_string = serde_json::to_string(&value)?;
-                        self.settings.set_json(&key, value).await?;
+                        self.settings.set_json_by(&key, value, ctx.actor()).await?;
                         respond!(ctx, "Updated setting {} = {}", key, value_string);
                     }
                 }
@@ -223,6 +278,8 @@ impl command::Handler for Handler {
                      refresh-vips, \
                      version, \
                      shutdown, \
+                     profile, \
+                     schedule, \
                      setting.",
                 );
             }
@@ -275,11 +332,185 @@ impl Handler {
         };
 
         let value_string = serde_json::to_string(&toggled)?;
-        self.settings.set_json(&key, toggled).await?;
+        self.settings
+            .set_json_by(&key, toggled, ctx.actor())
+            .await?;
         respond!(ctx, "Updated setting {} = {}", key, value_string);
         Ok(())
     }
 
+    /// Handler for the schedule command.
+    ///
+    /// Changes are scheduled with any combination of `at <HH:MM>`,
+    /// `in <duration>`, and `for <duration>` before the key and value.
+    async fn schedule(&self, ctx: &mut command::Context) -> Result<(), anyhow::Error> {
+        let schedule = match self.schedule.load().await {
+            Some(schedule) => schedule,
+            None => {
+                respond_bail!("Scheduling settings is not available");
+            }
+        };
+
+        let mut run_at = None;
+        let mut revert_after = None;
+
+        let key = loop {
+            let arg = ctx.next_str("`list`, `cancel <id>`, or <key>")?;
+
+            match arg.as_str() {
+                "list" => {
+                    let scheduled = schedule
+                        .list()
+                        .await?
+                        .into_iter()
+                        .filter(|s| self.local_key(&s.key).is_some())
+                        .collect::<Vec<_>>();
+
+                    if scheduled.is_empty() {
+                        respond_bail!("No settings changes are scheduled");
+                    }
+
+                    let now = Utc::now();
+
+                    let scheduled = scheduled
+                        .iter()
+                        .take(5)
+                        .map(|s| {
+                            let value = match &s.value {
+                                Some(value) => value.to_string(),
+                                None => String::from("(cleared)"),
+                            };
+
+                            format!(
+                                "#{} {} = {} in {}",
+                                s.id,
+                                self.local_key(&s.key).unwrap_or_default(),
+                                value,
+                                until(now, s.run_at)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    respond!(ctx, "{}", scheduled.join(", "));
+                    return Ok(());
+                }
+                "cancel" => {
+                    let id = ctx.next_parse::<i32, _>("<id>")?;
+
+                    // NB: only changes to settings in this channel can be
+                    // cancelled.
+                    let local = schedule
+                        .list()
+                        .await?
+                        .iter()
+                        .any(|s| s.id == id && self.local_key(&s.key).is_some());
+
+                    if !local || !schedule.cancel(id).await? {
+                        respond_bail!("No scheduled change with id #{}", id);
+                    }
+
+                    respond!(ctx, "Cancelled scheduled change #{}", id);
+                    return Ok(());
+                }
+                "at" => {
+                    let time = ctx.next_str("<HH:MM>")?;
+
+                    let time = NaiveTime::parse_from_str(&time, "%H:%M")
+                        .map_err(|_| respond_err!("Bad time `{}`, expected <HH:MM>", time))?;
+
+                    run_at = Some(schedule.next_time(time).await?);
+                }
+                "in" => {
+                    let delay = ctx.next_parse::<utils::Duration, _>("<duration>")?;
+                    run_at = Some(Utc::now() + delay.as_chrono());
+                }
+                "for" => {
+                    revert_after = Some(ctx.next_parse::<utils::Duration, _>("<duration>")?);
+                }
+                _ => break arg,
+            }
+        };
+
+        if run_at.is_none() && revert_after.is_none() {
+            respond_bail!(
+                "Expected `at <HH:MM>`, `in <duration>`, or `for <duration>` before the key"
+            );
+        }
+
+        if key.starts_with("secrets/") {
+            respond_bail!("Cannot access secrets through chat!");
+        }
+
+        let schema = self
+            .settings
+            .lookup(&key)
+            .ok_or_else(|| respond_err!("No such setting"))?;
+
+        if let Some(scope) = schema.scope {
+            if !ctx.user.has_scope(scope).await {
+                respond_bail!("You are not permitted to modify that setting, sorry :(");
+            }
+        }
+
+        let value = match ctx.rest().trim() {
+            "" => {
+                respond_bail!("Expected <value>");
+            }
+            value => schema
+                .ty
+                .parse_as_json(value)
+                .map_err(|e| respond_err!("Value is not a valid {} type: {}", schema.ty, e))?,
+        };
+
+        let now = Utc::now();
+        let run_at = run_at.unwrap_or(now);
+        let value_string = serde_json::to_string(&value)?;
+
+        // NB: changes are applied through the unscoped settings, so the key
+        // is stored with the scope of the channel it was scheduled in.
+        let id = schedule
+            .schedule(
+                &self.settings.full_key(&key),
+                Some(value),
+                run_at,
+                revert_after,
+                ctx.actor(),
+            )
+            .await?;
+
+        let mut response = format!(
+            "Scheduled #{}: {} = {} in {}",
+            id,
+            key,
+            value_string,
+            until(now, run_at)
+        );
+
+        if let Some(revert_after) = revert_after {
+            response.push_str(&format!(", reverting after {}", revert_after));
+        }
+
+        respond!(ctx, response);
+        Ok(())
+    }
+
+    /// Get the key relative to the settings of this channel from the given
+    /// full key, or `None` if it belongs to another channel.
+    fn local_key<'a>(&self, key: &'a str) -> Option<&'a str> {
+        let scope = self.settings.full_key("");
+
+        if scope.is_empty() {
+            return Some(key);
+        }
+
+        if key.len() > scope.len() && key.starts_with(&scope) && key[scope.len()..].starts_with('/')
+        {
+            return Some(&key[scope.len() + 1..]);
+        }
+
+        None
+    }
+
     /// Parse the rest of the context as a value corresponding to the given set.
     ///
     /// Also tests that we have the permission to modify the specified setting.
@@ -322,6 +553,11 @@ impl Handler {
     }
 }
 
+/// Format the time from `now` until `then` in a compact form.
+fn until(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    utils::compact_duration((then - now).to_std().unwrap_or_default())
+}
+
 /// Extract a settings key from the context.
 fn key(ctx: &mut command::Context) -> Result<String> {
     let key = ctx.next().ok_or_else(|| respond_err!("Expected <key>"))?;
@@ -347,6 +583,7 @@ impl super::Module for Module {
             injector,
             handlers,
             settings,
+            primary,
             ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
@@ -358,6 +595,9 @@ impl super::Module for Module {
                 commands: injector.var().await?,
                 promotions: injector.var().await?,
                 themes: injector.var().await?,
+                profiles: injector.var().await?,
+                schedule: injector.var().await?,
+                primary,
             },
         );
 
@@ -0,0 +1,171 @@
+use crate::alerts;
+use crate::api;
+use crate::module;
+use crate::prelude::*;
+use crate::utils;
+use chrono::{DateTime, Utc};
+
+pub struct Module;
+
+#[async_trait]
+impl super::Module for Module {
+    fn ty(&self) -> &'static str {
+        "alerts"
+    }
+
+    fn primary_only(&self) -> bool {
+        true
+    }
+
+    /// Set up polling for new followers, which are not announced in chat.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            futures,
+            settings,
+            stream_info,
+            streamer_twitch,
+            ..
+        }: module::HookContext<'_>,
+    ) -> Result<(), anyhow::Error> {
+        let settings = settings.scoped("alerts");
+        let enabled = settings.var("enabled", false).await?;
+        let follow_enabled = settings.var("follow/enabled", true).await?;
+
+        let (mut interval_stream, interval) = settings
+            .stream("follow/poll-interval")
+            .or_with(utils::Duration::seconds(60))
+            .await?;
+
+        let alerts = injector.var::<alerts::Alerts>().await?;
+        let streamer_twitch = streamer_twitch.clone();
+        let streamer_id = stream_info.user.id.clone();
+        let mut interval = tokio::time::interval(interval.as_std()).fuse();
+
+        let future = async move {
+            let mut last = None::<DateTime<Utc>>;
+
+            loop {
+                futures::select! {
+                    update = interval_stream.select_next_some() => {
+                        interval = tokio::time::interval(update.as_std()).fuse();
+                    }
+                    _ = interval.select_next_some() => {
+                        if !enabled.load().await || !follow_enabled.load().await {
+                            continue;
+                        }
+
+                        let follows = match streamer_twitch.new_followers(&streamer_id).await {
+                            Ok(follows) => follows,
+                            Err(e) => {
+                                log_warn!(e, "Failed to poll for new followers");
+                                continue;
+                            }
+                        };
+
+                        let handle = alerts.load().await;
+                        last = notify_follows(handle.as_ref(), last, follows);
+                    }
+                }
+            }
+        };
+
+        futures.push(future.boxed());
+        Ok(())
+    }
+}
+
+/// Queue alerts for all follows newer than `last`, returning the time of the
+/// newest follow seen.
+///
+/// The first poll only records the newest follow, so that we don't alert on
+/// old followers when starting up.
+fn notify_follows(
+    alerts: Option<&alerts::Alerts>,
+    last: Option<DateTime<Utc>>,
+    follows: Vec<api::twitch::Follow>,
+) -> Option<DateTime<Utc>> {
+    let newest = follows.iter().map(|f| f.followed_at).max();
+
+    let last = match last {
+        Some(last) => last,
+        None => return newest,
+    };
+
+    if let Some(alerts) = alerts {
+        // NB: follows are listed newest first.
+        for follow in follows.into_iter().rev() {
+            if follow.followed_at <= last {
+                continue;
+            }
+
+            let alert = alerts::Alert {
+                kind: alerts::AlertKind::Follow,
+                name: follow.from_name,
+                amount: None,
+                message: None,
+            };
+
+            if let Err(e) = alerts.push(alert) {
+                log_warn!(e, "Failed to queue follower alert");
+            }
+        }
+    }
+
+    Some(newest.map(|n| n.max(last)).unwrap_or(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::notify_follows;
+    use crate::alerts;
+    use crate::api::twitch::Follow;
+    use chrono::{DateTime, TimeZone as _, Utc};
+
+    fn follow(name: &str, followed_at: DateTime<Utc>) -> Follow {
+        Follow {
+            from_id: String::from("1"),
+            from_name: name.to_string(),
+            to_id: String::from("2"),
+            to_name: String::from("setbac"),
+            followed_at,
+        }
+    }
+
+    #[test]
+    fn test_notify_follows() {
+        let (handle, mut rx) = alerts::Alerts::channel();
+        let t = |s| Utc.timestamp(s, 0);
+
+        // First poll only records the newest follow.
+        let last = notify_follows(
+            Some(&handle),
+            None,
+            vec![follow("b", t(20)), follow("a", t(10))],
+        );
+        assert_eq!(Some(t(20)), last);
+        assert!(rx.try_next().is_err());
+
+        // Only follows newer than the last one are alerted, oldest first.
+        let last = notify_follows(
+            Some(&handle),
+            last,
+            vec![follow("d", t(40)), follow("c", t(30)), follow("b", t(20))],
+        );
+        assert_eq!(Some(t(40)), last);
+
+        let names = std::iter::from_fn(|| rx.try_next().ok().flatten())
+            .map(|a| a.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["c", "d"], names);
+
+        // An empty poll keeps the last follow.
+        assert_eq!(Some(t(40)), notify_follows(Some(&handle), last, vec![]));
+        // Without a queue, we still keep track of the newest follow.
+        assert_eq!(
+            Some(t(50)),
+            notify_follows(None, last, vec![follow("e", t(50))])
+        );
+    }
+}
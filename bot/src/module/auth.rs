@@ -1,10 +1,11 @@
 use crate::auth;
 use crate::command;
+use crate::db;
 use crate::module;
 use crate::prelude::*;
-use crate::utils::Duration;
+use crate::utils::{self, Duration};
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 /// Handler for the !auth command.
 pub struct Handler {
@@ -47,8 +48,8 @@ impl command::Handler for Handler {
                     ));
                 }
 
-                for role in user.roles() {
-                    let by_role = filter(self.auth.scopes_for_role(role).await);
+                for role in user.roles().await {
+                    let by_role = filter(self.auth.scopes_for_role(role.clone()).await);
 
                     if !by_role.is_empty() {
                         result.push(format!("{}: {}", role, by_role.join(", ")));
@@ -61,7 +62,8 @@ impl command::Handler for Handler {
                 ctx.check_scope(auth::Scope::AuthPermit).await?;
 
                 let duration: Duration = ctx.next_parse("<duration> <principal> <scope>")?;
-                let principal = ctx.next_parse("<duration> <principal> <scope>")?;
+                let principal: auth::RoleOrUser =
+                    ctx.next_parse("<duration> <principal> <scope>")?;
                 let scope = ctx.next_parse("<duration> <principal> <scope>")?;
 
                 if !ctx.user.has_scope(scope).await {
@@ -76,6 +78,10 @@ impl command::Handler for Handler {
                 let now = Utc::now();
                 let expires_at = now + duration.as_chrono();
 
+                self.auth
+                    .insert_temporary(scope, principal.clone(), expires_at)
+                    .await?;
+
                 respond!(
                     ctx,
                     "Gave: {scope} to {principal} for {duration}",
@@ -83,13 +89,99 @@ impl command::Handler for Handler {
                     principal = principal,
                     scope = scope
                 );
+            }
+            Some("explain") => {
+                ctx.check_scope(auth::Scope::AuthExplain).await?;
+
+                let user = db::user_id(&ctx.next_str("<user> <scope>")?);
+                let scope: auth::Scope = ctx.next_parse("<user> <scope>")?;
+
+                if scope == auth::Scope::Unknown {
+                    respond!(ctx, "No such scope");
+                    return Ok(());
+                }
+
+                let roles = match ctx.user.real() {
+                    Some(requester) => requester.base_roles_of(&user),
+                    None => smallvec::smallvec![auth::Role::Everyone],
+                };
+
+                let explanation = self.auth.explain(scope, &user, roles).await;
+                respond!(ctx, describe(&explanation, Utc::now()));
+            }
+            Some("role") => {
+                ctx.check_scope(auth::Scope::AuthRoles).await?;
+                self.handle_role(ctx).await?;
+            }
+            _ => {
+                respond!(ctx, "Expected: scopes, permit, explain, role");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Handler {
+    /// Handle the `!auth role` command.
+    async fn handle_role(&self, ctx: &mut command::Context) -> Result<()> {
+        match ctx.next().as_deref() {
+            Some("add") => {
+                let role = next_custom_role(ctx, "<role> <user>")?;
+                let user = ctx.next_str("<role> <user>")?;
+
+                self.auth.insert_member(role.clone(), &user).await?;
+                respond!(ctx, "Added {} to {}", user, role);
+            }
+            Some("remove") => {
+                let role = next_custom_role(ctx, "<role> <user>")?;
+                let user = ctx.next_str("<role> <user>")?;
+
+                if self.auth.delete_member(role.clone(), &user).await? {
+                    respond!(ctx, "Removed {} from {}", user, role);
+                } else {
+                    respond!(ctx, "{} is not a member of {}", user, role);
+                }
+            }
+            Some("inherit") => {
+                let role = next_custom_role(ctx, "<role> [role...]")?;
+
+                let mut inherits = Vec::new();
+
+                while let Some(parent) = ctx.next() {
+                    match str::parse::<auth::Role>(&parent)? {
+                        auth::Role::Unknown => {
+                            respond_bail!("No such role: {}", parent);
+                        }
+                        auth::Role::Streamer | auth::Role::Owner => {
+                            respond_bail!("Roles can't inherit {}", parent);
+                        }
+                        parent => inherits.push(parent),
+                    }
+                }
 
                 self.auth
-                    .insert_temporary(scope, principal, expires_at)
-                    .await;
+                    .put_role(role.clone(), None, inherits.clone())
+                    .await?;
+
+                if inherits.is_empty() {
+                    respond!(ctx, "{} no longer inherits any roles", role);
+                } else {
+                    let inherits = inherits.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+                    respond!(ctx, "{} now inherits {}", role, inherits.join(", "));
+                }
+            }
+            Some("delete") => {
+                let role = next_custom_role(ctx, "<role>")?;
+
+                if self.auth.delete_role(role.clone()).await? {
+                    respond!(ctx, "Deleted {}", role);
+                } else {
+                    respond!(ctx, "No custom role named {}", role);
+                }
             }
             _ => {
-                respond!(ctx, "Expected: scopes, permit");
+                respond!(ctx, "Expected: add, remove, inherit, delete");
             }
         }
 
@@ -97,6 +189,58 @@ impl command::Handler for Handler {
     }
 }
 
+/// Describe the given explanation in a single line.
+fn describe(e: &auth::Explanation, now: DateTime<Utc>) -> String {
+    let risk = match e.risk {
+        auth::Risk::High => " (high risk)",
+        auth::Risk::Default => "",
+    };
+
+    let verdict = if e.allowed {
+        let grants = e
+            .grants
+            .iter()
+            .map(|g| match g {
+                auth::Grant::Permanent { role } => format!("granted to {}", role),
+                auth::Grant::Temporary {
+                    principal,
+                    expires_at,
+                } => format!(
+                    "temporarily granted to {} for {}",
+                    principal,
+                    utils::compact_duration((*expires_at - now).to_std().unwrap_or_default())
+                ),
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{} can use {}{}: {}",
+            e.user,
+            e.scope,
+            risk,
+            grants.join(", ")
+        )
+    } else {
+        format!(
+            "{} can't use {}{}: no grant allows it",
+            e.user, e.scope, risk
+        )
+    };
+
+    let roles = e.roles.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    format!("{}. Roles: {}", verdict, roles.join(", "))
+}
+
+/// Parse the next argument as a custom role.
+fn next_custom_role(ctx: &mut command::Context, m: &str) -> Result<auth::Role> {
+    match ctx.next_parse(m)? {
+        role @ auth::Role::Custom(..) => Ok(role),
+        _ => {
+            respond_bail!("Expected {}, where <role> is a custom role like @editor", m);
+        }
+    }
+}
+
 pub struct Module;
 
 impl Module {
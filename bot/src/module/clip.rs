@@ -1,11 +1,21 @@
 use crate::api;
 use crate::auth;
 use crate::command;
+use crate::db;
 use crate::module;
 use crate::prelude::*;
 use crate::stream_info;
 use crate::utils::{Cooldown, Duration};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::{header, Client, Method, Url};
+use std::time;
+
+/// How many clips are shown by `!clip list`.
+const LIST_LIMIT: i64 = 3;
+
+/// Discord refuses webhook messages longer than this.
+const WEBHOOK_MAX_LENGTH: usize = 2000;
 
 /// Handler for the `!clip` command.
 pub struct Clip {
@@ -13,6 +23,25 @@ pub struct Clip {
     pub stream_info: stream_info::StreamInfo,
     pub clip_cooldown: settings::Var<Cooldown>,
     pub twitch: api::Twitch,
+    pub clips: injector::Var<Option<db::Clips>>,
+}
+
+impl Clip {
+    /// List the last few clips.
+    async fn list(&self, ctx: &mut command::Context) -> Result<()> {
+        let clips = match self.clips.load().await {
+            Some(clips) => clips,
+            None => {
+                respond!(ctx, "Clips are not available right now");
+                return Ok(());
+            }
+        };
+
+        let clips = clips.list_by_channel(ctx.channel(), LIST_LIMIT).await?;
+        let clips = clips.iter().map(describe).collect::<Vec<_>>();
+        ctx.respond_lines(clips, "No clips yet").await;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -26,6 +55,10 @@ impl command::Handler for Clip {
             return Ok(());
         }
 
+        if ctx.rest().trim() == "list" {
+            return self.list(ctx).await;
+        }
+
         if !self.clip_cooldown.write().await.is_open() {
             respond!(ctx, "A clip was already created recently");
             return Ok(());
@@ -49,8 +82,30 @@ impl command::Handler for Clip {
                     clip.id
                 );
 
-                if let Some(_title) = title {
-                    log::warn!("Title was requested, but it can't be set (right now)")
+                let clips = match self.clips.load().await {
+                    Some(clips) => clips,
+                    None => return Ok(()),
+                };
+
+                let user = ctx
+                    .user
+                    .display_name()
+                    .or_else(|| ctx.user.name())
+                    .unwrap_or("unknown");
+
+                let game = self.stream_info.data.read().game.clone();
+
+                if let Err(e) = clips
+                    .push(
+                        ctx.channel(),
+                        &clip.id,
+                        user,
+                        title.as_deref(),
+                        game.as_deref(),
+                    )
+                    .await
+                {
+                    log_error!(e, "failed to record clip");
                 }
             }
             None => {
@@ -63,6 +118,111 @@ impl command::Handler for Clip {
     }
 }
 
+/// Sends a digest of the clips created during a stream to a webhook once the
+/// stream ends.
+struct Digest {
+    channel: String,
+    enabled: settings::Var<bool>,
+    webhook_url: settings::Var<Option<String>>,
+    clips: injector::Var<Option<db::Clips>>,
+    client: Client,
+}
+
+impl Digest {
+    /// Send a digest of all clips created since the stream started.
+    async fn send(&self, started_at: DateTime<Utc>) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let url = match self.webhook_url.load().await {
+            Some(url) => str::parse::<Url>(&url)?,
+            None => return Ok(()),
+        };
+
+        let clips = match self.clips.load().await {
+            Some(clips) => clips.list_since(&self.channel, started_at).await?,
+            None => return Ok(()),
+        };
+
+        if clips.is_empty() {
+            return Ok(());
+        }
+
+        let content = digest(&clips);
+        let body = serde_json::to_vec(&Webhook { content: &content })?;
+
+        let req = api::RequestBuilder::new(self.client.clone(), Method::POST, url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body);
+
+        req.execute().await?.ok()?;
+
+        log::info!("Sent digest of {} clip(s)", clips.len());
+        return Ok(());
+
+        /// Body of the webhook, compatible with Discord.
+        #[derive(serde::Serialize)]
+        struct Webhook<'a> {
+            content: &'a str,
+        }
+    }
+}
+
+/// Build the digest message for the given clips, leaving out the clips which
+/// don't fit in a single webhook message.
+fn digest(clips: &[db::Clip]) -> String {
+    let lines = clips
+        .iter()
+        .map(|clip| format!("\n- {}", describe(clip)))
+        .collect::<Vec<_>>();
+
+    let header = format!("{} clip(s) from the stream:", clips.len());
+
+    if header.len() + lines.iter().map(String::len).sum::<usize>() <= WEBHOOK_MAX_LENGTH {
+        return header + &lines.concat();
+    }
+
+    // NB: leave room for the longest header we could end up using.
+    let mut len = truncated_header(clips.len(), clips.len()).len();
+    let mut shown = 0;
+
+    for line in &lines {
+        if len + line.len() > WEBHOOK_MAX_LENGTH {
+            break;
+        }
+
+        len += line.len();
+        shown += 1;
+    }
+
+    truncated_header(shown, clips.len()) + &lines[..shown].concat()
+}
+
+/// Header of a digest which only shows some of the clips.
+fn truncated_header(shown: usize, total: usize) -> String {
+    format!("Showing {} of {} clip(s) from the stream:", shown, total)
+}
+
+/// Describe a clip in a single line.
+fn describe(clip: &db::Clip) -> String {
+    let title = clip.title.as_deref().unwrap_or("Untitled");
+
+    let game = match clip.game.as_deref() {
+        Some(game) => format!(" ({})", game),
+        None => String::new(),
+    };
+
+    format!(
+        "{}{} by {}: {}/{}",
+        title,
+        game,
+        clip.user,
+        api::twitch::CLIPS_URL,
+        clip.clip_id
+    )
+}
+
 pub struct Module;
 
 #[async_trait]
@@ -71,12 +231,19 @@ impl super::Module for Module {
         "clip"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
         module::HookContext {
             handlers,
+            futures,
+            injector,
             settings,
+            sender,
             stream_info,
             twitch,
             ..
@@ -93,9 +260,112 @@ impl super::Module for Module {
                     .var("cooldown", Cooldown::from_duration(Duration::seconds(30)))
                     .await?,
                 twitch: twitch.clone(),
+                clips: injector.var().await?,
             },
         );
 
+        let digest = Digest {
+            channel: sender.channel().to_string(),
+            enabled: settings.var("digest/enabled", false).await?,
+            webhook_url: settings.optional("digest/webhook-url").await?,
+            clips: injector.var().await?,
+            client: Client::new(),
+        };
+
+        let stream_info = stream_info.clone();
+
+        let future = async move {
+            let started = || {
+                stream_info
+                    .data
+                    .read()
+                    .stream
+                    .as_ref()
+                    .map(|s| s.started_at)
+            };
+
+            let mut interval = tokio::time::interval(time::Duration::from_secs(30));
+            // NB: seeded so that a stream which was already live when we
+            // started still gets a digest when it ends.
+            let mut started_at = started();
+
+            loop {
+                interval.tick().await;
+
+                let current = started();
+
+                // NB: send the digest once the stream goes offline.
+                if let (Some(started_at), None) = (started_at, current) {
+                    if let Err(e) = digest.send(started_at).await {
+                        log_error!(e, "failed to send clip digest");
+                    }
+                }
+
+                started_at = current;
+            }
+        };
+
+        futures.push(future.boxed());
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, digest, truncated_header, WEBHOOK_MAX_LENGTH};
+    use crate::db;
+
+    fn clip(id: i32, title: Option<&str>) -> db::Clip {
+        db::Clip {
+            id,
+            channel: String::from("#setbac"),
+            clip_id: format!("Clip{}", id),
+            user: String::from("udoprog"),
+            title: title.map(String::from),
+            game: Some(String::from("Minecraft")),
+            created_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let clips = vec![clip(1, Some("Nice jump")), clip(2, None)];
+        let content = digest(&clips);
+
+        assert_eq!(
+            content,
+            format!(
+                "2 clip(s) from the stream:\n- {}\n- {}",
+                describe(&clips[0]),
+                describe(&clips[1])
+            )
+        );
+        assert!(content.contains("Nice jump (Minecraft) by udoprog"));
+        assert!(content.contains("Untitled (Minecraft) by udoprog"));
+    }
+
+    #[test]
+    fn test_digest_truncation() {
+        let title = "a".repeat(100);
+        let clips = (0..100)
+            .map(|id| clip(id, Some(&title)))
+            .collect::<Vec<_>>();
+        let content = digest(&clips);
+
+        assert!(content.len() <= WEBHOOK_MAX_LENGTH);
+
+        // only whole lines are included, and as many as will fit.
+        let lines = content.lines().skip(1).count();
+        assert!(lines > 0 && lines < clips.len());
+        assert!(content.starts_with(&format!(
+            "Showing {} of 100 clip(s) from the stream:",
+            lines
+        )));
+        assert!(content.ends_with(&describe(&clips[lines - 1])));
+
+        let body = content.len() - truncated_header(lines, clips.len()).len();
+        let next = format!("\n- {}", describe(&clips[lines]));
+        let longest = truncated_header(clips.len(), clips.len()).len();
+        assert!(longest + body + next.len() > WEBHOOK_MAX_LENGTH);
+    }
+}
@@ -1,25 +1,43 @@
 use crate::auth;
+use crate::bus;
 use crate::command;
+use crate::irc;
 use crate::module;
 use crate::prelude::*;
 use crate::template;
 use crate::utils;
 use parking_lot::RwLock;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time;
 
+/// Name of the countdown set without a name, which is the one written to
+/// `countdown/path`.
+const DEFAULT_COUNTDOWN: &str = "countdown";
+
+/// Name of the stopwatch used when none is specified.
+const DEFAULT_STOPWATCH: &str = "stopwatch";
+
 enum Event {
-    /// Set the countdown.
-    Set(utils::Duration, template::Template),
-    /// Clear the countdown.
-    Clear,
+    /// Set the countdown with the given name.
+    Set(String, utils::Duration, Option<template::Template>),
+    /// Clear the countdown with the given name.
+    Clear(String),
+    /// Start or resume the stopwatch with the given name.
+    Start(String),
+    /// Pause the stopwatch with the given name.
+    Stop(String),
+    /// Remove the stopwatch with the given name.
+    Reset(String),
 }
 
+/// Handler for the `!countdown` command.
 pub struct Handler {
     sender: mpsc::UnboundedSender<Event>,
     enabled: Arc<RwLock<bool>>,
+    timers: Arc<RwLock<Vec<bus::Countdown>>>,
 }
 
 #[async_trait]
@@ -35,10 +53,28 @@ impl command::Handler for Handler {
 
         match ctx.next().as_deref() {
             Some("set") => {
-                let duration = ctx.next_parse("<duration> <template>")?;
-                let template = ctx.rest_parse("<duration> <template>")?;
+                let first = ctx.next_str("[name] <duration> [template]")?;
+
+                // NB: `!countdown set <duration> <template>` sets the default
+                // countdown, which requires a template.
+                let event = match str::parse::<utils::Duration>(&first) {
+                    Ok(duration) => {
+                        let template = ctx.rest_parse("<duration> <template>")?;
+                        Event::Set(DEFAULT_COUNTDOWN.to_string(), duration, Some(template))
+                    }
+                    Err(_) => {
+                        let duration = ctx.next_parse("<name> <duration> [template]")?;
+
+                        let template = match ctx.rest().trim() {
+                            "" => None,
+                            _ => Some(ctx.rest_parse("<name> <duration> [template]")?),
+                        };
 
-                match self.sender.unbounded_send(Event::Set(duration, template)) {
+                        Event::Set(first, duration, template)
+                    }
+                };
+
+                match self.sender.unbounded_send(event) {
                     Ok(()) => {
                         respond!(ctx, "Countdown set!");
                     }
@@ -48,19 +84,34 @@ impl command::Handler for Handler {
                     }
                 }
             }
-            Some("clear") => match self.sender.unbounded_send(Event::Clear) {
-                Ok(()) => {
-                    respond!(ctx, "Countdown cleared!");
-                }
-                Err(_) => {
-                    respond!(ctx, "Could not clear countdown :(");
-                    return Ok(());
+            Some("clear") => {
+                let name = ctx.next().unwrap_or_else(|| DEFAULT_COUNTDOWN.to_string());
+
+                match self.sender.unbounded_send(Event::Clear(name)) {
+                    Ok(()) => {
+                        respond!(ctx, "Countdown cleared!");
+                    }
+                    Err(_) => {
+                        respond!(ctx, "Could not clear countdown :(");
+                        return Ok(());
+                    }
                 }
-            },
+            }
+            Some("list") => {
+                let timers = self
+                    .timers
+                    .read()
+                    .iter()
+                    .filter(|t| t.kind == bus::CountdownKind::Countdown)
+                    .map(|t| format!("{}: {}", t.name, t.text))
+                    .collect::<Vec<_>>();
+
+                ctx.respond_lines(timers, "No countdowns running").await;
+            }
             _ => {
                 respond!(
                     ctx,
-                    "Expected: !countdown set <duration> <template>, or !countdown clear"
+                    "Expected: !countdown set [name] <duration> [template], !countdown clear [name], or !countdown list"
                 );
                 return Ok(());
             }
@@ -70,6 +121,75 @@ impl command::Handler for Handler {
     }
 }
 
+/// Handler for the `!stopwatch` command.
+pub struct Stopwatch {
+    sender: mpsc::UnboundedSender<Event>,
+    enabled: Arc<RwLock<bool>>,
+    timers: Arc<RwLock<Vec<bus::Countdown>>>,
+}
+
+#[async_trait]
+impl command::Handler for Stopwatch {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Countdown)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context) -> Result<(), anyhow::Error> {
+        if !*self.enabled.read() {
+            return Ok(());
+        }
+
+        let (event, what): (fn(String) -> Event, _) = match ctx.next().as_deref() {
+            Some("start") => (Event::Start, "started"),
+            Some("stop") => (Event::Stop, "stopped"),
+            Some("reset") => (Event::Reset, "reset"),
+            None => {
+                let timers = self
+                    .timers
+                    .read()
+                    .iter()
+                    .filter(|t| t.kind == bus::CountdownKind::Stopwatch)
+                    .map(|t| format!("{}: {}", t.name, t.text))
+                    .collect::<Vec<_>>();
+
+                ctx.respond_lines(timers, "No stopwatches running").await;
+                return Ok(());
+            }
+            Some(_) => {
+                respond!(
+                    ctx,
+                    "Expected: !stopwatch start [name], !stopwatch stop [name], or !stopwatch reset [name]"
+                );
+                return Ok(());
+            }
+        };
+
+        let name = ctx.next().unwrap_or_else(|| DEFAULT_STOPWATCH.to_string());
+
+        let is_countdown = self
+            .timers
+            .read()
+            .iter()
+            .any(|t| t.name == name && t.kind == bus::CountdownKind::Countdown);
+
+        if is_countdown {
+            respond!(ctx, "`{}` is a countdown, not a stopwatch", name);
+            return Ok(());
+        }
+
+        match self.sender.unbounded_send(event(name.clone())) {
+            Ok(()) => {
+                respond!(ctx, "Stopwatch `{}` {}!", name, what);
+            }
+            Err(_) => {
+                respond!(ctx, "Could not update stopwatch :(");
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub struct Module;
 
 #[async_trait]
@@ -78,6 +198,10 @@ impl super::Module for Module {
         "countdown"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
@@ -85,6 +209,8 @@ impl super::Module for Module {
             handlers,
             futures,
             settings,
+            sender,
+            global_bus,
             ..
         }: module::HookContext<'_>,
     ) -> Result<(), anyhow::Error> {
@@ -98,18 +224,49 @@ impl super::Module for Module {
         let mut writer = FileWriter::default();
         writer.path = path;
 
+        let default_announce_template =
+            template::Template::compile("{{name}}: {{remaining}} left!")?;
+
+        let announcer = Announcer {
+            sender: sender.clone(),
+            announce_at: settings.var("announce-at", Vec::new()).await?,
+            announce_template: settings
+                .var("announce-template", default_announce_template)
+                .await?,
+            finished_template: settings.optional("finished-template").await?,
+        };
+
         let (sender, mut receiver) = mpsc::unbounded();
+        let snapshot = Arc::new(RwLock::new(Vec::new()));
 
         handlers.insert(
             "countdown",
             Handler {
+                sender: sender.clone(),
+                enabled: enabled.clone(),
+                timers: snapshot.clone(),
+            },
+        );
+
+        handlers.insert(
+            "stopwatch",
+            Stopwatch {
                 sender,
                 enabled: enabled.clone(),
+                timers: snapshot.clone(),
             },
         );
 
+        let mut timers = Timers {
+            timers: BTreeMap::new(),
+            snapshot,
+            global_bus: global_bus.clone(),
+        };
+
         let future = async move {
-            let mut timer = Option::<Timer>::None;
+            let second = time::Duration::from_secs(1);
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + second, second).fuse();
 
             loop {
                 futures::select! {
@@ -117,41 +274,65 @@ impl super::Module for Module {
                         writer.path = update;
                     }
                     update = enabled_stream.select_next_some() => {
-                        if (!update) {
-                            timer.take();
+                        if !update {
+                            timers.timers.clear();
+                            timers.publish().await;
                             writer.clear_log();
                         }
 
                         *enabled.write() = update;
                     }
-                    out = timer.next() => {
-                        match out {
-                            Some(()) => if let Some(timer) = timer.as_ref() {
-                                writer.write_log(timer);
-                            },
-                            None => {
-                                writer.clear_log();
-                            },
+                    _ = interval.select_next_some() => {
+                        if timers.timers.is_empty() {
+                            continue;
                         }
+
+                        let finished = timers.tick(&announcer).await;
+
+                        if finished.iter().any(|name| name == DEFAULT_COUNTDOWN) {
+                            writer.clear_log();
+                        } else if let Some(timer) = timers.timers.get(DEFAULT_COUNTDOWN) {
+                            writer.write_log(timer);
+                        }
+
+                        timers.publish().await;
                     },
                     event = receiver.select_next_some() => {
                         match event {
-                            Event::Set(duration, template) => {
-                                let mut t = Timer {
+                            Event::Set(name, duration, template) => {
+                                let timer = Timer {
+                                    kind: bus::CountdownKind::Countdown,
                                     duration,
                                     elapsed: Default::default(),
-                                    interval: tokio::time::interval(time::Duration::from_secs(1)),
+                                    running: true,
+                                    template,
                                 };
 
-                                writer.template = Some(template);
-                                writer.write_log(&t);
-                                timer = Some(t);
+                                if name == DEFAULT_COUNTDOWN {
+                                    writer.write_log(&timer);
+                                }
+
+                                timers.timers.insert(name, timer);
                             }
-                            Event::Clear => {
-                                timer.take();
-                                writer.clear_log();
+                            Event::Clear(name) => {
+                                if name == DEFAULT_COUNTDOWN {
+                                    writer.clear_log();
+                                }
+
+                                timers.timers.remove(&name);
+                            }
+                            Event::Start(name) => {
+                                timers.start(name);
+                            }
+                            Event::Stop(name) => {
+                                timers.stop(&name);
+                            }
+                            Event::Reset(name) => {
+                                timers.reset(&name);
                             }
                         }
+
+                        timers.publish().await;
                     }
                 }
             }
@@ -162,10 +343,165 @@ impl super::Module for Module {
     }
 }
 
+/// Announces countdowns in chat as they pass thresholds.
+struct Announcer {
+    sender: irc::Sender,
+    announce_at: settings::Var<Vec<utils::Duration>>,
+    announce_template: settings::Var<template::Template>,
+    finished_template: settings::Var<Option<template::Template>>,
+}
+
+impl Announcer {
+    /// Announce that the countdown passed a threshold.
+    async fn announce(&self, name: &str, timer: &Timer) {
+        let template = self.announce_template.load().await;
+        self.send(&template, name, timer).await;
+    }
+
+    /// Announce that the countdown finished, if configured.
+    async fn finished(&self, name: &str, timer: &Timer) {
+        if let Some(template) = self.finished_template.load().await {
+            self.send(&template, name, timer).await;
+        }
+    }
+
+    async fn send(&self, template: &template::Template, name: &str, timer: &Timer) {
+        match template.render_to_string(timer.data(name)) {
+            Ok(message) => self.sender.privmsg(message).await,
+            Err(e) => log_error!(e, "failed to render countdown announcement"),
+        }
+    }
+}
+
+/// All running countdowns and stopwatches.
+struct Timers {
+    timers: BTreeMap<String, Timer>,
+    snapshot: Arc<RwLock<Vec<bus::Countdown>>>,
+    global_bus: Arc<bus::Bus<bus::Global>>,
+}
+
+impl Timers {
+    /// Start or resume the stopwatch with the given name.
+    ///
+    /// NB: names which are in use by a countdown are left alone.
+    fn start(&mut self, name: String) {
+        let timer = self.timers.entry(name).or_insert_with(|| Timer {
+            kind: bus::CountdownKind::Stopwatch,
+            duration: Default::default(),
+            elapsed: Default::default(),
+            running: true,
+            template: None,
+        });
+
+        if timer.kind == bus::CountdownKind::Stopwatch {
+            timer.running = true;
+        }
+    }
+
+    /// Pause the stopwatch with the given name.
+    fn stop(&mut self, name: &str) {
+        if let Some(timer) = self.timers.get_mut(name) {
+            if timer.kind == bus::CountdownKind::Stopwatch {
+                timer.running = false;
+            }
+        }
+    }
+
+    /// Remove the stopwatch with the given name.
+    fn reset(&mut self, name: &str) {
+        if let Some(timer) = self.timers.get(name) {
+            if timer.kind == bus::CountdownKind::Stopwatch {
+                self.timers.remove(name);
+            }
+        }
+    }
+
+    /// Advance all running timers by one second, announcing thresholds as
+    /// they are passed.
+    ///
+    /// Returns the names of countdowns which finished.
+    async fn tick(&mut self, announcer: &Announcer) -> Vec<String> {
+        let announce_at = announcer.announce_at.load().await;
+        let (passed, finished) = self.advance(&announce_at);
+
+        for name in passed {
+            if let Some(timer) = self.timers.get(&name) {
+                announcer.announce(&name, timer).await;
+            }
+        }
+
+        let mut names = Vec::with_capacity(finished.len());
+
+        for (name, timer) in finished {
+            announcer.finished(&name, &timer).await;
+            names.push(name);
+        }
+
+        names
+    }
+
+    /// Advance all running timers by one second.
+    ///
+    /// Returns the names of countdowns which passed one of the `announce_at`
+    /// thresholds, and the countdowns which finished and were removed.
+    fn advance(&mut self, announce_at: &[utils::Duration]) -> (Vec<String>, Vec<(String, Timer)>) {
+        let mut passed = Vec::new();
+        let mut finished = Vec::new();
+
+        for (name, timer) in &mut self.timers {
+            if !timer.running {
+                continue;
+            }
+
+            let before = timer.remaining();
+            timer.elapsed += utils::Duration::seconds(1);
+
+            if timer.kind != bus::CountdownKind::Countdown {
+                continue;
+            }
+
+            if timer.elapsed >= timer.duration {
+                finished.push(name.clone());
+                continue;
+            }
+
+            let after = timer.remaining();
+
+            if announce_at.iter().any(|t| before > *t && after <= *t) {
+                passed.push(name.clone());
+            }
+        }
+
+        let finished = finished
+            .into_iter()
+            .filter_map(|name| {
+                let timer = self.timers.remove(&name)?;
+                Some((name, timer))
+            })
+            .collect();
+
+        (passed, finished)
+    }
+
+    /// Publish the state of all timers to the overlay.
+    async fn publish(&self) {
+        let timers = self
+            .timers
+            .iter()
+            .map(|(name, timer)| timer.to_countdown(name))
+            .collect::<Vec<_>>();
+
+        *self.snapshot.write() = timers.clone();
+
+        self.global_bus
+            .send(bus::Global::Countdowns { timers })
+            .await;
+    }
+}
+
 #[derive(Default)]
 struct FileWriter {
     path: Option<PathBuf>,
-    template: Option<template::Template>,
 }
 
 impl FileWriter {
@@ -175,7 +511,7 @@ impl FileWriter {
             None => return Ok(()),
         };
 
-        let template = match &self.template {
+        let template = match &timer.template {
             Some(template) => template,
             None => return Ok(()),
         };
@@ -183,28 +519,8 @@ impl FileWriter {
         log::trace!("Writing to log: {}", path.display());
 
         let mut f = fs::File::create(path)?;
-        let remaining = timer.duration.saturating_sub(timer.elapsed.clone());
-        let remaining = remaining.as_digital();
-        let elapsed = timer.elapsed.as_digital();
-        let duration = timer.duration.as_digital();
-
-        template.render(
-            &mut f,
-            Data {
-                remaining,
-                elapsed,
-                duration,
-            },
-        )?;
-
-        return Ok(());
-
-        #[derive(serde::Serialize)]
-        struct Data {
-            remaining: String,
-            elapsed: String,
-            duration: String,
-        }
+        template.render(&mut f, timer.data(DEFAULT_COUNTDOWN))?;
+        Ok(())
     }
 
     fn clear(&self) -> Result<(), anyhow::Error> {
@@ -238,32 +554,197 @@ impl FileWriter {
     }
 }
 
+/// Variables available to countdown templates.
+#[derive(serde::Serialize)]
+struct Data<'a> {
+    name: &'a str,
+    remaining: String,
+    elapsed: String,
+    duration: String,
+}
+
+/// A single countdown or stopwatch.
 struct Timer {
+    kind: bus::CountdownKind,
+    /// Duration of a countdown, unused for stopwatches.
     duration: utils::Duration,
     elapsed: utils::Duration,
-    interval: tokio::time::Interval,
+    running: bool,
+    template: Option<template::Template>,
 }
 
-impl Stream for Timer {
-    type Item = ();
+impl Timer {
+    /// Time remaining of a countdown.
+    fn remaining(&self) -> utils::Duration {
+        self.duration.saturating_sub(self.elapsed)
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        if let Poll::Ready(Some(_)) = Pin::new(&mut self.interval).poll_next(cx) {
-            self.as_mut().elapsed += utils::Duration::seconds(1);
+    /// Template variables for the timer.
+    fn data<'a>(&self, name: &'a str) -> Data<'a> {
+        Data {
+            name,
+            remaining: self.remaining().as_digital(),
+            elapsed: self.elapsed.as_digital(),
+            duration: self.duration.as_digital(),
+        }
+    }
 
-            if self.as_ref().elapsed >= self.as_ref().duration {
-                return Poll::Ready(None);
-            }
+    /// Convert into a countdown for the overlay.
+    fn to_countdown(&self, name: &str) -> bus::Countdown {
+        let text = match &self.template {
+            Some(template) => match template.render_to_string(self.data(name)) {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    log_error!(e, "failed to render countdown");
+                    None
+                }
+            },
+            None => None,
+        };
 
-            return Poll::Ready(Some(()));
-        }
+        let (text, duration, remaining) = match self.kind {
+            bus::CountdownKind::Countdown => (
+                text.unwrap_or_else(|| self.remaining().as_digital()),
+                Some(self.duration.num_seconds()),
+                Some(self.remaining().num_seconds()),
+            ),
+            bus::CountdownKind::Stopwatch => (
+                text.unwrap_or_else(|| self.elapsed.as_digital()),
+                None,
+                None,
+            ),
+        };
 
-        Poll::Pending
+        bus::Countdown {
+            name: name.to_string(),
+            kind: self.kind,
+            text,
+            elapsed: self.elapsed.num_seconds(),
+            duration,
+            remaining,
+            running: self.running,
+        }
     }
 }
 
-impl stream::FusedStream for Timer {
-    fn is_terminated(&self) -> bool {
-        self.elapsed >= self.duration
+#[cfg(test)]
+mod tests {
+    use super::{Timer, Timers};
+    use crate::bus;
+    use crate::utils::Duration;
+    use parking_lot::RwLock;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn timers() -> Timers {
+        Timers {
+            timers: BTreeMap::new(),
+            snapshot: Arc::new(RwLock::new(Vec::new())),
+            global_bus: Arc::new(bus::Bus::new()),
+        }
+    }
+
+    fn timer(kind: bus::CountdownKind, duration: Duration) -> Timer {
+        Timer {
+            kind,
+            duration,
+            elapsed: Default::default(),
+            running: true,
+            template: None,
+        }
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let mut timers = timers();
+        let countdown = timer(bus::CountdownKind::Countdown, Duration::seconds(12));
+        timers.timers.insert(String::from("countdown"), countdown);
+
+        let announce_at = vec![Duration::seconds(10), Duration::seconds(5)];
+
+        let (passed, finished) = timers.advance(&announce_at);
+        assert!(passed.is_empty());
+        assert!(finished.is_empty());
+
+        // 12 -> 10 seconds remaining passes the first threshold exactly once.
+        let (passed, _) = timers.advance(&announce_at);
+        assert_eq!(passed, vec![String::from("countdown")]);
+        let (passed, _) = timers.advance(&announce_at);
+        assert!(passed.is_empty());
+
+        // paused timers don't advance.
+        timers.timers.get_mut("countdown").unwrap().running = false;
+
+        for _ in 0..10 {
+            let (passed, finished) = timers.advance(&announce_at);
+            assert!(passed.is_empty());
+            assert!(finished.is_empty());
+        }
+
+        timers.timers.get_mut("countdown").unwrap().running = true;
+
+        for _ in 0..3 {
+            let (passed, _) = timers.advance(&announce_at);
+            assert!(passed.is_empty());
+        }
+
+        let (passed, _) = timers.advance(&announce_at);
+        assert_eq!(passed, vec![String::from("countdown")]);
+        assert_eq!(timers.timers["countdown"].remaining(), Duration::seconds(5));
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut timers = timers();
+        let countdown = timer(bus::CountdownKind::Countdown, Duration::seconds(2));
+        let stopwatch = timer(bus::CountdownKind::Stopwatch, Duration::default());
+        timers.timers.insert(String::from("countdown"), countdown);
+        timers.timers.insert(String::from("stopwatch"), stopwatch);
+
+        let (_, finished) = timers.advance(&[]);
+        assert!(finished.is_empty());
+
+        let (_, finished) = timers.advance(&[]);
+        let finished = finished
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(finished, vec![String::from("countdown")]);
+
+        // finished countdowns are removed, stopwatches keep running.
+        assert!(!timers.timers.contains_key("countdown"));
+
+        for _ in 0..5 {
+            let (_, finished) = timers.advance(&[]);
+            assert!(finished.is_empty());
+        }
+
+        assert_eq!(timers.timers["stopwatch"].elapsed, Duration::seconds(7));
+    }
+
+    #[test]
+    fn test_stopwatch_events_ignore_countdowns() {
+        let mut timers = timers();
+        let countdown = timer(bus::CountdownKind::Countdown, Duration::seconds(10));
+        timers.timers.insert(String::from("countdown"), countdown);
+
+        timers.stop("countdown");
+        assert!(timers.timers["countdown"].running);
+
+        timers.reset("countdown");
+        assert!(timers.timers.contains_key("countdown"));
+
+        timers.start(String::from("countdown"));
+        assert_eq!(
+            timers.timers["countdown"].kind,
+            bus::CountdownKind::Countdown
+        );
+
+        timers.start(String::from("stopwatch"));
+        timers.stop("stopwatch");
+        assert!(!timers.timers["stopwatch"].running);
+
+        timers.reset("stopwatch");
+        assert!(!timers.timers.contains_key("stopwatch"));
     }
 }
@@ -0,0 +1,693 @@
+//! A generic bridge which lets viewers trigger effects in a modded game.
+//!
+//! Unlike `gtav`, the available effects aren't hard-coded. They are declared
+//! in the `game-bridge/effects` setting, like:
+//!
+//! ```yaml
+//! - name: spawn-enemy
+//!   what: punished
+//!   cost: 20
+//!   cooldown: 1m
+//!   arguments:
+//!     - {name: count, type: integer, min: 1, max: 5}
+//! ```
+//!
+//! Effects are delivered to the game in one of the wire formats in
+//! [transport::Format].
+
+use crate::auth::Scope;
+use crate::command;
+use crate::currency;
+use crate::irc;
+use crate::module;
+use crate::prelude::*;
+use crate::utils::{compact_duration, update_cooldown, Cooldown, Cooldowns, Duration};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time;
+use tokio::sync::Mutex;
+
+mod transport;
+
+use self::transport::{Format, Message, Transport};
+use super::gtav::CommandsConfig;
+
+/// A single effect, as declared in settings.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Effect {
+    /// The name of the effect, used both in chat and on the wire.
+    name: String,
+    /// How the effect affects the streamer, like `rewarded` or `punished`.
+    #[serde(default)]
+    what: Option<String>,
+    /// How much the effect costs.
+    #[serde(default)]
+    cost: u32,
+    /// Cooldown specific to this effect.
+    #[serde(default)]
+    cooldown: Option<Duration>,
+    /// Arguments the effect takes.
+    #[serde(default, deserialize_with = "deserialize_arguments")]
+    arguments: Vec<Argument>,
+}
+
+/// Deserialize arguments, treating `null` as no arguments since that's what
+/// an empty optional field in the settings UI looks like.
+fn deserialize_arguments<'de, D>(deserializer: D) -> Result<Vec<Argument>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize as _;
+    Ok(Option::<Vec<Argument>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A single argument to an effect.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Argument {
+    name: String,
+    #[serde(flatten)]
+    ty: ArgumentType,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum ArgumentType {
+    /// An integer, optionally bounded.
+    Integer {
+        #[serde(default)]
+        min: Option<i64>,
+        #[serde(default)]
+        max: Option<i64>,
+    },
+    /// A floating point number, optionally bounded.
+    Number {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// A single word.
+    String {
+        #[serde(default, rename = "max-length")]
+        max_length: Option<usize>,
+    },
+    /// One out of a fixed set of words.
+    Choice { choices: Vec<String> },
+}
+
+impl Argument {
+    /// Parse the given input according to this argument.
+    ///
+    /// Returns a human readable error on failure.
+    fn parse(&self, input: &str) -> Result<serde_json::Value, String> {
+        match &self.ty {
+            ArgumentType::Integer { min, max } => {
+                let n = str::parse::<i64>(input)
+                    .map_err(|_| format!("`{}` must be a whole number", self.name))?;
+
+                if min.map(|min| n < min).unwrap_or_default()
+                    || max.map(|max| n > max).unwrap_or_default()
+                {
+                    return Err(self.range_error(min, max));
+                }
+
+                Ok(n.into())
+            }
+            ArgumentType::Number { min, max } => {
+                let n = str::parse::<f64>(input)
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .ok_or_else(|| format!("`{}` must be a number", self.name))?;
+
+                if min.map(|min| n < min).unwrap_or_default()
+                    || max.map(|max| n > max).unwrap_or_default()
+                {
+                    return Err(self.range_error(min, max));
+                }
+
+                Ok(n.into())
+            }
+            ArgumentType::String { max_length } => {
+                if max_length
+                    .map(|m| input.chars().count() > m)
+                    .unwrap_or_default()
+                {
+                    return Err(format!(
+                        "`{}` can be at most {} characters",
+                        self.name,
+                        max_length.unwrap_or_default()
+                    ));
+                }
+
+                if !input.is_ascii() {
+                    return Err(format!("`{}` can only contain ASCII characters", self.name));
+                }
+
+                Ok(input.into())
+            }
+            ArgumentType::Choice { choices } => {
+                let input = input.to_lowercase();
+
+                match choices.iter().find(|c| c.to_lowercase() == input) {
+                    Some(choice) => Ok(choice.as_str().into()),
+                    None => Err(format!(
+                        "`{}` must be one of: {}",
+                        self.name,
+                        choices.join(", ")
+                    )),
+                }
+            }
+        }
+    }
+
+    fn range_error<T>(&self, min: &Option<T>, max: &Option<T>) -> String
+    where
+        T: std::fmt::Display,
+    {
+        match (min, max) {
+            (Some(min), Some(max)) => {
+                format!("`{}` must be between {} and {}", self.name, min, max)
+            }
+            (Some(min), None) => format!("`{}` must be at least {}", self.name, min),
+            (None, Some(max)) => format!("`{}` must be at most {}", self.name, max),
+            (None, None) => format!("`{}` is out of range", self.name),
+        }
+    }
+}
+
+impl Effect {
+    /// Usage string for the effect, like `spawn-enemy <count>`.
+    fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+
+        for argument in &self.arguments {
+            usage.push_str(&format!(" <{}>", argument.name));
+        }
+
+        usage
+    }
+
+    /// Parse the arguments to the effect from the given command context.
+    fn parse_arguments(
+        &self,
+        ctx: &mut command::Context,
+    ) -> Result<Vec<serde_json::Value>, String> {
+        let mut out = Vec::with_capacity(self.arguments.len());
+
+        for argument in &self.arguments {
+            let input = match ctx.next() {
+                Some(input) => input,
+                None => return Err(format!("missing `{}`", argument.name)),
+            };
+
+            out.push(argument.parse(&input)?);
+        }
+
+        Ok(out)
+    }
+}
+
+/// An effect which has been paid for and should be sent to the game.
+struct Request {
+    user: irc::User,
+    /// What the user was charged, which is refunded if the effect can't be
+    /// sent.
+    cost: u64,
+    message: Message,
+}
+
+pub struct Handler {
+    enabled: settings::Var<bool>,
+    currency: injector::Var<Option<currency::Currency>>,
+    effects: settings::Var<Vec<Effect>>,
+    command_configs: settings::Var<CommandsConfig>,
+    cooldown: settings::Var<Cooldown>,
+    per_user_cooldown: settings::Var<Cooldown>,
+    per_effect_cooldown: settings::Var<Cooldown>,
+    prefix: settings::Var<String>,
+    cost_percentage: settings::Var<u32>,
+    success_feedback: settings::Var<bool>,
+    id_counter: AtomicUsize,
+    tx: mpsc::UnboundedSender<Request>,
+    per_user_cooldowns: Mutex<HashMap<String, Cooldown>>,
+    per_effect_cooldowns: Mutex<HashMap<String, Cooldown>>,
+}
+
+impl Handler {
+    /// Check if the given user is subject to cooldown right now.
+    ///
+    /// An effect which has its own cooldown is only subject to that cooldown
+    /// and the per-user cooldown, like overrides in `gtav`.
+    async fn check_cooldown(
+        &self,
+        ctx: &command::Context,
+        effect: &str,
+        specific: Option<Duration>,
+    ) -> Option<(&'static str, time::Duration)> {
+        let mut per_user_cooldowns = self.per_user_cooldowns.lock().await;
+        let mut per_effect_cooldowns = self.per_effect_cooldowns.lock().await;
+
+        let per_user_cooldown = self.per_user_cooldown.load().await;
+
+        // NB: only real users are subject to cooldown.
+        let user_cooldown = ctx.user.real().map(|user| {
+            update_cooldown(
+                per_user_cooldowns.entry(user.name().to_string()),
+                per_user_cooldown.cooldown,
+            )
+        });
+
+        let effect_duration = match specific {
+            Some(specific) => specific,
+            None => self.per_effect_cooldown.load().await.cooldown,
+        };
+
+        let effect_cooldown = update_cooldown(
+            per_effect_cooldowns.entry(effect.to_string()),
+            effect_duration,
+        );
+
+        let mut cooldown = self.cooldown.write().await;
+        let mut cooldowns = Cooldowns::default();
+
+        if let Some(user_cooldown) = user_cooldown {
+            cooldowns.check("User", user_cooldown);
+        }
+
+        if specific.is_some() {
+            cooldowns.check("Effect specific", effect_cooldown);
+            cooldowns.poke(&mut cooldown);
+        } else {
+            cooldowns.check("Global", &mut cooldown);
+            cooldowns.check("Effect", effect_cooldown);
+        }
+
+        cooldowns.finish(time::Instant::now())
+    }
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<Scope> {
+        Some(Scope::GameBridge)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let currency = self
+            .currency
+            .load()
+            .await
+            .ok_or_else(|| respond_err!("No currency configured for stream, sorry :("))?;
+
+        let effects = self.effects.load().await;
+        let command_configs = self.command_configs.load().await;
+
+        // NB: effects which are disabled through overrides are hidden.
+        let enabled = |effect: &Effect| {
+            command_configs
+                .0
+                .iter()
+                .find(|c| c.name == effect.name)
+                .and_then(|c| c.enabled)
+                .unwrap_or(true)
+        };
+
+        let name = match ctx.next() {
+            Some(name) => name.to_lowercase(),
+            None => {
+                let available = effects
+                    .iter()
+                    .filter(|e| enabled(e))
+                    .map(|e| e.usage())
+                    .collect::<Vec<_>>();
+
+                if available.is_empty() {
+                    respond!(ctx, "No effects are available right now.");
+                } else {
+                    respond!(ctx, "Available effects are: {}.", available.join(", "));
+                }
+
+                return Ok(());
+            }
+        };
+
+        let effect = match effects.iter().find(|e| e.name.to_lowercase() == name) {
+            Some(effect) if enabled(effect) => effect,
+            _ => {
+                respond!(ctx, "No effect named `{}`.", name);
+                return Ok(());
+            }
+        };
+
+        let arguments = match effect.parse_arguments(ctx) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                respond!(ctx, "Bad effect, {}. Usage: {}", e, effect.usage());
+                return Ok(());
+            }
+        };
+
+        let config = command_configs.0.iter().find(|c| c.name == effect.name);
+
+        let mut cost = effect.cost;
+        let mut specific = effect.cooldown;
+
+        if let Some(config) = config {
+            if let Some(c) = config.cost {
+                cost = c;
+            }
+
+            if let Some(c) = config.cooldown {
+                specific = Some(c);
+            }
+        }
+
+        if !ctx.user.has_scope(Scope::GameBridgeBypassCooldown).await {
+            if let Some((what, remaining)) = self.check_cooldown(ctx, &effect.name, specific).await
+            {
+                respond!(
+                    ctx,
+                    "{} cooldown in effect, please wait at least {}!",
+                    what,
+                    compact_duration(remaining),
+                );
+
+                return Ok(());
+            }
+        }
+
+        // NB: computed in 64 bits so that large costs or percentages can't
+        // overflow.
+        let cost = u64::from(cost) * u64::from(self.cost_percentage.load().await) / 100;
+        let prefix = self.prefix.load().await;
+        let mut charged = 0;
+
+        if let Some(real) = ctx.user.real() {
+            let balance = currency
+                .balance_of(ctx.user.channel(), real.name())
+                .await?
+                .unwrap_or_default();
+
+            let balance = if balance.balance < 0 {
+                0u64
+            } else {
+                balance.balance as u64
+            };
+
+            if balance < cost {
+                respond!(
+                    ctx,
+                    "{prefix}\
+                        You need at least {limit} {currency} for {effect}, \
+                        you currently have {balance} {currency}. \
+                        Keep watching to earn more!",
+                    prefix = prefix,
+                    limit = cost,
+                    currency = currency.name,
+                    effect = effect.name,
+                    balance = balance,
+                );
+
+                return Ok(());
+            }
+
+            currency
+                .balance_add(ctx.user.channel(), real.name(), -(cost as i64))
+                .await?;
+
+            charged = cost;
+        }
+
+        if self.success_feedback.load().await {
+            let who = ctx.user.display_name().unwrap_or("Someone");
+
+            ctx.privmsg(format!(
+                "{prefix}{user} {what} the streamer for {cost} {currency} with {effect}",
+                prefix = prefix,
+                user = who,
+                what = effect.what.as_deref().unwrap_or("spiced up"),
+                cost = cost,
+                currency = currency.name,
+                effect = effect.name,
+            ))
+            .await;
+        }
+
+        let message = Message {
+            id: self.id_counter.fetch_add(1, Ordering::SeqCst),
+            user: ctx.user.name().unwrap_or("unknown").to_string(),
+            effect: effect.name.clone(),
+            arguments,
+        };
+
+        let request = Request {
+            user: ctx.user.clone(),
+            cost: charged,
+            message,
+        };
+
+        if self.tx.unbounded_send(request).is_err() {
+            bail!("failed to send effect");
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Module;
+
+#[async_trait]
+impl super::Module for Module {
+    fn ty(&self) -> &'static str {
+        "game-bridge"
+    }
+
+    fn primary_only(&self) -> bool {
+        true
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            handlers,
+            settings,
+            futures,
+            injector,
+            sender,
+            ..
+        }: module::HookContext<'_>,
+    ) -> Result<()> {
+        let settings = settings.scoped("game-bridge");
+
+        let enabled = settings.var("enabled", false).await?;
+        let (mut format_stream, format) = settings.stream("format").or_default().await?;
+        let (mut address_stream, address) = settings
+            .stream("address")
+            .or_with(String::from("127.0.0.1:7292"))
+            .await?;
+        let (mut echo_stream, echo) = settings.stream("echo-server").or_default().await?;
+
+        let currency = injector.var().await?;
+        let prefix = settings.var("chat-prefix", String::new()).await?;
+        let (tx, mut rx) = mpsc::unbounded();
+
+        handlers.insert(
+            "effect",
+            Handler {
+                enabled,
+                currency: currency.clone(),
+                effects: settings.var("effects", Vec::new()).await?,
+                command_configs: settings
+                    .var("command-configs", CommandsConfig::default())
+                    .await?,
+                cooldown: settings
+                    .var("cooldown", Cooldown::from_duration(Duration::seconds(1)))
+                    .await?,
+                per_user_cooldown: settings
+                    .var(
+                        "per-user-cooldown",
+                        Cooldown::from_duration(Duration::seconds(60)),
+                    )
+                    .await?,
+                per_effect_cooldown: settings
+                    .var(
+                        "per-effect-cooldown",
+                        Cooldown::from_duration(Duration::seconds(5)),
+                    )
+                    .await?,
+                prefix: prefix.clone(),
+                cost_percentage: settings.var("cost%", 100).await?,
+                success_feedback: settings.var("success-feedback", false).await?,
+                id_counter: AtomicUsize::new(0),
+                tx,
+                per_user_cooldowns: Mutex::new(Default::default()),
+                per_effect_cooldowns: Mutex::new(Default::default()),
+            },
+        );
+
+        let sender = sender.clone();
+
+        let future = async move {
+            let mut format: Format = format;
+            let mut address: String = address;
+            let mut echo: bool = echo;
+            let mut transport = Transport::new(format, address.clone());
+
+            let echo_server = |echo, format, address: &String| match echo {
+                true => Some(transport::echo_server(format, address.clone()).boxed()),
+                false => None,
+            };
+
+            let mut echo_server_future = echo_server(echo, format, &address);
+
+            loop {
+                futures::select! {
+                    update = format_stream.select_next_some() => {
+                        format = update;
+                        transport = Transport::new(format, address.clone());
+                        echo_server_future = echo_server(echo, format, &address);
+                    }
+                    update = address_stream.select_next_some() => {
+                        address = update;
+                        transport = Transport::new(format, address.clone());
+                        echo_server_future = echo_server(echo, format, &address);
+                    }
+                    update = echo_stream.select_next_some() => {
+                        echo = update;
+                        echo_server_future = echo_server(echo, format, &address);
+                    }
+                    result = echo_server_future.current() => {
+                        echo_server_future = None;
+
+                        if let Err(e) = result {
+                            log_error!(e, "Game bridge echo server failed");
+                        }
+                    }
+                    request = rx.select_next_some() => {
+                        log::info!("sent: {}", request.message.to_line());
+
+                        if let Err(e) = transport.send(&request.message).await {
+                            log_error!(e, "Failed to send effect to game");
+                            refund(&sender, &currency, &prefix, request).await;
+                        }
+                    }
+                }
+            }
+        };
+
+        futures.push(future.boxed());
+        Ok(())
+    }
+}
+
+/// Refund an effect which couldn't be sent to the game, and let the user know.
+async fn refund(
+    sender: &irc::Sender,
+    currency: &injector::Var<Option<currency::Currency>>,
+    prefix: &settings::Var<String>,
+    request: Request,
+) {
+    let Request {
+        user,
+        cost,
+        message,
+    } = request;
+
+    let who = user.display_name().unwrap_or("Someone");
+    let prefix = prefix.load().await;
+
+    let currency = match currency.load().await {
+        Some(currency) if cost > 0 => currency,
+        _ => {
+            sender
+                .privmsg(format!(
+                    "{}{}'s {} couldn't be sent to the game.",
+                    prefix, who, message.effect
+                ))
+                .await;
+            return;
+        }
+    };
+
+    let result = match user.real() {
+        Some(real) => {
+            currency
+                .balance_add(user.channel(), real.name(), cost as i64)
+                .await
+        }
+        None => Ok(()),
+    };
+
+    if let Err(e) = result {
+        log_error!(e, "Failed to refund {} to {}", cost, who);
+        return;
+    }
+
+    sender
+        .privmsg(format!(
+            "{prefix}{user}'s {effect} couldn't be sent to the game, refunded {cost} {currency}.",
+            prefix = prefix,
+            user = who,
+            effect = message.effect,
+            cost = cost,
+            currency = currency.name,
+        ))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Argument, ArgumentType};
+
+    fn argument(ty: ArgumentType) -> Argument {
+        Argument {
+            name: String::from("count"),
+            ty,
+        }
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let integer = argument(ArgumentType::Integer {
+            min: Some(1),
+            max: Some(5),
+        });
+
+        assert_eq!(integer.parse("3"), Ok(serde_json::json!(3)));
+        assert!(integer.parse("6").is_err());
+        assert!(integer.parse("three").is_err());
+
+        let choice = argument(ArgumentType::Choice {
+            choices: vec![String::from("Left"), String::from("Right")],
+        });
+
+        assert_eq!(choice.parse("left"), Ok(serde_json::json!("Left")));
+        assert!(choice.parse("up").is_err());
+
+        let string = argument(ArgumentType::String {
+            max_length: Some(3),
+        });
+
+        assert_eq!(string.parse("abc"), Ok(serde_json::json!("abc")));
+        assert!(string.parse("abcd").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_effect() {
+        let effect: super::Effect = serde_json::from_value(serde_json::json!({
+            "name": "spawn-enemy",
+            "cost": 20,
+            "arguments": [{"name": "count", "type": "integer", "min": 1}],
+        }))
+        .unwrap();
+
+        assert_eq!(effect.usage(), "spawn-enemy <count>");
+        assert!(effect.arguments[0].parse("0").is_err());
+    }
+}
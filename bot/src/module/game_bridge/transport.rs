@@ -0,0 +1,306 @@
+//! Wire formats used to deliver effects to a game.
+
+use anyhow::{anyhow, bail, Result};
+use futures::prelude::*;
+use std::fmt;
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::WebSocketStream;
+
+/// The wire format used to communicate with the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// A single JSON object per UDP datagram.
+    UdpJson,
+    /// One line of text per effect over a TCP connection.
+    TcpLine,
+    /// A single JSON object per websocket text message.
+    Websocket,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::UdpJson
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Format::UdpJson => "udp-json".fmt(fmt),
+            Format::TcpLine => "tcp-line".fmt(fmt),
+            Format::Websocket => "websocket".fmt(fmt),
+        }
+    }
+}
+
+/// A single effect being sent to the game.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Message {
+    /// Unique identifier of the effect.
+    pub id: usize,
+    /// The user who triggered the effect.
+    pub user: String,
+    /// The name of the effect.
+    pub effect: String,
+    /// Arguments to the effect, in the order they are declared.
+    pub arguments: Vec<serde_json::Value>,
+}
+
+impl Message {
+    /// Encode the message as a single line of text: `<user> <id> <effect> <arguments...>`.
+    ///
+    /// This is the same layout that ChaosMod uses.
+    pub fn to_line(&self) -> String {
+        let mut line = format!("{} {} {}", self.user, self.id, self.effect);
+
+        for argument in &self.arguments {
+            line.push(' ');
+
+            match argument {
+                serde_json::Value::String(s) => line.push_str(s),
+                other => line.push_str(&other.to_string()),
+            }
+        }
+
+        line
+    }
+}
+
+/// A connection to the game using one of the supported wire formats.
+///
+/// Connection-oriented formats connect lazily and reconnect on the next
+/// message if the connection is lost.
+pub struct Transport {
+    format: Format,
+    address: String,
+    udp: Option<UdpSocket>,
+    tcp: Option<TcpStream>,
+    websocket: Option<WebSocketStream<TcpStream>>,
+}
+
+impl Transport {
+    /// Construct a new transport sending to the given address.
+    pub fn new(format: Format, address: String) -> Self {
+        Self {
+            format,
+            address,
+            udp: None,
+            tcp: None,
+            websocket: None,
+        }
+    }
+
+    /// Send the given message.
+    pub async fn send(&mut self, message: &Message) -> Result<()> {
+        let result = match self.format {
+            Format::UdpJson => self.send_udp(message).await,
+            Format::TcpLine => self.send_tcp(message).await,
+            Format::Websocket => self.send_websocket(message).await,
+        };
+
+        // NB: drop any broken connection so that we reconnect next time.
+        if result.is_err() {
+            self.udp = None;
+            self.tcp = None;
+            self.websocket = None;
+        }
+
+        result
+    }
+
+    async fn send_udp(&mut self, message: &Message) -> Result<()> {
+        if self.udp.is_none() {
+            let address = socket_addr(Format::UdpJson, &self.address).await?;
+            // NB: bind to the same address family as the one we're sending
+            // to.
+            let local = match address {
+                SocketAddr::V4(..) => "0.0.0.0:0",
+                SocketAddr::V6(..) => "[::]:0",
+            };
+
+            let socket = UdpSocket::bind(&str::parse::<SocketAddr>(local)?).await?;
+            socket.connect(&address).await?;
+            self.udp = Some(socket);
+        }
+
+        let socket = self.udp.as_mut().ok_or_else(|| anyhow!("not connected"))?;
+        let message = serde_json::to_vec(message)?;
+        socket.send(&message).await?;
+        Ok(())
+    }
+
+    async fn send_tcp(&mut self, message: &Message) -> Result<()> {
+        if self.tcp.is_none() {
+            let address = socket_addr(Format::TcpLine, &self.address).await?;
+            self.tcp = Some(TcpStream::connect(&address).await?);
+        }
+
+        let stream = self.tcp.as_mut().ok_or_else(|| anyhow!("not connected"))?;
+        let mut line = message.to_line();
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    async fn send_websocket(&mut self, message: &Message) -> Result<()> {
+        if self.websocket.is_none() {
+            let url = url::Url::parse(&self.address)?;
+            let address = socket_addr(Format::Websocket, &self.address).await?;
+            let stream = TcpStream::connect(&address).await?;
+            let (stream, _) = tokio_tungstenite::client_async(url, stream).await?;
+            self.websocket = Some(stream);
+        }
+
+        let stream = self
+            .websocket
+            .as_mut()
+            .ok_or_else(|| anyhow!("not connected"))?;
+
+        let message = serde_json::to_string(message)?;
+        stream.send(tungstenite::Message::Text(message)).await?;
+        Ok(())
+    }
+}
+
+/// Resolve the socket address to use for the given format.
+///
+/// Websockets are addressed by URL, like `ws://localhost:7292`, while the
+/// other formats use a plain `<host>:<port>`, like `localhost:7292`.
+async fn socket_addr(format: Format, address: &str) -> Result<SocketAddr> {
+    let address = match format {
+        Format::Websocket => {
+            let url = url::Url::parse(address)?;
+
+            if url.scheme() != "ws" {
+                bail!(
+                    "unsupported websocket scheme `{}`, expected `ws`",
+                    url.scheme()
+                );
+            }
+
+            let host = url
+                .host_str()
+                .ok_or_else(|| anyhow!("missing host in `{}`", address))?;
+            let port = url.port_or_known_default().unwrap_or(80);
+            format!("{}:{}", host, port)
+        }
+        _ => address.to_string(),
+    };
+
+    let mut addrs = tokio::net::lookup_host(address.as_str()).await?;
+
+    match addrs.next() {
+        Some(addr) => Ok(addr),
+        None => bail!("`{}` didn't resolve to any address", address),
+    }
+}
+
+/// Run a local echo server which receives effects in the given format and
+/// logs them.
+///
+/// This stands in for a game when testing effects.
+pub async fn echo_server(format: Format, address: String) -> Result<()> {
+    let address = socket_addr(format, &address).await?;
+    log::info!(
+        "Game bridge echo server listening on {} ({})",
+        address,
+        format
+    );
+
+    match format {
+        Format::UdpJson => {
+            let mut socket = UdpSocket::bind(&address).await?;
+            let mut buf = vec![0u8; 65536];
+
+            loop {
+                let (n, from) = socket.recv_from(&mut buf).await?;
+                log::info!("echo: {}: {}", from, String::from_utf8_lossy(&buf[..n]));
+            }
+        }
+        Format::TcpLine => {
+            let mut listener = TcpListener::bind(&address).await?;
+
+            loop {
+                let (stream, from) = listener.accept().await?;
+
+                tokio::spawn(async move {
+                    let mut lines = BufReader::new(stream).lines();
+
+                    while let Some(line) = lines.next().await {
+                        match line {
+                            Ok(line) => log::info!("echo: {}: {}", from, line),
+                            Err(e) => {
+                                log::warn!("echo: {}: {}", from, e);
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        }
+        Format::Websocket => {
+            let mut listener = TcpListener::bind(&address).await?;
+
+            loop {
+                let (stream, from) = listener.accept().await?;
+
+                tokio::spawn(async move {
+                    let mut stream = match tokio_tungstenite::accept_async(stream).await {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            log::warn!("echo: {}: {}", from, e);
+                            return;
+                        }
+                    };
+
+                    while let Some(message) = stream.next().await {
+                        match message {
+                            Ok(tungstenite::Message::Text(text)) => {
+                                log::info!("echo: {}: {}", from, text)
+                            }
+                            Ok(tungstenite::Message::Close(..)) => break,
+                            Ok(..) => (),
+                            Err(e) => {
+                                log::warn!("echo: {}: {}", from, e);
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{socket_addr, Format};
+
+    #[tokio::test]
+    async fn test_socket_addr() {
+        let addr = socket_addr(Format::UdpJson, "127.0.0.1:7292")
+            .await
+            .unwrap();
+        assert_eq!(addr, str::parse("127.0.0.1:7292").unwrap());
+
+        let addr = socket_addr(Format::TcpLine, "localhost:7292")
+            .await
+            .unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(addr.port(), 7292);
+
+        let addr = socket_addr(Format::Websocket, "ws://localhost:7292")
+            .await
+            .unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(addr.port(), 7292);
+
+        assert!(socket_addr(Format::Websocket, "wss://localhost:7292")
+            .await
+            .is_err());
+    }
+}
@@ -1,21 +1,29 @@
 use crate::auth::Scope;
+use crate::bus;
 use crate::command;
 use crate::currency;
 use crate::irc;
 use crate::module;
 use crate::player;
 use crate::prelude::*;
-use crate::utils::{compact_duration, Cooldown, Duration};
+use crate::utils::{compact_duration, update_cooldown, Cooldown, Cooldowns, Duration};
 use anyhow::{bail, Result};
-use std::collections::{hash_map, HashMap};
+use parking_lot::RwLock;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time;
+use tokio::net::udp::SendHalf;
 use tokio::net::UdpSocket;
 use tokio::sync::Mutex;
 
 const VEHICLE_URL: &str = "http://bit.ly/gtavvehicles";
+/// How long since the last heartbeat before ChaosMod is considered
+/// disconnected.
+const HEARTBEAT_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+/// How frequently we check for effects which weren't acknowledged in time.
+const ACK_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(1);
 
 mod vehicle;
 mod weapon;
@@ -54,19 +62,22 @@ macro_rules! vehicle {
     };
 }
 
+/// A per-command configuration override.
+///
+/// Also used for effect overrides in `game_bridge`.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct CommandConfig {
-    name: String,
+pub(super) struct CommandConfig {
+    pub(super) name: String,
     #[serde(default)]
-    enabled: Option<bool>,
+    pub(super) enabled: Option<bool>,
     #[serde(default)]
-    cooldown: Option<Duration>,
+    pub(super) cooldown: Option<Duration>,
     #[serde(default)]
-    cost: Option<u32>,
+    pub(super) cost: Option<u32>,
 }
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
-struct CommandsConfig(Vec<CommandConfig>);
+pub(super) struct CommandsConfig(pub(super) Vec<CommandConfig>);
 
 #[derive(Debug)]
 struct CommandSetting {
@@ -105,6 +116,7 @@ impl CommandsConfig {
     }
 }
 
+#[derive(Clone)]
 enum Command {
     /// Spawn a vehicle.
     SpawnVehicle(Vehicle),
@@ -277,6 +289,19 @@ impl Command {
         }
     }
 
+    /// How long the effect lasts, if it lasts for a specific amount of time.
+    fn duration(&self) -> Option<time::Duration> {
+        use self::Command::*;
+
+        match *self {
+            SuperSpeed(n) | SuperSwim(n) | SuperJump(n) | Invincibility(n)
+            | ExplodingBullets(n) | FireAmmo(n) | ExplodingPunches(n) | MakeFireProof(n) => {
+                Some(time::Duration::from_secs_f32(n.max(0.0)))
+            }
+            _ => None,
+        }
+    }
+
     /// If the command is a reward or a punishment.
     fn what(&self) -> &'static str {
         use self::Command::*;
@@ -536,6 +561,75 @@ impl fmt::Display for Command {
     }
 }
 
+/// A reply from ChaosMod.
+#[derive(Debug, PartialEq, Eq)]
+enum Reply {
+    /// `ack <id>`: the effect with the given id was applied.
+    Ack(usize),
+    /// `nack <id> <reason>`: the effect with the given id could not be
+    /// applied.
+    Nack(usize, String),
+    /// `heartbeat`: sent periodically while the mod is running.
+    Heartbeat,
+}
+
+impl Reply {
+    /// Parse a reply datagram.
+    fn parse(input: &str) -> Option<Reply> {
+        let mut it = input.trim().splitn(3, ' ');
+
+        match it.next()? {
+            "ack" => Some(Reply::Ack(str::parse(it.next()?).ok()?)),
+            "nack" => {
+                let id = str::parse(it.next()?).ok()?;
+                let reason = it.next().unwrap_or("unknown reason").to_string();
+                Some(Reply::Nack(id, reason))
+            }
+            "heartbeat" => Some(Reply::Heartbeat),
+            _ => None,
+        }
+    }
+}
+
+/// An effect which has been paid for and should be sent to ChaosMod.
+#[derive(Clone)]
+struct Request {
+    user: irc::User,
+    id: usize,
+    command: Command,
+    /// What the user was charged, which is refunded if the effect isn't
+    /// acknowledged.
+    cost: u32,
+}
+
+impl Request {
+    /// Describe the request for the overlay.
+    fn to_effect(&self, remaining: Option<time::Duration>) -> bus::GtavEffect {
+        bus::GtavEffect {
+            id: self.id,
+            user: self.user.display_name().unwrap_or("Someone").to_string(),
+            effect: self.command.command_name().to_string(),
+            description: self.command.to_string(),
+            cost: self.cost,
+            duration: self.command.duration().map(|d| d.as_secs()),
+            remaining: remaining.map(|d| d.as_secs()),
+        }
+    }
+}
+
+/// An effect which is waiting for an acknowledgement.
+struct Pending {
+    request: Request,
+    deadline: time::Instant,
+}
+
+/// The state of the connection to ChaosMod, as seen through heartbeats.
+#[derive(Default)]
+struct Connection {
+    last_heartbeat: Option<time::Instant>,
+    pending: usize,
+}
+
 #[derive(Clone)]
 pub struct Reward {
     user: String,
@@ -557,13 +651,44 @@ pub struct Handler {
     reward_percentage: settings::Var<u32>,
     success_feedback: settings::Var<bool>,
     id_counter: AtomicUsize,
-    tx: mpsc::UnboundedSender<(irc::User, usize, Command)>,
+    tx: mpsc::UnboundedSender<Request>,
+    connection: Arc<Mutex<Connection>>,
     per_user_cooldowns: Mutex<HashMap<String, Cooldown>>,
     per_command_cooldowns: Mutex<HashMap<&'static str, Cooldown>>,
     per_command_configs: settings::Var<HashMap<String, CommandSetting>>,
 }
 
 impl Handler {
+    /// Report on whether ChaosMod is connected.
+    async fn handle_status(&self, ctx: &mut command::Context) {
+        let connection = self.connection.lock().await;
+
+        let since = connection
+            .last_heartbeat
+            .map(|last| time::Instant::now().saturating_duration_since(last));
+
+        match since {
+            Some(since) if since < HEARTBEAT_TIMEOUT => {
+                respond!(
+                    ctx,
+                    "ChaosMod is connected (last heartbeat {} ago), {} effect(s) waiting for the game.",
+                    compact_duration(since),
+                    connection.pending,
+                );
+            }
+            Some(since) => {
+                respond!(
+                    ctx,
+                    "ChaosMod is not connected, last heartbeat was {} ago.",
+                    compact_duration(since),
+                );
+            }
+            None => {
+                respond!(ctx, "ChaosMod is not connected, no heartbeat received.");
+            }
+        }
+    }
+
     /// Play the specified theme song.
     async fn play_theme_song(&self, ctx: &command::Context, id: &str) {
         let player = self.player.load().await;
@@ -603,102 +728,63 @@ impl Handler {
         let per_user_cooldown = self.per_user_cooldown.load().await;
 
         // NB: only real users are subject to cooldown.
-        let mut user_cooldown = {
-            match ctx.user.real() {
-                Some(user) => match per_user_cooldowns.entry(user.name().to_string()) {
-                    hash_map::Entry::Vacant(e) => Some(e.insert(per_user_cooldown.clone())),
-                    hash_map::Entry::Occupied(e) => {
-                        let cooldown = e.into_mut();
-
-                        if cooldown.cooldown != per_user_cooldown.cooldown {
-                            cooldown.cooldown = per_user_cooldown.cooldown.clone();
-                        }
-
-                        Some(cooldown)
-                    }
-                },
-                None => None,
-            }
-        };
+        let user_cooldown = ctx.user.real().map(|user| {
+            update_cooldown(
+                per_user_cooldowns.entry(user.name().to_string()),
+                per_user_cooldown.cooldown,
+            )
+        });
 
         let per_command_cooldown = self.per_command_cooldown.load().await;
 
-        let command_cooldown = {
-            match per_command_cooldowns.entry(command.command_name()) {
-                hash_map::Entry::Vacant(e) => e.insert(per_command_cooldown.clone()),
-                hash_map::Entry::Occupied(e) => {
-                    let cooldown = e.into_mut();
+        let command_cooldown = update_cooldown(
+            per_command_cooldowns.entry(command.command_name()),
+            per_command_cooldown.cooldown,
+        );
 
-                    if cooldown.cooldown != per_command_cooldown.cooldown {
-                        cooldown.cooldown = per_command_cooldown.cooldown.clone();
-                    }
+        let command_specific = self
+            .per_command_configs
+            .read()
+            .await
+            .get(command.command_name())
+            .and_then(|setting| setting.cooldown.clone());
 
-                    cooldown
-                }
-            }
-        };
+        let mut cooldown = self.cooldown.write().await;
 
-        let command_specific = {
-            match self
-                .per_command_configs
-                .read()
-                .await
-                .get(command.command_name())
-                .clone()
-            {
-                Some(setting) => setting.cooldown.clone(),
-                None => None,
-            }
+        let mut command_specific = match command_specific.as_ref() {
+            Some(command_specific) => Some(command_specific.write().await),
+            None => None,
         };
 
-        let now = time::Instant::now();
-
-        let mut cooldown = self.cooldown.write().await;
+        let mut category_cooldown = match category_cooldown.as_ref() {
+            Some(category_cooldown) => Some(category_cooldown.write().await),
+            None => None,
+        };
 
-        let mut remaining = smallvec::SmallVec::<[_; 4]>::new();
+        let mut cooldowns = Cooldowns::default();
 
-        if let Some(user_cooldown) = user_cooldown.as_mut() {
-            remaining.extend(user_cooldown.check(now.clone()).map(|d| ("User", d)));
+        if let Some(user_cooldown) = user_cooldown {
+            cooldowns.check("User", user_cooldown);
         }
 
-        if let Some(command_specific) = command_specific.as_ref() {
-            let mut cooldown = command_specific.write().await;
+        if let Some(command_specific) = command_specific.as_mut() {
+            cooldowns.check("Command specific", command_specific);
+            cooldowns.poke(&mut cooldown);
+            cooldowns.poke(command_cooldown);
 
-            remaining.extend(cooldown.check(now.clone()).map(|d| ("Command specific", d)));
+            if let Some(category_cooldown) = category_cooldown.as_mut() {
+                cooldowns.poke(category_cooldown);
+            }
         } else {
-            remaining.extend(cooldown.check(now.clone()).map(|d| ("Global", d)));
-            remaining.extend(command_cooldown.check(now.clone()).map(|d| ("Command", d)));
+            cooldowns.check("Global", &mut cooldown);
+            cooldowns.check("Command", command_cooldown);
 
-            if let Some(category_cooldown) = category_cooldown.as_ref() {
-                let mut cooldown = category_cooldown.write().await;
-                remaining.extend(cooldown.check(now.clone()).map(|d| ("Category", d)));
+            if let Some(category_cooldown) = category_cooldown.as_mut() {
+                cooldowns.check("Category", category_cooldown);
             }
         }
 
-        remaining.sort_by(|a, b| b.1.cmp(&a.1));
-
-        match remaining.into_iter().next() {
-            Some((name, remaining)) => Some((name, remaining)),
-            None => {
-                cooldown.poke(now);
-
-                if let Some(user_cooldown) = user_cooldown.as_mut() {
-                    user_cooldown.poke(now);
-                }
-
-                command_cooldown.poke(now);
-
-                if let Some(category_cooldown) = category_cooldown.as_ref() {
-                    category_cooldown.write().await.poke(now);
-                }
-
-                if let Some(command_specific) = command_specific.as_ref() {
-                    command_specific.write().await.poke(now);
-                }
-
-                None
-            }
-        }
+        cooldowns.finish(time::Instant::now())
     }
 
     /// Handle the other commands.
@@ -904,13 +990,20 @@ impl command::Handler for Handler {
             return Ok(());
         }
 
+        let action = ctx.next();
+
+        if action.as_deref() == Some("status") {
+            self.handle_status(ctx).await;
+            return Ok(());
+        }
+
         let currency = self
             .currency
             .load()
             .await
             .ok_or_else(|| respond_err!("No currency configured for stream, sorry :("))?;
 
-        let (result, category_cooldown) = match ctx.next().as_deref() {
+        let (result, category_cooldown) = match action.as_deref() {
             Some("other") => {
                 let command = self.handle_other(ctx).await?;
                 (command, None)
@@ -930,8 +1023,9 @@ impl command::Handler for Handler {
                     ctx,
                     "You have the following actions available: \
                     reward - To reward the streamer, \
-                    punish - To punish the streamer,
-                    other - To do other kinds of modifications.",
+                    punish - To punish the streamer, \
+                    other - To do other kinds of modifications, \
+                    status - To check if the game is connected.",
                 );
 
                 return Ok(());
@@ -982,6 +1076,7 @@ impl command::Handler for Handler {
         let sender = ctx.inner.sender.clone();
         let prefix = self.prefix.load().await;
         let tx = self.tx.clone();
+        let mut charged = 0;
 
         if let Some(real) = ctx.user.real() {
             let balance = currency
@@ -1014,6 +1109,8 @@ impl command::Handler for Handler {
             currency
                 .balance_add(ctx.user.channel(), real.name(), -(cost as i64))
                 .await?;
+
+            charged = cost;
         }
 
         if self.success_feedback.load().await {
@@ -1035,7 +1132,14 @@ impl command::Handler for Handler {
                 .await;
         }
 
-        if tx.unbounded_send((ctx.user.clone(), id, command)).is_err() {
+        let request = Request {
+            user: ctx.user.clone(),
+            id,
+            command,
+            cost: charged,
+        };
+
+        if tx.unbounded_send(request).is_err() {
             bail!("failed to send event");
         }
 
@@ -1043,6 +1147,279 @@ impl command::Handler for Handler {
     }
 }
 
+/// A command to the effect queue, sent from the web UI.
+enum QueueCommand {
+    /// Veto the queued effect with the given id, refunding it.
+    Veto(usize, oneshot::Sender<bool>),
+    /// Veto all queued effects, refunding them.
+    Clear(oneshot::Sender<usize>),
+}
+
+/// Pending and active effects.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct QueueState {
+    /// Effects waiting to be sent to the game.
+    pub pending: Vec<bus::GtavEffect>,
+    /// Effects which have been sent and are still in effect.
+    pub active: Vec<bus::GtavEffect>,
+}
+
+/// Handle to the queue of effects.
+#[derive(Clone)]
+pub struct Queue {
+    tx: mpsc::UnboundedSender<QueueCommand>,
+    state: Arc<RwLock<QueueState>>,
+}
+
+impl Queue {
+    /// Get the current state of the queue.
+    pub fn state(&self) -> QueueState {
+        self.state.read().clone()
+    }
+
+    /// Veto the queued effect with the given id, refunding the user.
+    ///
+    /// Returns `false` if the effect is no longer queued.
+    pub async fn veto(&self, id: usize) -> Result<bool> {
+        let (tx, rx) = oneshot::channel();
+
+        if self.tx.unbounded_send(QueueCommand::Veto(id, tx)).is_err() {
+            bail!("queue is not running");
+        }
+
+        Ok(rx.await?)
+    }
+
+    /// Veto all queued effects, refunding the users.
+    ///
+    /// Returns the number of vetoed effects.
+    pub async fn clear(&self) -> Result<usize> {
+        let (tx, rx) = oneshot::channel();
+
+        if self.tx.unbounded_send(QueueCommand::Clear(tx)).is_err() {
+            bail!("queue is not running");
+        }
+
+        Ok(rx.await?)
+    }
+}
+
+/// An effect which has been sent and is still in effect.
+struct Active {
+    request: Request,
+    until: time::Instant,
+}
+
+/// State of the connection to ChaosMod.
+struct Game {
+    socket: SendHalf,
+    currency: injector::Var<Option<currency::Currency>>,
+    sender: irc::Sender,
+    prefix: settings::Var<String>,
+    global_bus: Arc<bus::Bus<bus::Global>>,
+    acknowledge: settings::Var<bool>,
+    ack_timeout: settings::Var<Duration>,
+    connection: Arc<Mutex<Connection>>,
+    state: Arc<RwLock<QueueState>>,
+    /// Effects waiting for an acknowledgement.
+    pending: HashMap<usize, Pending>,
+    /// Effects waiting to be sent.
+    queue: VecDeque<Request>,
+    /// Effects which are in effect.
+    active: Vec<Active>,
+}
+
+impl Game {
+    /// Send the given effect to the game.
+    async fn send(&mut self, request: Request) {
+        let who = request.user.name().unwrap_or("unknown");
+        let message = format!("{} {} {}", who, request.id, request.command.command());
+        log::info!("sent: {}", message);
+
+        if let Err(e) = self.socket.send(message.as_bytes()).await {
+            log::error!("failed to send message: {}", e);
+        }
+
+        let now = time::Instant::now();
+
+        if let Some(duration) = request.command.duration() {
+            self.active.push(Active {
+                request: request.clone(),
+                until: now + duration,
+            });
+        }
+
+        if self.acknowledge.load().await {
+            let deadline = now + self.ack_timeout.load().await.as_std();
+            self.pending
+                .insert(request.id, Pending { request, deadline });
+            self.connection.lock().await.pending = self.pending.len();
+        }
+
+        self.publish().await;
+    }
+
+    /// Handle a reply from the game.
+    async fn handle_reply(&mut self, reply: Reply) {
+        match reply {
+            Reply::Heartbeat => {
+                self.connection.lock().await.last_heartbeat = Some(time::Instant::now());
+            }
+            Reply::Ack(id) => {
+                if self.pending.remove(&id).is_some() {
+                    log::info!("acknowledged: {}", id);
+                }
+            }
+            Reply::Nack(id, reason) => {
+                if let Some(p) = self.pending.remove(&id) {
+                    self.active.retain(|a| a.request.id != id);
+                    let what = format!("failed in the game ({})", reason);
+                    self.refund(p.request, &what).await;
+                    self.publish().await;
+                }
+            }
+        }
+
+        self.connection.lock().await.pending = self.pending.len();
+    }
+
+    /// Handle a command from the web UI.
+    async fn handle_queue_command(&mut self, command: QueueCommand) {
+        let vetoed = match command {
+            QueueCommand::Veto(id, tx) => {
+                let index = self.queue.iter().position(|r| r.id == id);
+                let request = index.and_then(|index| self.queue.remove(index));
+                let _ = tx.send(request.is_some());
+                request.into_iter().collect::<Vec<_>>()
+            }
+            QueueCommand::Clear(tx) => {
+                let vetoed = self.queue.drain(..).collect::<Vec<_>>();
+                let _ = tx.send(vetoed.len());
+                vetoed
+            }
+        };
+
+        if vetoed.is_empty() {
+            return;
+        }
+
+        for request in vetoed {
+            self.refund(request, "was vetoed by the streamer").await;
+        }
+
+        self.publish().await;
+    }
+
+    /// Refund effects which weren't acknowledged in time, and expire
+    /// effects which are no longer active.
+    async fn expire(&mut self) {
+        let now = time::Instant::now();
+
+        let expired = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in &expired {
+            if let Some(p) = self.pending.remove(id) {
+                self.active.retain(|a| a.request.id != *id);
+                self.refund(p.request, "wasn't acknowledged by the game")
+                    .await;
+            }
+        }
+
+        if !expired.is_empty() {
+            self.connection.lock().await.pending = self.pending.len();
+        }
+
+        // NB: publish every tick while effects are active so that overlays
+        // see the remaining time.
+        if !expired.is_empty() || !self.active.is_empty() {
+            self.active.retain(|a| a.until > now);
+            self.publish().await;
+        }
+    }
+
+    /// Publish the current state of the queue to the overlay.
+    async fn publish(&mut self) {
+        let now = time::Instant::now();
+
+        let state = QueueState {
+            pending: self.queue.iter().map(|r| r.to_effect(None)).collect(),
+            active: self
+                .active
+                .iter()
+                .map(|a| {
+                    a.request
+                        .to_effect(Some(a.until.saturating_duration_since(now)))
+                })
+                .collect(),
+        };
+
+        *self.state.write() = state.clone();
+
+        self.global_bus
+            .send(bus::Global::GtavQueue {
+                pending: state.pending,
+                active: state.active,
+            })
+            .await;
+    }
+
+    /// Refund an effect which couldn't be applied, and let the user know.
+    async fn refund(&self, request: Request, what: &str) {
+        let Request {
+            user,
+            command,
+            cost,
+            ..
+        } = request;
+
+        log::warn!("effect {} {}, refunding {}", command, what, cost);
+
+        let who = user.display_name().unwrap_or("Someone");
+        let prefix = self.prefix.load().await;
+
+        let currency = match self.currency.load().await {
+            Some(currency) if cost > 0 => currency,
+            _ => {
+                self.sender
+                    .privmsg(format!("{}{}'s {} {}.", prefix, who, command, what))
+                    .await;
+                return;
+            }
+        };
+
+        let result = match user.real() {
+            Some(real) => {
+                currency
+                    .balance_add(user.channel(), real.name(), cost as i64)
+                    .await
+            }
+            None => Ok(()),
+        };
+
+        if let Err(e) = result {
+            log_error!(e, "failed to refund {} to {}", cost, who);
+            return;
+        }
+
+        self.sender
+            .privmsg(format!(
+                "{prefix}{user}'s {command} {what}, refunded {cost} {currency}.",
+                prefix = prefix,
+                user = who,
+                command = command,
+                what = what,
+                cost = cost,
+                currency = currency.name,
+            ))
+            .await;
+    }
+}
+
 /// Parse a license plate.Arc
 async fn license(input: &str, ctx: &command::Context) -> Option<String> {
     match input {
@@ -1067,6 +1444,10 @@ impl super::Module for Module {
         "gtav"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
@@ -1075,6 +1456,8 @@ impl super::Module for Module {
             settings,
             futures,
             injector,
+            sender,
+            global_bus,
             ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
@@ -1122,14 +1505,15 @@ impl super::Module for Module {
 
         let player = injector.var().await?;
 
-        let (tx, mut rx) = mpsc::unbounded();
+        let (tx, mut rx) = mpsc::unbounded::<Request>();
+        let connection = Arc::new(Mutex::new(Connection::default()));
 
         handlers.insert(
             "gtav",
             Handler {
                 enabled: enabled.clone(),
                 player,
-                currency,
+                currency: currency.clone(),
                 cooldown,
                 reward_cooldown,
                 punish_cooldown,
@@ -1138,13 +1522,14 @@ impl super::Module for Module {
                 per_command_cooldown,
                 per_command_cooldowns: Mutex::new(Default::default()),
                 per_command_configs: per_command_configs.clone(),
-                prefix,
+                prefix: prefix.clone(),
                 other_percentage,
                 punish_percentage,
                 reward_percentage,
                 success_feedback,
                 id_counter: AtomicUsize::new(0),
                 tx,
+                connection: connection.clone(),
             },
         );
 
@@ -1153,6 +1538,69 @@ impl super::Module for Module {
             .connect(&str::parse::<SocketAddr>("127.0.0.1:7291")?)
             .await?;
 
+        let (mut socket_rx, socket_tx) = socket.split();
+        let (reply_tx, mut reply_rx) = mpsc::unbounded::<Reply>();
+
+        // Receive replies from ChaosMod.
+        let replies = async move {
+            let mut buf = vec![0u8; 1024];
+
+            loop {
+                let n = match socket_rx.recv(&mut buf).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        // NB: sending to a closed port causes the next receive
+                        // to fail on some platforms, which is expected when
+                        // the game isn't running.
+                        log::trace!("failed to receive reply: {}", e);
+                        tokio::time::delay_for(ACK_CHECK_INTERVAL).await;
+                        continue;
+                    }
+                };
+
+                let reply = String::from_utf8_lossy(&buf[..n]);
+
+                match Reply::parse(&reply) {
+                    Some(reply) => {
+                        if reply_tx.unbounded_send(reply).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    None => log::warn!("unsupported reply from ChaosMod: {}", reply),
+                }
+            }
+        };
+
+        futures.push(replies.boxed());
+
+        let (queue_tx, mut queue_rx) = mpsc::unbounded();
+        let state = Arc::new(RwLock::new(QueueState::default()));
+
+        injector
+            .update(Queue {
+                tx: queue_tx,
+                state: state.clone(),
+            })
+            .await;
+
+        let mut game = Game {
+            socket: socket_tx,
+            currency,
+            sender: sender.clone(),
+            prefix,
+            global_bus: global_bus.clone(),
+            acknowledge: settings.var("acknowledge", false).await?,
+            ack_timeout: settings.var("ack-timeout", Duration::seconds(10)).await?,
+            connection,
+            state,
+            pending: HashMap::new(),
+            queue: VecDeque::new(),
+            active: Vec::new(),
+        };
+
+        let queued = settings.var("queue", false).await?;
+        let queue_gap = settings.var("queue-gap", Duration::seconds(10)).await?;
+
         let future = async move {
             let mut receiver = if enabled.load().await {
                 Some(&mut rx)
@@ -1160,6 +1608,10 @@ impl super::Module for Module {
                 None
             };
 
+            let mut ack_interval = tokio::time::interval(ACK_CHECK_INTERVAL).fuse();
+            // Delay until the next queued effect can be sent.
+            let mut gap = None::<tokio::time::Delay>;
+
             loop {
                 futures::select! {
                     update = commands_config_stream.select_next_some() => {
@@ -1173,20 +1625,37 @@ impl super::Module for Module {
 
                         *enabled.write().await = update;
                     }
-                    command = receiver.next() => {
-                        if let Some((user, id, command)) = command {
-                            let who = user.name().unwrap_or("unknown");
-                            let message = format!("{} {} {}", who, id, command.command());
-                            log::info!("sent: {}", message);
-
-                            match socket.send(message.as_bytes()).await {
-                                Ok(_) => (),
-                                Err(e) => {
-                                    log::error!("failed to send message: {}", e);
-                                }
+                    request = receiver.next() => {
+                        if let Some(request) = request {
+                            if queued.load().await || !game.queue.is_empty() {
+                                game.queue.push_back(request);
+                                game.publish().await;
+                            } else {
+                                game.send(request).await;
                             }
                         }
                     }
+                    _ = gap.current() => {
+                        gap = None;
+                    }
+                    command = queue_rx.select_next_some() => {
+                        game.handle_queue_command(command).await;
+                    }
+                    reply = reply_rx.select_next_some() => {
+                        game.handle_reply(reply).await;
+                    }
+                    _ = ack_interval.select_next_some() => {
+                        game.expire().await;
+                    }
+                }
+
+                // NB: send the next queued effect once the gap since the last
+                // effect has passed.
+                if gap.is_none() {
+                    if let Some(request) = game.queue.pop_front() {
+                        game.send(request).await;
+                        gap = Some(tokio::time::delay_for(queue_gap.load().await.as_std()));
+                    }
                 }
             }
         };
@@ -1309,3 +1778,20 @@ impl fmt::Display for Control {
         s.fmt(fmt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Reply;
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(Reply::parse("ack 42"), Some(Reply::Ack(42)));
+        assert_eq!(
+            Reply::parse("nack 42 not in a vehicle\n"),
+            Some(Reply::Nack(42, String::from("not in a vehicle")))
+        );
+        assert_eq!(Reply::parse("heartbeat"), Some(Reply::Heartbeat));
+        assert_eq!(Reply::parse("ack foo"), None);
+        assert_eq!(Reply::parse("what"), None);
+    }
+}
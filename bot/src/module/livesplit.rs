@@ -0,0 +1,490 @@
+use crate::api::{
+    self,
+    livesplit::{self, TimerPhase},
+    speedrun::{Embed, Embeds},
+    LiveSplit,
+};
+use crate::auth;
+use crate::bus;
+use crate::command;
+use crate::module;
+use crate::module::speedrun::CachedSpeedrun;
+use crate::prelude::*;
+use crate::storage::Cache;
+use anyhow::{anyhow, Result};
+use chrono::Duration;
+use std::time;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// How often to poll LiveSplit Server for splits.
+const POLL_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+/// A connection to LiveSplit Server shared between commands and the poller.
+#[derive(Clone)]
+struct Shared {
+    client: Arc<Mutex<LiveSplit>>,
+    address: settings::Var<String>,
+}
+
+impl Shared {
+    /// Lock the client, making sure that it uses the configured address.
+    async fn lock(&self) -> MutexGuard<'_, LiveSplit> {
+        let address = self.address.load().await;
+        let mut client = self.client.lock().await;
+        client.set_address(address);
+        client
+    }
+}
+
+/// The personal best of the configured runner on speedrun.com.
+struct PersonalBest {
+    runner: String,
+    game: String,
+    category: String,
+    time: Duration,
+    place: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Splits,
+    Pace,
+    PersonalBest,
+}
+
+/// Handler for the `!splits`, `!pace`, and `!pb` commands.
+struct Handler {
+    kind: Kind,
+    enabled: settings::Var<bool>,
+    shared: Shared,
+    speedrun: CachedSpeedrun,
+    runner: settings::Var<Option<String>>,
+    game: settings::Var<Option<String>>,
+    category: settings::Var<Option<String>>,
+}
+
+impl Handler {
+    /// Respond with the current split.
+    async fn splits(&self, ctx: &mut command::Context) -> Result<()> {
+        let mut client = self.shared.lock().await;
+
+        let phase = client.timer_phase().await?;
+
+        if phase == TimerPhase::NotRunning {
+            respond!(ctx, "The timer isn't running");
+            return Ok(());
+        }
+
+        let index = client.split_index().await?;
+        let current = client.current_split_name().await?;
+        let previous = client.previous_split_name().await?;
+        let last_split_time = client.last_split_time().await?;
+        let delta = client.delta().await?;
+        drop(client);
+
+        let mut parts = Vec::new();
+
+        match (phase, index, current) {
+            (TimerPhase::Ended, ..) => parts.push(String::from("The run is over")),
+            (_, Some(index), Some(current)) => {
+                parts.push(format!("Split #{}: {}", index + 1, current))
+            }
+            _ => (),
+        }
+
+        if let (Some(previous), Some(time)) = (previous, last_split_time) {
+            let delta = match delta {
+                Some(delta) => format!(" ({})", describe_delta(delta)),
+                None => String::new(),
+            };
+
+            parts.push(format!(
+                "last split {} at {}{}",
+                previous,
+                livesplit::format_time(time),
+                delta
+            ));
+        }
+
+        match parts.as_slice() {
+            [] => respond!(ctx, "No splits yet"),
+            parts => respond!(ctx, parts.join(", ")),
+        }
+
+        Ok(())
+    }
+
+    /// Respond with how the current run compares against the comparison in
+    /// LiveSplit and the personal best on speedrun.com.
+    async fn pace(&self, ctx: &mut command::Context) -> Result<()> {
+        let mut client = self.shared.lock().await;
+
+        if client.timer_phase().await? == TimerPhase::NotRunning {
+            respond!(ctx, "The timer isn't running");
+            return Ok(());
+        }
+
+        let current = client.current_split_name().await?;
+        let delta = client.delta().await?;
+        let predicted = client.predicted_time().await?;
+        drop(client);
+
+        let mut response = match (delta, current) {
+            (Some(delta), Some(current)) => {
+                format!(
+                    "Currently {} going into {}.",
+                    describe_delta(delta),
+                    current
+                )
+            }
+            (Some(delta), None) => format!("Currently {}.", describe_delta(delta)),
+            (None, _) => String::from("No comparison yet."),
+        };
+
+        if let Some(predicted) = predicted {
+            response.push_str(&format!(
+                " Predicted finish {}",
+                livesplit::format_time(predicted)
+            ));
+
+            match self.personal_best().await {
+                Ok(Some(pb)) => {
+                    response.push_str(&format!(
+                        ", {} compared to the PB on speedrun.com ({}).",
+                        describe_delta(predicted - pb.time),
+                        livesplit::format_time(pb.time),
+                    ));
+                }
+                Ok(None) => response.push('.'),
+                Err(e) => {
+                    log_error!(e, "Failed to get personal best from speedrun.com");
+                    response.push('.');
+                }
+            }
+        }
+
+        respond!(ctx, response);
+        Ok(())
+    }
+
+    /// Respond with the personal best on speedrun.com.
+    async fn personal_best_command(&self, ctx: &mut command::Context) -> Result<()> {
+        let pb = match self.personal_best().await {
+            Ok(Some(pb)) => pb,
+            Ok(None) => {
+                respond!(ctx, "No personal best found on speedrun.com");
+                return Ok(());
+            }
+            Err(e) => {
+                log_error!(e, "Failed to get personal best from speedrun.com");
+                respond!(ctx, "Couldn't talk to speedrun.com, try again later");
+                return Ok(());
+            }
+        };
+
+        let mut response = format!(
+            "{}'s PB in {} {} is {} (#{}).",
+            pb.runner,
+            pb.game,
+            pb.category,
+            livesplit::format_time(pb.time),
+            pb.place,
+        );
+
+        // NB: LiveSplit not running is not an error for this command.
+        let mut client = self.shared.lock().await;
+
+        let current = match client.timer_phase().await {
+            Ok(TimerPhase::Running) | Ok(TimerPhase::Paused) => client
+                .predicted_time()
+                .await
+                .ok()
+                .flatten()
+                .map(|t| ("The current run is predicted to finish in", t)),
+            Ok(TimerPhase::Ended) => client
+                .current_time()
+                .await
+                .ok()
+                .flatten()
+                .map(|t| ("The last run finished in", t)),
+            _ => None,
+        };
+
+        drop(client);
+
+        if let Some((what, time)) = current {
+            response.push_str(&format!(
+                " {} {}, {}.",
+                what,
+                livesplit::format_time(time),
+                describe_delta(time - pb.time)
+            ));
+        }
+
+        respond!(ctx, response);
+        Ok(())
+    }
+
+    /// Look up the personal best of the configured runner.
+    async fn personal_best(&self) -> Result<Option<PersonalBest>> {
+        let runner = match self.runner.load().await {
+            Some(runner) => runner,
+            None => return Ok(None),
+        };
+
+        let user = match self.speedrun.user_by_id(&runner).await? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        let game = self.game.load().await.map(|g| g.to_lowercase());
+        let category = self.category.load().await.map(|c| c.to_lowercase());
+
+        let mut embeds = Embeds::default();
+        embeds.push(Embed::Game);
+        embeds.push(Embed::Category);
+
+        let personal_bests = self
+            .speedrun
+            .user_personal_bests(&user.id, &embeds)
+            .await?
+            .unwrap_or_default();
+
+        for mut run in personal_bests {
+            // NB: per-level runs aren't comparable with a full run.
+            if run.run.level.is_some() {
+                continue;
+            }
+
+            let g = match run.game.take() {
+                Some(g) => g.data,
+                None => continue,
+            };
+
+            let c = match run.category.take() {
+                Some(c) => c.data,
+                None => continue,
+            };
+
+            if let Some(game) = game.as_deref() {
+                if !g.matches(game) {
+                    continue;
+                }
+            }
+
+            if let Some(category) = category.as_deref() {
+                if !c.name.to_lowercase().contains(category) {
+                    continue;
+                }
+            }
+
+            let time = run.run.times.primary.as_std();
+
+            return Ok(Some(PersonalBest {
+                runner: user.names.name().to_string(),
+                game: g.names.name().to_string(),
+                category: c.name,
+                time: Duration::milliseconds(time.as_millis() as i64),
+                place: run.place,
+            }));
+        }
+
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::LiveSplit)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let result = match self.kind {
+            Kind::Splits => self.splits(ctx).await,
+            Kind::Pace => self.pace(ctx).await,
+            Kind::PersonalBest => self.personal_best_command(ctx).await,
+        };
+
+        if let Err(e) = result {
+            log_error!(e, "LiveSplit command failed");
+            respond!(
+                ctx,
+                "Couldn't talk to LiveSplit, is LiveSplit Server running?"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Describe a delta, like `1.23 ahead`.
+fn describe_delta(delta: Duration) -> String {
+    if delta < Duration::zero() {
+        format!("{} ahead", livesplit::format_time(-delta))
+    } else if delta > Duration::zero() {
+        format!("{} behind", livesplit::format_time(delta))
+    } else {
+        String::from("even")
+    }
+}
+
+/// Poll LiveSplit Server and publish split events on the global bus.
+async fn poll(
+    shared: &Shared,
+    global_bus: &bus::Bus<bus::Global>,
+    last: &mut Option<(TimerPhase, Option<usize>)>,
+) -> Result<()> {
+    let mut client = shared.lock().await;
+
+    let phase = client.timer_phase().await?;
+    let split_index = client.split_index().await?;
+
+    if *last == Some((phase, split_index)) {
+        return Ok(());
+    }
+
+    let split_name = client.current_split_name().await?;
+    let previous_split_name = client.previous_split_name().await?;
+    let last_split_time = client.last_split_time().await?;
+    let delta = client.delta().await?;
+    drop(client);
+
+    *last = Some((phase, split_index));
+
+    global_bus
+        .send(bus::Global::LiveSplit {
+            phase,
+            split_index,
+            split_name,
+            previous_split_name,
+            last_split_time: last_split_time.map(livesplit::format_time),
+            delta: delta.map(livesplit::format_time),
+        })
+        .await;
+
+    Ok(())
+}
+
+pub struct Module;
+
+#[async_trait]
+impl super::Module for Module {
+    fn ty(&self) -> &'static str {
+        "livesplit"
+    }
+
+    fn primary_only(&self) -> bool {
+        true
+    }
+
+    /// Set up command handlers for this module.
+    async fn hook(
+        &self,
+        module::HookContext {
+            handlers,
+            futures,
+            settings,
+            injector,
+            global_bus,
+            ..
+        }: module::HookContext<'_>,
+    ) -> Result<()> {
+        let cache: Cache = injector
+            .get()
+            .await
+            .ok_or_else(|| anyhow!("missing cache"))?;
+
+        let speedrun: api::Speedrun = injector
+            .get()
+            .await
+            .ok_or_else(|| anyhow!("missing speedrun api"))?;
+
+        let speedrun = CachedSpeedrun::new(&cache, speedrun)?;
+
+        let settings = settings.scoped("livesplit");
+
+        let enabled = settings.var("enabled", false).await?;
+        let address = settings
+            .var("address", String::from(livesplit::DEFAULT_ADDRESS))
+            .await?;
+
+        let shared = Shared {
+            client: Arc::new(Mutex::new(LiveSplit::new(address.load().await))),
+            address,
+        };
+
+        let runner = settings.optional("runner").await?;
+        let game = settings.optional("game").await?;
+        let category = settings.optional("category").await?;
+
+        for (command, kind) in &[
+            ("splits", Kind::Splits),
+            ("pace", Kind::Pace),
+            ("pb", Kind::PersonalBest),
+        ] {
+            handlers.insert(
+                *command,
+                Handler {
+                    kind: *kind,
+                    enabled: enabled.clone(),
+                    shared: shared.clone(),
+                    speedrun: speedrun.clone(),
+                    runner: runner.clone(),
+                    game: game.clone(),
+                    category: category.clone(),
+                },
+            );
+        }
+
+        let (mut enabled_stream, mut enabled) =
+            settings.stream::<bool>("enabled").or_default().await?;
+        let global_bus = global_bus.clone();
+
+        let future = async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL).fuse();
+            let mut last = None;
+            let mut connected = true;
+
+            loop {
+                futures::select! {
+                    update = enabled_stream.select_next_some() => {
+                        enabled = update;
+                        last = None;
+                    }
+                    _ = interval.select_next_some() => {
+                        if !enabled {
+                            continue;
+                        }
+
+                        match poll(&shared, &*global_bus, &mut last).await {
+                            Ok(()) => {
+                                if !connected {
+                                    log::info!("Connected to LiveSplit Server");
+                                }
+
+                                connected = true;
+                            }
+                            Err(e) => {
+                                // NB: only log once when we lose the connection.
+                                if connected {
+                                    log_error!(e, "Failed to poll LiveSplit Server");
+                                }
+
+                                connected = false;
+                                last = None;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        futures.push(future.boxed());
+        Ok(())
+    }
+}
@@ -186,6 +186,10 @@ impl super::Module for Module {
         "misc"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
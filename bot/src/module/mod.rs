@@ -1,4 +1,5 @@
 use crate::api;
+use crate::bus;
 use crate::command;
 use crate::idle;
 use crate::injector;
@@ -13,17 +14,22 @@ use std::sync::Arc;
 mod macros;
 pub mod admin;
 pub mod after_stream;
+pub mod alerts;
 pub mod alias_admin;
 pub mod auth;
 pub mod clip;
 pub mod command_admin;
 pub mod countdown;
 pub mod eight_ball;
+pub mod game_bridge;
 pub mod gtav;
 pub mod help;
+pub mod livesplit;
 pub mod misc;
 pub mod poll;
+pub mod profiles;
 pub mod promotions;
+pub mod script;
 pub mod song;
 pub mod speedrun;
 pub mod swearjar;
@@ -32,9 +38,17 @@ pub mod time;
 pub mod water;
 pub mod weather;
 
+/// Handlers which are looked up dynamically, like commands registered by
+/// scripts.
+pub trait DynamicHandlers: 'static + Send + Sync {
+    /// Lookup the handler for the given command.
+    fn get(&self, command: &str) -> Option<Arc<dyn command::Handler>>;
+}
+
 #[derive(Default)]
 pub struct Handlers {
     handlers: HashMap<String, Arc<dyn command::Handler>>,
+    dynamic: Vec<Arc<dyn DynamicHandlers>>,
 }
 
 impl Handlers {
@@ -44,9 +58,20 @@ impl Handlers {
             .insert(command.as_ref().to_string(), Arc::new(handler));
     }
 
+    /// Insert handlers which are looked up dynamically.
+    ///
+    /// These are only consulted if no static handler matches the command.
+    pub fn insert_dynamic(&mut self, dynamic: Arc<dyn DynamicHandlers>) {
+        self.dynamic.push(dynamic);
+    }
+
     /// Lookup the given command mutably.
     pub fn get(&self, command: &str) -> Option<Arc<dyn command::Handler>> {
-        self.handlers.get(command).cloned()
+        if let Some(handler) = self.handlers.get(command) {
+            return Some(handler.clone());
+        }
+
+        self.dynamic.iter().find_map(|d| d.get(command))
     }
 }
 
@@ -62,6 +87,11 @@ pub struct HookContext<'a> {
     pub sender: &'a irc::Sender,
     pub settings: &'a settings::Settings,
     pub auth: &'a crate::auth::Auth,
+    pub message_hooks: &'a mut Vec<Box<dyn command::MessageHook>>,
+    pub global_bus: &'a Arc<bus::Bus<bus::Global>>,
+    /// If the module is being set up for the streamer's own channel, as
+    /// opposed to one of the other joined channels.
+    pub primary: bool,
 }
 
 #[async_trait::async_trait]
@@ -72,6 +102,12 @@ where
     /// Type of the module as a string to help with diagnostics.
     fn ty(&self) -> &'static str;
 
+    /// If the module integrates with the streamer's account or some other
+    /// shared resource, it should only be set up for the streamer's channel.
+    fn primary_only(&self) -> bool {
+        false
+    }
+
     /// Set up command handlers for this module.
     async fn hook(&self, _: HookContext<'_>) -> Result<(), anyhow::Error>;
 }
@@ -0,0 +1,84 @@
+use crate::module;
+use crate::prelude::*;
+use crate::profiles;
+use std::time;
+
+pub struct Module;
+
+#[async_trait]
+impl super::Module for Module {
+    fn ty(&self) -> &'static str {
+        "profiles"
+    }
+
+    fn primary_only(&self) -> bool {
+        true
+    }
+
+    /// Set up automatic activation of settings profiles when the game changes.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            futures,
+            settings,
+            stream_info,
+            ..
+        }: module::HookContext<'_>,
+    ) -> Result<(), anyhow::Error> {
+        let game_switch = settings.var("profiles/game-switch", false).await?;
+        let profiles = injector.var::<profiles::Profiles>().await?;
+        let stream_info = stream_info.clone();
+
+        let future = async move {
+            let mut interval = tokio::time::interval(time::Duration::from_secs(30));
+            let mut last = None::<String>;
+
+            loop {
+                interval.tick().await;
+
+                let game = stream_info.data.read().game.clone();
+
+                let game = match game {
+                    Some(game) if last.as_ref() != Some(&game) => game,
+                    _ => continue,
+                };
+
+                // NB: the first game we see is the one we started with.
+                let first = last.is_none();
+                last = Some(game.clone());
+
+                if first || !game_switch.load().await {
+                    continue;
+                }
+
+                let profiles = match profiles.load().await {
+                    Some(profiles) => profiles,
+                    None => continue,
+                };
+
+                let profile = match profiles.for_game(&game).await {
+                    Ok(Some(profile)) => profile,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log_error!(e, "Failed to look up settings profile for game");
+                        continue;
+                    }
+                };
+
+                log::info!(
+                    "Game changed to `{}`, activating settings profile `{}`",
+                    game,
+                    profile.name
+                );
+
+                if let Err(e) = profiles.activate(&profile.name, settings::Actor::Bot).await {
+                    log_error!(e, "Failed to activate settings profile");
+                }
+            }
+        };
+
+        futures.push(future.boxed());
+        Ok(())
+    }
+}
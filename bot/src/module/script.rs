@@ -0,0 +1,33 @@
+use crate::module;
+use crate::prelude::*;
+use crate::script;
+use std::sync::Arc;
+
+pub struct Module;
+
+#[async_trait]
+impl super::Module for Module {
+    fn ty(&self) -> &'static str {
+        "script"
+    }
+
+    /// Set up commands and message hooks registered by scripts.
+    async fn hook(
+        &self,
+        module::HookContext {
+            injector,
+            handlers,
+            message_hooks,
+            ..
+        }: module::HookContext<'_>,
+    ) -> Result<(), anyhow::Error> {
+        let scripts = match injector.get::<script::Scripts>().await {
+            Some(scripts) => scripts,
+            None => return Ok(()),
+        };
+
+        handlers.insert_dynamic(Arc::new(scripts.clone()));
+        message_hooks.push(Box::new(script::Hooks::new(scripts)));
+        Ok(())
+    }
+}
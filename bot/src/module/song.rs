@@ -735,6 +735,10 @@ impl module::Module for Module {
         "song"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
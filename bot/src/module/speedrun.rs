@@ -2,7 +2,7 @@ use crate::api::{
     self,
     speedrun::{
         Category, CategoryType, Embed, Embeds, Game, GameRecord, Level, Page, Players,
-        RelatedPlayer, Run, RunInfo, User, Variable, Variables,
+        RelatedPlayer, Run, RunInfo, RunsFilter, User, Variable, Variables,
     },
 };
 use crate::auth;
@@ -10,9 +10,15 @@ use crate::command;
 use crate::module;
 use crate::prelude::*;
 use crate::storage::Cache;
+use crate::template::Template;
 use crate::utils;
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+
+mod notifier;
+
+pub use self::notifier::RecordKind;
 
 /// Handler for the !speedrun command.
 pub struct Speedrun {
@@ -534,6 +540,10 @@ pub enum Key<'a> {
     GameLevels {
         game_id: &'a str,
     },
+    VerifiedRuns {
+        filter: &'a RunsFilter,
+        max: u32,
+    },
     Leaderboard {
         game_id: &'a str,
         category_id: &'a str,
@@ -543,13 +553,22 @@ pub enum Key<'a> {
     },
 }
 
+/// speedrun.com client which caches responses.
 #[derive(Clone)]
-struct CachedSpeedrun {
+pub(crate) struct CachedSpeedrun {
     cache: Cache,
     speedrun: api::Speedrun,
 }
 
 impl CachedSpeedrun {
+    /// Construct a new cached client.
+    pub(crate) fn new(cache: &Cache, speedrun: api::Speedrun) -> Result<Self> {
+        Ok(Self {
+            cache: cache.namespaced(&"speedrun")?,
+            speedrun,
+        })
+    }
+
     /// Get cached user information by ID.
     pub async fn user_by_id(&self, user: &str) -> Result<Option<User>> {
         let result = self
@@ -664,6 +683,24 @@ impl CachedSpeedrun {
         Ok(result)
     }
 
+    /// Get the most recently verified runs matching the given filter.
+    pub async fn verified_runs(
+        &self,
+        filter: &RunsFilter,
+        max: u32,
+    ) -> Result<Option<Vec<RunInfo>>> {
+        let result = self
+            .cache
+            .wrap(
+                Key::VerifiedRuns { filter, max },
+                chrono::Duration::minutes(5),
+                self.speedrun.verified_runs(filter, max),
+            )
+            .await?;
+
+        Ok(result)
+    }
+
     /// Get the specified leaderboard.
     pub async fn leaderboard(
         &self,
@@ -706,8 +743,12 @@ impl super::Module for Module {
         &self,
         module::HookContext {
             handlers,
+            futures,
+            sender,
             settings,
             injector,
+            global_bus,
+            primary,
             ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
@@ -721,20 +762,84 @@ impl super::Module for Module {
             .await
             .ok_or_else(|| anyhow!("missing speedrun api"))?;
 
-        let speedrun = CachedSpeedrun {
-            cache: cache.namespaced(&"speedrun")?,
-            speedrun,
-        };
+        let speedrun = CachedSpeedrun::new(&cache, speedrun)?;
 
         handlers.insert(
             "speedrun",
             Speedrun {
-                speedrun,
+                speedrun: speedrun.clone(),
                 enabled: settings.var("speedrun/enabled", false).await?,
                 top: settings.var("speedrun/top", 20).await?,
             },
         );
 
+        // NB: announcements are only made in the streamer's own channel.
+        if !primary {
+            return Ok(());
+        }
+
+        let settings = settings.scoped("speedrun/notify");
+
+        let (mut enabled_stream, mut enabled) = settings.stream("enabled").or_default().await?;
+
+        let (mut interval_stream, interval) = settings
+            .stream("interval")
+            .or_with(utils::Duration::seconds(10 * 60))
+            .await?;
+
+        let default_pb_template = Template::compile(
+            "{{runner}} got a new personal best in {{game}} {{category}}: {{time}} (#{{place}}) {{weblink}}",
+        )?;
+
+        let default_wr_template = Template::compile(
+            "{{runner}} set a new world record in {{game}} {{category}}: {{time}}! {{weblink}}",
+        )?;
+
+        let mut notifier = notifier::Notifier {
+            speedrun,
+            sender: sender.clone(),
+            global_bus: global_bus.clone(),
+            runners: settings.var("runners", Vec::new()).await?,
+            games: settings.var("games", Vec::new()).await?,
+            pb_template: settings.var("pb-template", default_pb_template).await?,
+            wr_template: settings.var("wr-template", default_wr_template).await?,
+            overlay_duration: settings
+                .var("overlay-duration", utils::Duration::seconds(10))
+                .await?,
+            since: Utc::now(),
+            seen: HashSet::new(),
+        };
+
+        let future = async move {
+            let mut interval = tokio::time::interval(interval.as_std()).fuse();
+
+            loop {
+                futures::select! {
+                    update = enabled_stream.select_next_some() => {
+                        // NB: only announce runs verified after we were enabled.
+                        if update && !enabled {
+                            notifier.since = Utc::now();
+                        }
+
+                        enabled = update;
+                    }
+                    update = interval_stream.select_next_some() => {
+                        interval = tokio::time::interval(update.as_std()).fuse();
+                    }
+                    _ = interval.select_next_some() => {
+                        if !enabled {
+                            continue;
+                        }
+
+                        if let Err(e) = notifier.poll().await {
+                            log_error!(e, "Failed to poll speedrun.com for new runs");
+                        }
+                    }
+                }
+            }
+        };
+
+        futures.push(future.boxed());
         Ok(())
     }
 }
@@ -0,0 +1,236 @@
+//! Background polling of speedrun.com for new personal bests and world records.
+
+use super::{match_levels, CachedSpeedrun, Speedrun, SubCategory};
+use crate::api::speedrun::{Embed, Embeds, RelatedPlayer, RunInfo, RunsFilter};
+use crate::bus;
+use crate::irc;
+use crate::prelude::*;
+use crate::template::Template;
+use crate::utils;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// How many of the most recently verified runs to fetch for each runner or
+/// game.
+const RECENT_RUNS: u32 = 20;
+
+/// The kind of record which is being announced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordKind {
+    PersonalBest,
+    WorldRecord,
+}
+
+/// Variables available to the announcement templates.
+#[derive(Debug, Clone, serde::Serialize)]
+struct Record {
+    runner: String,
+    game: String,
+    category: String,
+    time: String,
+    place: u32,
+    weblink: String,
+}
+
+/// Polls configured runners and games for newly verified runs.
+pub struct Notifier {
+    pub(super) speedrun: CachedSpeedrun,
+    pub(super) sender: irc::Sender,
+    pub(super) global_bus: Arc<bus::Bus<bus::Global>>,
+    pub(super) runners: settings::Var<Vec<String>>,
+    pub(super) games: settings::Var<Vec<String>>,
+    pub(super) pb_template: settings::Var<Template>,
+    pub(super) wr_template: settings::Var<Template>,
+    pub(super) overlay_duration: settings::Var<utils::Duration>,
+    /// Only runs verified after this point in time are announced.
+    pub(super) since: DateTime<Utc>,
+    /// Runs which have already been looked at.
+    pub(super) seen: HashSet<String>,
+}
+
+impl Notifier {
+    /// Poll all configured runners and games once.
+    pub async fn poll(&mut self) -> Result<()> {
+        let mut filters = Vec::new();
+
+        for runner in self.runners.load().await {
+            match self.speedrun.user_by_id(&runner).await? {
+                Some(user) => filters.push((RunsFilter::User(user.id), true)),
+                None => log::warn!("No user on speedrun.com named `{}`", runner),
+            }
+        }
+
+        for game in self.games.load().await {
+            match self.speedrun.game_by_id(&game).await? {
+                Some(game) => filters.push((RunsFilter::Game(game.id), false)),
+                None => log::warn!("No game on speedrun.com matching `{}`", game),
+            }
+        }
+
+        for (filter, personal_bests) in filters {
+            let runs = match self.speedrun.verified_runs(&filter, RECENT_RUNS).await? {
+                Some(runs) => runs,
+                None => continue,
+            };
+
+            for run in runs {
+                if self.seen.contains(&run.id) {
+                    continue;
+                }
+
+                match run.status.verify_date {
+                    Some(verify_date) if verify_date > self.since => (),
+                    _ => continue,
+                }
+
+                // NB: runs which fail to be announced are tried again on the
+                // next poll.
+                if let Err(e) = self.announce(&run, personal_bests).await {
+                    log_error!(e, "Failed to announce speedrun.com run `{}`", run.id);
+                    continue;
+                }
+
+                self.seen.insert(run.id.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Announce the given run if it's a personal best or a world record.
+    ///
+    /// Personal bests are only announced if `personal_bests` is set, which is
+    /// the case for runs by configured runners.
+    async fn announce(&self, run: &RunInfo, personal_bests: bool) -> Result<()> {
+        let player = run.players.iter().find_map(|p| match p {
+            RelatedPlayer::Player(player) => Some(player),
+            RelatedPlayer::Guest(..) => None,
+        });
+
+        // NB: guests don't have personal bests we can look up.
+        let player = match player {
+            Some(player) => player,
+            None => return Ok(()),
+        };
+
+        // NB: this is deliberately not cached, since we need to know where
+        // the newly verified run placed.
+        let place = self
+            .speedrun
+            .speedrun
+            .user_personal_bests(&player.id, &Embeds::default())
+            .await?
+            .into_iter()
+            .flatten()
+            .find(|pb| pb.run.id == run.id)
+            .map(|pb| pb.place);
+
+        // Not a personal best, so it must've been superseded already.
+        let place = match place {
+            Some(place) => place,
+            None => return Ok(()),
+        };
+
+        let kind = match place {
+            1 => RecordKind::WorldRecord,
+            _ if personal_bests => RecordKind::PersonalBest,
+            _ => return Ok(()),
+        };
+
+        let mut runners = Vec::new();
+
+        for player in &run.players {
+            let name = Speedrun::player_name(&self.speedrun, player, None, &HashMap::new()).await?;
+            runners.extend(name);
+        }
+
+        let game = match self.speedrun.game_by_id(&run.game).await? {
+            Some(game) => game.names.name().to_string(),
+            None => run.game.clone(),
+        };
+
+        let record = Record {
+            runner: utils::human_list(&runners).unwrap_or_else(|| String::from("*none*")),
+            game,
+            category: self.category_name(run).await?,
+            time: utils::compact_duration(run.times.primary.as_std()),
+            place,
+            weblink: run.weblink.clone(),
+        };
+
+        let template = match kind {
+            RecordKind::PersonalBest => self.pb_template.load().await,
+            RecordKind::WorldRecord => self.wr_template.load().await,
+        };
+
+        let text = template.render_to_string(&record)?;
+        self.sender.privmsg(&text).await;
+
+        let Record {
+            runner,
+            game,
+            category,
+            time,
+            place,
+            weblink,
+        } = record;
+
+        self.global_bus
+            .send(bus::Global::SpeedrunRecord {
+                kind,
+                text,
+                runner,
+                game,
+                category,
+                time,
+                place,
+                weblink,
+                duration: self.overlay_duration.load().await.as_std().as_secs(),
+            })
+            .await;
+
+        Ok(())
+    }
+
+    /// Build a human readable name of the category of the run, including its
+    /// sub category and level.
+    async fn category_name(&self, run: &RunInfo) -> Result<String> {
+        let mut embeds = Embeds::default();
+        embeds.push(Embed::Variables);
+
+        let categories = self
+            .speedrun
+            .game_categories_by_id(&run.game, &embeds)
+            .await?;
+
+        let category = categories
+            .into_iter()
+            .flatten()
+            .find(|c| c.id == run.category);
+
+        let category = match category {
+            Some(category) => category,
+            None => return Ok(run.category.clone()),
+        };
+
+        let mut name = category.name.clone();
+
+        if let Some(variables) = &category.variables {
+            let sub_categories = SubCategory::from_variables(&variables.data);
+
+            if let Some(c) = SubCategory::match_run(run, &sub_categories) {
+                name = format!("{} ({})", name, c.label);
+            }
+        }
+
+        if let Some(levels) = self.speedrun.game_levels(&run.game).await? {
+            if let Some(level) = match_levels(run.level.as_ref(), &levels) {
+                name = format!("{} ({})", level.name, name);
+            }
+        }
+
+        Ok(name)
+    }
+}
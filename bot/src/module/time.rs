@@ -1,39 +1,70 @@
 use crate::auth;
 use crate::command;
+use crate::db;
 use crate::module;
 use crate::prelude::*;
+use crate::schedule;
 use crate::template::Template;
+use crate::utils;
 use anyhow::Result;
 use chrono::prelude::*;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use chrono_tz::{Etc, Tz};
+use std::time;
 
-/// Handler for the !8ball command.
+/// Regions to look for cities in, like `Europe/Stockholm`.
+const REGIONS: &[&str] = &[
+    "Europe",
+    "America",
+    "Asia",
+    "Australia",
+    "Africa",
+    "Pacific",
+    "Atlantic",
+    "Indian",
+    "Antarctica",
+];
+
+/// Common cities which aren't named by a time zone.
+const CITIES: &[(&str, Tz)] = &[
+    ("atlanta", Tz::America__New_York),
+    ("austin", Tz::America__Chicago),
+    ("barcelona", Tz::Europe__Madrid),
+    ("beijing", Tz::Asia__Shanghai),
+    ("boston", Tz::America__New_York),
+    ("dallas", Tz::America__Chicago),
+    ("delhi", Tz::Asia__Kolkata),
+    ("frankfurt", Tz::Europe__Berlin),
+    ("gothenburg", Tz::Europe__Stockholm),
+    ("hamburg", Tz::Europe__Berlin),
+    ("houston", Tz::America__Chicago),
+    ("las vegas", Tz::America__Los_Angeles),
+    ("miami", Tz::America__New_York),
+    ("milan", Tz::Europe__Rome),
+    ("montreal", Tz::America__Toronto),
+    ("mumbai", Tz::Asia__Kolkata),
+    ("munich", Tz::Europe__Berlin),
+    ("osaka", Tz::Asia__Tokyo),
+    ("san francisco", Tz::America__Los_Angeles),
+    ("seattle", Tz::America__Los_Angeles),
+    ("washington", Tz::America__New_York),
+];
+
+/// Handler for the !time command.
 pub struct Time {
     enabled: settings::Var<bool>,
     timezone: settings::Var<Tz>,
     template: settings::Var<Template>,
+    user_time_zones: injector::Var<Option<db::UserTimeZones>>,
 }
 
-#[async_trait]
-impl command::Handler for Time {
-    fn scope(&self) -> Option<auth::Scope> {
-        Some(auth::Scope::Time)
-    }
-
-    async fn handle(&self, ctx: &mut command::Context) -> Result<()> {
-        if !self.enabled.load().await {
-            return Ok(());
-        }
-
+impl Time {
+    /// Respond with the streamer's time.
+    async fn streamer(&self, ctx: &mut command::Context) -> Result<()> {
         let tz = self.timezone.load().await;
-        let now = Utc::now();
-
-        let offset = tz.offset_from_utc_datetime(&now.naive_utc());
-        let offset = offset.fix().local_minus_utc();
-        let offset = format_time_zone(offset);
+        let now = Utc::now().with_timezone(&tz);
 
-        let now = now.with_timezone(&tz);
+        let offset = format_time_zone(now.offset().fix().local_minus_utc());
 
         let time = now.time();
         let time = format!(
@@ -66,26 +97,291 @@ impl command::Handler for Time {
             time: &'a str,
             rfc2822: &'a str,
         }
+    }
+
+    /// Respond with the time in the given city or time zone.
+    async fn place(&self, ctx: &mut command::Context, place: &str) -> Result<()> {
+        let tz = match parse_time_zone(place) {
+            Some(tz) => tz,
+            None => {
+                respond!(
+                    ctx,
+                    "I don't know the time zone or city `{}`, try something like `Europe/Stockholm`",
+                    place
+                );
+                return Ok(());
+            }
+        };
+
+        respond!(
+            ctx,
+            "The time in {} is {}",
+            tz.name(),
+            format_time(&Utc::now().with_timezone(&tz))
+        );
+        Ok(())
+    }
+
+    /// Respond with how long it is until the given time in the streamer's
+    /// time zone.
+    async fn until(&self, ctx: &mut command::Context) -> Result<()> {
+        let time = match ctx.next().and_then(|t| parse_time(&t)) {
+            Some(time) => time,
+            None => {
+                respond!(ctx, "Expected: !time until <HH:MM>");
+                return Ok(());
+            }
+        };
+
+        let tz = self.timezone.load().await;
+        let now = Utc::now();
+
+        let at = match schedule::next_time(now, tz, time) {
+            Some(at) => at.with_timezone(&tz),
+            None => {
+                respond!(ctx, "That time doesn't exist today");
+                return Ok(());
+            }
+        };
+
+        let remaining = format_remaining(at.with_timezone(&Utc) - now);
+
+        let mut response = format!(
+            "{} in the streamer's time zone is in {}",
+            format_time(&at),
+            remaining
+        );
+
+        let user = ctx.user.real().map(|user| user.name().to_string());
+
+        let user_tz = match user {
+            Some(user) => self.user_time_zone(ctx.channel(), &user).await?,
+            None => None,
+        };
+
+        if let Some(user_tz) = user_tz {
+            if user_tz != tz {
+                response.push_str(&format!(
+                    ", which is {} for you",
+                    format_time(&at.with_timezone(&user_tz))
+                ));
+            }
+        }
+
+        respond!(ctx, response);
+        Ok(())
+    }
+
+    /// Set the time zone of the current user.
+    async fn set(&self, ctx: &mut command::Context) -> Result<()> {
+        let user_time_zones = match self.user_time_zones.load().await {
+            Some(user_time_zones) => user_time_zones,
+            None => {
+                respond!(ctx, "Time zones are not available right now");
+                return Ok(());
+            }
+        };
+
+        let user = match ctx.user.real() {
+            Some(user) => user.name().to_string(),
+            None => {
+                respond!(ctx, "Only real users can set a time zone");
+                return Ok(());
+            }
+        };
 
-        /// Format the given offset as a timezone offset.
-        fn format_time_zone(mut offset: i32) -> String {
-            let mut neg = false;
+        let place = ctx.rest().trim().to_string();
 
-            if offset < 0 {
-                offset = -offset;
-                neg = true;
+        if place.is_empty() {
+            respond!(ctx, "Expected: !time set <city or time zone>");
+            return Ok(());
+        }
+
+        let tz = match parse_time_zone(&place) {
+            Some(tz) => tz,
+            None => {
+                respond!(
+                    ctx,
+                    "I don't know the time zone or city `{}`, try something like `Europe/Stockholm`",
+                    place
+                );
+                return Ok(());
             }
+        };
+
+        user_time_zones.set(ctx.channel(), &user, tz.name()).await?;
 
-            let minutes = (offset % 3600) / 60;
-            let hours = offset / 3600;
+        respond!(
+            ctx,
+            "Your time zone is now {}, where it's {}",
+            tz.name(),
+            format_time(&Utc::now().with_timezone(&tz))
+        );
+        Ok(())
+    }
 
-            if neg {
-                format!("-{:02}{:02}", hours, minutes)
-            } else {
-                format!("+{:02}{:02}", hours, minutes)
+    /// Clear the time zone of the current user.
+    async fn clear(&self, ctx: &mut command::Context) -> Result<()> {
+        let user_time_zones = match self.user_time_zones.load().await {
+            Some(user_time_zones) => user_time_zones,
+            None => {
+                respond!(ctx, "Time zones are not available right now");
+                return Ok(());
             }
+        };
+
+        let user = match ctx.user.real() {
+            Some(user) => user.name().to_string(),
+            None => return Ok(()),
+        };
+
+        if user_time_zones.clear(ctx.channel(), &user).await? {
+            respond!(ctx, "Cleared your time zone");
+        } else {
+            respond!(ctx, "You don't have a time zone set");
+        }
+
+        Ok(())
+    }
+
+    /// Look up the stored time zone of the given user.
+    async fn user_time_zone(&self, channel: &str, user: &str) -> Result<Option<Tz>> {
+        let user_time_zones = match self.user_time_zones.load().await {
+            Some(user_time_zones) => user_time_zones,
+            None => return Ok(None),
+        };
+
+        let tz = user_time_zones.get(channel, user).await?;
+        Ok(tz.and_then(|tz| str::parse::<Tz>(&tz).ok()))
+    }
+}
+
+#[async_trait]
+impl command::Handler for Time {
+    fn scope(&self) -> Option<auth::Scope> {
+        Some(auth::Scope::Time)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context) -> Result<()> {
+        if !self.enabled.load().await {
+            return Ok(());
+        }
+
+        let rest = ctx.rest().trim().to_string();
+
+        match ctx.next().as_deref() {
+            None => self.streamer(ctx).await,
+            Some("until") => self.until(ctx).await,
+            Some("set") => self.set(ctx).await,
+            Some("clear") => self.clear(ctx).await,
+            Some(_) => self.place(ctx, &rest).await,
+        }
+    }
+}
+
+/// Resolve a time zone, like `Europe/Stockholm`, or a city, like `new york`.
+pub fn parse_time_zone(s: &str) -> Option<Tz> {
+    let s = s.trim();
+
+    if s.is_empty() {
+        return None;
+    }
+
+    if let Ok(tz) = str::parse::<Tz>(s) {
+        return Some(tz);
+    }
+
+    // NB: abbreviations like `utc` and `cet`.
+    if let Ok(tz) = str::parse::<Tz>(&s.to_uppercase()) {
+        return Some(tz);
+    }
+
+    let lower = s.to_lowercase();
+    let lower = lower.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    for (city, tz) in CITIES {
+        if *city == lower {
+            return Some(*tz);
+        }
+    }
+
+    // NB: time zones are title cased and separate words with underscores.
+    let name = s
+        .split('/')
+        .map(|part| {
+            part.split(|c: char| c.is_whitespace() || c == '_')
+                .filter(|w| !w.is_empty())
+                .map(title_case)
+                .collect::<Vec<_>>()
+                .join("_")
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if let Ok(tz) = str::parse::<Tz>(&name) {
+        return Some(tz);
+    }
+
+    for region in REGIONS {
+        if let Ok(tz) = str::parse::<Tz>(&format!("{}/{}", region, name)) {
+            return Some(tz);
         }
     }
+
+    None
+}
+
+/// Upper case the first character of the word and lower case the rest.
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(c) => c
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Parse a time of day, like `18:30`.
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+/// Format the remaining time with minute precision.
+fn format_remaining(remaining: Duration) -> String {
+    let minutes = (remaining.num_seconds() + 59) / 60;
+    utils::compact_duration(time::Duration::from_secs(minutes.max(0) as u64 * 60))
+}
+
+/// Format a time of day together with its offset.
+fn format_time(at: &DateTime<Tz>) -> String {
+    format!(
+        "{:02}:{:02} ({})",
+        at.hour(),
+        at.minute(),
+        format_time_zone(at.offset().fix().local_minus_utc())
+    )
+}
+
+/// Format the given offset as a timezone offset.
+fn format_time_zone(mut offset: i32) -> String {
+    let mut neg = false;
+
+    if offset < 0 {
+        offset = -offset;
+        neg = true;
+    }
+
+    let minutes = (offset % 3600) / 60;
+    let hours = offset / 3600;
+
+    if neg {
+        format!("-{:02}{:02}", hours, minutes)
+    } else {
+        format!("+{:02}{:02}", hours, minutes)
+    }
 }
 
 pub struct Module;
@@ -100,7 +396,10 @@ impl super::Module for Module {
     async fn hook(
         &self,
         module::HookContext {
-            handlers, settings, ..
+            handlers,
+            settings,
+            injector,
+            ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
         let default_template = Template::compile("The streamer's time is {{time}}{{offset}}")?;
@@ -111,9 +410,45 @@ impl super::Module for Module {
                 enabled: settings.var("time/enabled", true).await?,
                 timezone: settings.var("time/timezone", Etc::UTC).await?,
                 template: settings.var("time/template", default_template).await?,
+                user_time_zones: injector.var().await?,
             },
         );
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_time, parse_time_zone};
+    use chrono::prelude::*;
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_parse_time_zone() {
+        assert_eq!(
+            Some(Tz::Europe__Stockholm),
+            parse_time_zone("Europe/Stockholm")
+        );
+        assert_eq!(
+            Some(Tz::Europe__Stockholm),
+            parse_time_zone("europe/stockholm")
+        );
+        assert_eq!(Some(Tz::Europe__Stockholm), parse_time_zone("stockholm"));
+        assert_eq!(Some(Tz::America__New_York), parse_time_zone("new york"));
+        assert_eq!(
+            Some(Tz::America__Los_Angeles),
+            parse_time_zone("San Francisco")
+        );
+        assert_eq!(Some(Tz::UTC), parse_time_zone("utc"));
+        assert_eq!(None, parse_time_zone("atlantis"));
+        assert_eq!(None, parse_time_zone(""));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(Some(NaiveTime::from_hms(18, 30, 0)), parse_time("18:30"));
+        assert_eq!(Some(NaiveTime::from_hms(9, 0, 0)), parse_time(" 09:00 "));
+        assert_eq!(None, parse_time("25:00"));
+    }
+}
@@ -1,14 +1,162 @@
 use crate::auth;
+use crate::bus;
 use crate::command;
 use crate::currency::Currency;
+use crate::irc;
 use crate::module;
 use crate::prelude::*;
 use crate::stream_info;
+use crate::template::Template;
 use crate::utils;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::time;
 use tokio::sync::Mutex;
 
+/// How often to check if a reminder is due.
+const REMINDER_INTERVAL: time::Duration = time::Duration::from_secs(10);
+
+/// The kind of a break reminder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReminderKind {
+    Water,
+    Stretch,
+    Eyes,
+}
+
+impl ReminderKind {
+    /// The settings key of the reminder.
+    fn key(self) -> &'static str {
+        match self {
+            ReminderKind::Water => "water",
+            ReminderKind::Stretch => "stretch",
+            ReminderKind::Eyes => "eyes",
+        }
+    }
+
+    /// The default interval of the reminder.
+    fn default_interval(self) -> utils::Duration {
+        match self {
+            ReminderKind::Water => utils::Duration::seconds(30 * 60),
+            ReminderKind::Stretch => utils::Duration::hours(1),
+            ReminderKind::Eyes => utils::Duration::seconds(20 * 60),
+        }
+    }
+
+    /// The default message of the reminder.
+    fn default_template(self) -> &'static str {
+        match self {
+            ReminderKind::Water => "{{streamer}}, time to drink some water!",
+            ReminderKind::Stretch => {
+                "{{streamer}}, you've been live for {{elapsed}}, time to stand up and stretch!"
+            }
+            ReminderKind::Eyes => "{{streamer}}, rest your eyes and look at something far away!",
+        }
+    }
+}
+
+/// A single reminder, like the one to drink water.
+struct Reminder {
+    kind: ReminderKind,
+    enabled: settings::Var<bool>,
+    interval: settings::Var<utils::Duration>,
+    template: settings::Var<Template>,
+    /// When the reminder was last given, or reset.
+    last: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+/// Gives reminders in chat and on the overlay on intervals measured from when
+/// the stream started.
+struct Reminders {
+    reminders: Vec<Reminder>,
+    stream_info: stream_info::StreamInfo,
+    sender: irc::Sender,
+    global_bus: Arc<bus::Bus<bus::Global>>,
+    overlay_duration: settings::Var<utils::Duration>,
+}
+
+impl Reminders {
+    /// Give all reminders which are due.
+    async fn tick(&self) -> Result<()> {
+        let started_at = self
+            .stream_info
+            .data
+            .read()
+            .stream
+            .as_ref()
+            .map(|s| s.started_at);
+
+        // NB: reminders are paused while the stream is offline.
+        let started_at = match started_at {
+            Some(started_at) => started_at,
+            None => return Ok(()),
+        };
+
+        let now = Utc::now();
+
+        for reminder in &self.reminders {
+            if !reminder.enabled.load().await {
+                continue;
+            }
+
+            let mut last = reminder.last.lock().await;
+            let interval = reminder.interval.load().await.as_chrono();
+
+            if !is_due(*last, started_at, now, interval) {
+                continue;
+            }
+
+            *last = Some(now);
+            drop(last);
+
+            let elapsed = (now - started_at).to_std().unwrap_or_default();
+            // NB: only show whole minutes.
+            let elapsed = time::Duration::from_secs(elapsed.as_secs() / 60 * 60);
+
+            let text = reminder.template.load().await.render_to_string(&Data {
+                streamer: &self.stream_info.user.display_name,
+                elapsed: &utils::compact_duration(elapsed),
+            })?;
+
+            self.sender.privmsg(&text).await;
+
+            self.global_bus
+                .send(bus::Global::Reminder {
+                    kind: reminder.kind,
+                    text,
+                    duration: self.overlay_duration.load().await.num_seconds(),
+                })
+                .await;
+        }
+
+        return Ok(());
+
+        #[derive(serde::Serialize)]
+        struct Data<'a> {
+            streamer: &'a str,
+            elapsed: &'a str,
+        }
+    }
+}
+
+/// Test if a reminder which was last given, or reset, at `last` is due `now`
+/// for a stream which started at `started_at`.
+fn is_due(
+    last: Option<DateTime<Utc>>,
+    started_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    interval: chrono::Duration,
+) -> bool {
+    // NB: a new stream restarts the interval.
+    let since = match last {
+        Some(last) if last > started_at => last,
+        _ => started_at,
+    };
+
+    now - since >= interval
+}
+
 #[derive(Clone)]
 pub struct Reward {
     user: String,
@@ -22,6 +170,8 @@ pub struct Handler {
     waters: Mutex<Vec<(DateTime<Utc>, Option<Reward>)>>,
     stream_info: stream_info::StreamInfo,
     reward_multiplier: settings::Var<u32>,
+    /// When the water reminder was last given, reset by `!water`.
+    water_reminder: Arc<Mutex<Option<DateTime<Utc>>>>,
 }
 
 impl Handler {
@@ -129,6 +279,8 @@ impl command::Handler for Handler {
                     }),
                 ));
 
+                *self.water_reminder.lock().await = Some(now);
+
                 respond!(
                     ctx,
                     "{streamer}, DRINK SOME WATER! {user} has been rewarded {amount} {currency} for the reminder.",
@@ -162,14 +314,21 @@ impl super::Module for Module {
         "water"
     }
 
+    fn primary_only(&self) -> bool {
+        true
+    }
+
     /// Set up command handlers for this module.
     async fn hook(
         &self,
         module::HookContext {
             handlers,
+            futures,
             stream_info,
+            sender,
             settings,
             injector,
+            global_bus,
             ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
@@ -182,6 +341,41 @@ impl super::Module for Module {
             .await?;
         let reward_multiplier = settings.var("water/reward%", 100).await?;
 
+        let reminder_settings = settings.scoped("water/reminders");
+        let water_reminder = Arc::new(Mutex::new(None));
+        let mut reminders = Vec::new();
+
+        for kind in &[
+            ReminderKind::Water,
+            ReminderKind::Stretch,
+            ReminderKind::Eyes,
+        ] {
+            let settings = reminder_settings.scoped(kind.key());
+
+            reminders.push(Reminder {
+                kind: *kind,
+                enabled: settings.var("enabled", false).await?,
+                interval: settings.var("interval", kind.default_interval()).await?,
+                template: settings
+                    .var("template", Template::compile(kind.default_template())?)
+                    .await?,
+                last: match kind {
+                    ReminderKind::Water => water_reminder.clone(),
+                    _ => Arc::new(Mutex::new(None)),
+                },
+            });
+        }
+
+        let reminders = Reminders {
+            reminders,
+            stream_info: stream_info.clone(),
+            sender: sender.clone(),
+            global_bus: global_bus.clone(),
+            overlay_duration: reminder_settings
+                .var("overlay-duration", utils::Duration::seconds(10))
+                .await?,
+        };
+
         handlers.insert(
             "water",
             Handler {
@@ -191,9 +385,50 @@ impl super::Module for Module {
                 waters: Mutex::new(Vec::new()),
                 stream_info: stream_info.clone(),
                 reward_multiplier,
+                water_reminder,
             },
         );
 
+        let future = async move {
+            let mut interval = tokio::time::interval(REMINDER_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = reminders.tick().await {
+                    log_error!(e, "failed to give reminders");
+                }
+            }
+        };
+
+        futures.push(future.boxed());
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_due;
+    use chrono::{Duration, TimeZone as _, Utc};
+
+    #[test]
+    fn test_is_due() {
+        let started_at = Utc.ymd(2020, 3, 1).and_hms(12, 0, 0);
+        let interval = Duration::minutes(30);
+        let at = |minutes| started_at + Duration::minutes(minutes);
+
+        // measured from when the stream started.
+        assert!(!is_due(None, started_at, at(29), interval));
+        assert!(is_due(None, started_at, at(30), interval));
+
+        // measured from the last reminder, or `!water`.
+        assert!(!is_due(Some(at(30)), started_at, at(59), interval));
+        assert!(is_due(Some(at(30)), started_at, at(60), interval));
+        assert!(!is_due(Some(at(45)), started_at, at(60), interval));
+
+        // reminders from a previous stream are ignored.
+        let previous = started_at - Duration::minutes(5);
+        assert!(!is_due(Some(previous), started_at, at(20), interval));
+        assert!(is_due(Some(previous), started_at, at(30), interval));
+    }
+}
@@ -1,9 +1,14 @@
-use crate::api::OpenWeatherMap;
+use crate::api::{
+    open_weather_map::{self, Current, Forecast},
+    OpenWeatherMap,
+};
 use crate::auth;
 use crate::command;
 use crate::module;
 use crate::prelude::*;
-use anyhow::Result;
+use crate::storage::Cache;
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
 use uom::si::{
     f32::ThermodynamicTemperature,
     thermodynamic_temperature::{degree_celsius, degree_fahrenheit, kelvin},
@@ -41,14 +46,225 @@ impl TemperatureUnit {
     }
 }
 
+/// How many days `!weather forecast` covers, including today.
+const FORECAST_DAYS: usize = 3;
+
+/// A named location, like `home`, set by moderators.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct LocationAlias {
+    name: String,
+    location: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "method")]
+enum Key<'a> {
+    Current { location: &'a str },
+    Forecast { location: &'a str },
+}
+
+/// OpenWeatherMap with responses cached per location.
+struct CachedOpenWeatherMap {
+    cache: Cache,
+    api: OpenWeatherMap,
+}
+
+impl CachedOpenWeatherMap {
+    /// Get the cached current weather of a location.
+    async fn current(&self, location: &str) -> Result<Option<Current>> {
+        let key = location.to_lowercase();
+
+        let result = self
+            .cache
+            .wrap(
+                Key::Current { location: &key },
+                chrono::Duration::minutes(10),
+                self.api.current(location.to_string()),
+            )
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Get the cached forecast of a location.
+    async fn forecast(&self, location: &str) -> Result<Option<Forecast>> {
+        let key = location.to_lowercase();
+
+        let result = self
+            .cache
+            .wrap(
+                Key::Forecast { location: &key },
+                chrono::Duration::hours(1),
+                self.api.forecast(location.to_string()),
+            )
+            .await?;
+
+        Ok(result)
+    }
+}
+
 /// Handler for the !weather command.
 pub struct Weather {
     enabled: settings::Var<bool>,
     temperature_unit: settings::Var<TemperatureUnit>,
     location: settings::Var<Option<String>>,
+    aliases: settings::Var<Vec<LocationAlias>>,
+    settings: settings::Settings,
+    cache: Cache,
     api: injector::Var<Option<OpenWeatherMap>>,
 }
 
+impl Weather {
+    /// Access the API, if it's configured.
+    async fn api(&self) -> Result<CachedOpenWeatherMap> {
+        let api = self
+            .api
+            .read()
+            .await
+            .as_ref()
+            .ok_or_else(|| respond_err!("API not configured"))?
+            .clone();
+
+        Ok(CachedOpenWeatherMap {
+            cache: self.cache.clone(),
+            api,
+        })
+    }
+
+    /// Resolve the location to look up, falling back to the default location.
+    async fn resolve(&self, location: &str) -> Option<String> {
+        let location = match location.trim() {
+            "" => self.location.load().await?,
+            location => location.to_string(),
+        };
+
+        Some(self.alias(&location).await.unwrap_or(location))
+    }
+
+    /// Look up the location of an alias.
+    async fn alias(&self, name: &str) -> Option<String> {
+        self.aliases
+            .load()
+            .await
+            .into_iter()
+            .find(|a| a.name.eq_ignore_ascii_case(name))
+            .map(|a| a.location)
+    }
+
+    /// Respond with the current weather.
+    async fn current(&self, ctx: &mut command::Context, loc: Option<String>) -> Result<()> {
+        let api = self.api().await?;
+
+        let loc = match loc {
+            Some(loc) => loc,
+            None => {
+                respond!(ctx, "Must specify <location>");
+                return Ok(());
+            }
+        };
+
+        let current = match api.current(&loc).await? {
+            Some(current) => current,
+            None => {
+                respond!(ctx, "Could not find location `{}`", loc);
+                return Ok(());
+            }
+        };
+
+        let temperature_unit = self.temperature_unit.load().await;
+        respond!(ctx, render_current(&current, temperature_unit));
+        Ok(())
+    }
+
+    /// Respond with the forecast.
+    async fn forecast(&self, ctx: &mut command::Context, loc: Option<String>) -> Result<()> {
+        let api = self.api().await?;
+
+        let loc = match loc {
+            Some(loc) => loc,
+            None => {
+                respond!(ctx, "Must specify <location>");
+                return Ok(());
+            }
+        };
+
+        let forecast = match api.forecast(&loc).await? {
+            Some(forecast) => forecast,
+            None => {
+                respond!(ctx, "Could not find location `{}`", loc);
+                return Ok(());
+            }
+        };
+
+        let temperature_unit = self.temperature_unit.load().await;
+        respond!(ctx, render_forecast(&forecast, temperature_unit));
+        Ok(())
+    }
+
+    /// Handle the `!weather alias` command.
+    async fn alias_command(&self, ctx: &mut command::Context) -> Result<()> {
+        match ctx.next().as_deref() {
+            Some("list") => {
+                let aliases = self
+                    .aliases
+                    .load()
+                    .await
+                    .into_iter()
+                    .map(|a| format!("{} -> {}", a.name, a.location));
+
+                ctx.respond_lines(aliases, "No location aliases").await;
+            }
+            Some("set") => {
+                ctx.check_scope(auth::Scope::WeatherAlias).await?;
+
+                let name = ctx.next_str("<name> <location>")?;
+                let location = ctx.rest().trim().to_string();
+
+                if location.is_empty() {
+                    respond!(ctx, "Expected: <name> <location>");
+                    return Ok(());
+                }
+
+                let mut aliases = self.aliases.load().await;
+                aliases.retain(|a| !a.name.eq_ignore_ascii_case(&name));
+                aliases.push(LocationAlias {
+                    name: name.clone(),
+                    location: location.clone(),
+                });
+
+                self.settings
+                    .set_by("aliases", aliases, ctx.actor())
+                    .await?;
+                respond!(ctx, "Set location alias `{}` to `{}`", name, location);
+            }
+            Some("delete") => {
+                ctx.check_scope(auth::Scope::WeatherAlias).await?;
+
+                let name = ctx.next_str("<name>")?;
+
+                let mut aliases = self.aliases.load().await;
+                let len = aliases.len();
+                aliases.retain(|a| !a.name.eq_ignore_ascii_case(&name));
+
+                if aliases.len() == len {
+                    respond!(ctx, "No location alias named `{}`", name);
+                    return Ok(());
+                }
+
+                self.settings
+                    .set_by("aliases", aliases, ctx.actor())
+                    .await?;
+                respond!(ctx, "Deleted location alias `{}`", name);
+            }
+            _ => {
+                respond!(ctx, "Expected: list, set, or delete.");
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl command::Handler for Weather {
     fn scope(&self) -> Option<auth::Scope> {
@@ -62,73 +278,156 @@ impl command::Handler for Weather {
 
         match ctx.next().as_deref() {
             Some("current") => {
-                let api = self
-                    .api
-                    .read()
-                    .await
-                    .as_ref()
-                    .ok_or_else(|| respond_err!("API not configured"))?
-                    .clone();
+                let loc = self.resolve(ctx.rest()).await;
+                self.current(ctx, loc).await?;
+            }
+            Some("forecast") => {
+                let loc = self.resolve(ctx.rest()).await;
+                self.forecast(ctx, loc).await?;
+            }
+            Some("alias") => {
+                self.alias_command(ctx).await?;
+            }
+            Some(name) => match self.alias(name).await {
+                Some(loc) => self.current(ctx, Some(loc)).await?,
+                None => respond!(
+                    ctx,
+                    "Expected: current, forecast, alias, or a location alias."
+                ),
+            },
+            None => {
+                respond!(
+                    ctx,
+                    "Expected: current, forecast, alias, or a location alias."
+                );
+            }
+        }
 
-                let loc = match ctx.rest() {
-                    "" => self.location.load().await,
-                    rest => Some(rest.to_string()),
-                };
+        Ok(())
+    }
+}
 
-                let loc = match loc {
-                    Some(loc) => loc,
-                    None => {
-                        respond!(ctx, "Must specify <location>");
-                        return Ok(());
-                    }
-                };
+/// Render the current weather.
+fn render_current(current: &Current, temperature_unit: TemperatureUnit) -> String {
+    let mut parts = Vec::with_capacity(4);
 
-                let temperature_unit = self.temperature_unit.load().await;
+    let t = ThermodynamicTemperature::new::<kelvin>(current.main.temp);
 
-                let current = api.current(loc.clone()).await?;
+    parts.push(temperature_unit.with(t));
 
-                let current = match current {
-                    Some(current) => current,
-                    None => {
-                        respond!(ctx, "Could not find location `{}`", loc);
-                        return Ok(());
-                    }
-                };
+    for w in &current.weather {
+        parts.push(w.to_string());
+    }
 
-                let mut parts = Vec::with_capacity(4);
+    if let Some(rain) = &current.rain {
+        parts.extend(match (rain._1h, rain._3h) {
+            (Some(m), _) => Some(format!("raining {:.0}mm/h", m)),
+            (_, Some(m)) => Some(format!("raining {:.0}mm/3h", m)),
+            _ => None,
+        });
+    }
 
-                let t = ThermodynamicTemperature::new::<kelvin>(current.main.temp);
+    if let Some(snow) = &current.snow {
+        parts.extend(match (snow._1h, snow._3h) {
+            (Some(m), _) => Some(format!("snowing {:.0}mm/h", m)),
+            (_, Some(m)) => Some(format!("snowing {:.0}mm/3h", m)),
+            _ => None,
+        });
+    }
 
-                parts.push(temperature_unit.with(t));
+    format!("{} -> {}.", current.name, parts.join(", "))
+}
 
-                for w in current.weather {
-                    parts.push(w.to_string());
-                }
+/// Render the forecast as a summary per day.
+fn render_forecast(forecast: &Forecast, temperature_unit: TemperatureUnit) -> String {
+    let offset = forecast
+        .city
+        .timezone
+        .and_then(FixedOffset::east_opt)
+        .unwrap_or_else(|| FixedOffset::east(0));
 
-                if let Some(rain) = current.rain {
-                    parts.extend(match (rain._1h, rain._3h) {
-                        (Some(m), _) => Some(format!("raining {:.0}mm/h", m)),
-                        (_, Some(m)) => Some(format!("raining {:.0}mm/3h", m)),
-                        _ => None,
-                    });
-                }
+    let mut days = Vec::<Day>::new();
 
-                if let Some(snow) = current.snow {
-                    parts.extend(match (snow._1h, snow._3h) {
-                        (Some(m), _) => Some(format!("snowing {:.0}mm/h", m)),
-                        (_, Some(m)) => Some(format!("snowing {:.0}mm/3h", m)),
-                        _ => None,
-                    });
-                }
+    for entry in &forecast.list {
+        let at = offset.timestamp(entry.dt, 0);
+        let date = at.date().naive_local();
 
-                respond!(ctx, "{} -> {}.", current.name, parts.join(", "));
-            }
-            _ => {
-                respond!(ctx, "Expected: current.");
+        if days.last().map(|d| d.date) != Some(date) {
+            if days.len() == FORECAST_DAYS {
+                break;
             }
+
+            days.push(Day {
+                date,
+                weekday: at.weekday(),
+                min: entry.main.temp,
+                max: entry.main.temp,
+                precipitation: 0f32,
+                weather: None,
+                distance: u32::MAX,
+            });
         }
 
-        Ok(())
+        let day = match days.last_mut() {
+            Some(day) => day,
+            None => continue,
+        };
+
+        day.min = day.min.min(entry.main.temp_min.unwrap_or(entry.main.temp));
+        day.max = day.max.max(entry.main.temp_max.unwrap_or(entry.main.temp));
+
+        for p in entry.rain.iter().chain(entry.snow.iter()) {
+            day.precipitation += p._3h.unwrap_or_default();
+        }
+
+        // NB: describe the day with the weather closest to midday.
+        let distance = (at.hour() as i32 - 12).abs() as u32;
+
+        if distance < day.distance {
+            day.distance = distance;
+            day.weather = entry.weather.first().cloned();
+        }
+    }
+
+    if days.is_empty() {
+        return format!("No forecast for {}.", forecast.city.name);
+    }
+
+    let days = days
+        .into_iter()
+        .map(|day| {
+            let min = temperature_unit.with(ThermodynamicTemperature::new::<kelvin>(day.min));
+            let max = temperature_unit.with(ThermodynamicTemperature::new::<kelvin>(day.max));
+
+            let mut parts = Vec::with_capacity(3);
+
+            if min == max {
+                parts.push(format!("{} {}", day.weekday, min));
+            } else {
+                parts.push(format!("{} {} to {}", day.weekday, min, max));
+            }
+
+            parts.extend(day.weather.map(|w| w.to_string()));
+
+            if day.precipitation > 0f32 {
+                parts.push(format!("{:.1}mm precipitation", day.precipitation));
+            }
+
+            parts.join(", ")
+        })
+        .collect::<Vec<_>>();
+
+    return format!("{} forecast -> {}.", forecast.city.name, days.join("; "));
+
+    struct Day {
+        date: NaiveDate,
+        weekday: Weekday,
+        min: f32,
+        max: f32,
+        precipitation: f32,
+        weather: Option<open_weather_map::Weather>,
+        /// Hours from midday of the entry that `weather` is taken from.
+        distance: u32,
     }
 }
 
@@ -150,14 +449,24 @@ impl super::Module for Module {
             ..
         }: module::HookContext<'_>,
     ) -> Result<()> {
+        let cache: Cache = injector
+            .get()
+            .await
+            .ok_or_else(|| anyhow!("missing cache"))?;
+
+        let settings = settings.scoped("weather");
+
         handlers.insert(
             "weather",
             Weather {
-                enabled: settings.var("weather/enabled", false).await?,
+                enabled: settings.var("enabled", false).await?,
                 temperature_unit: settings
-                    .var("weather/temperature-unit", TemperatureUnit::DegreesCelsius)
+                    .var("temperature-unit", TemperatureUnit::DegreesCelsius)
                     .await?,
-                location: settings.optional("weather/location").await?,
+                location: settings.optional("location").await?,
+                aliases: settings.var("aliases", Vec::new()).await?,
+                settings: settings.clone(),
+                cache: cache.namespaced(&"weather")?,
                 api: injector.var().await?,
             },
         );
@@ -165,3 +474,48 @@ impl super::Module for Module {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{render_current, render_forecast, TemperatureUnit};
+    use crate::api::open_weather_map::mock;
+
+    #[tokio::test]
+    async fn test_render_current() -> Result<(), anyhow::Error> {
+        let api = mock::server();
+
+        let current = api
+            .current(String::from("Stockholm"))
+            .await?
+            .expect("current weather");
+
+        assert_eq!(
+            "Stockholm -> 2.0 °C, light snow 🌨️, snowing 1mm/h.",
+            render_current(&current, TemperatureUnit::DegreesCelsius)
+        );
+
+        assert!(api.current(String::from("Atlantis")).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_render_forecast() -> Result<(), anyhow::Error> {
+        let api = mock::server();
+
+        let forecast = api
+            .forecast(String::from("Stockholm"))
+            .await?
+            .expect("forecast");
+
+        assert_eq!(
+            "Stockholm forecast -> \
+             Sun 0.5 °C to 3.0 °C, few clouds ⛅, 0.5mm precipitation; \
+             Mon -1.0 °C to 5.0 °C, light rain 🌦️, 2.0mm precipitation; \
+             Tue 7.0 °C, overcast clouds 🌧️.",
+            render_forecast(&forecast, TemperatureUnit::DegreesCelsius)
+        );
+
+        assert!(api.forecast(String::from("Atlantis")).await?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+//! Named settings profiles.
+//!
+//! A profile is a snapshot of a subset of settings, which can be activated to
+//! apply all of them at once.
+
+use crate::db;
+use crate::db::schema::settings_profiles;
+use crate::settings;
+use anyhow::{anyhow, bail, Result};
+use diesel::prelude::*;
+use std::collections::BTreeMap;
+
+/// A named snapshot of settings.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    /// The name of the profile.
+    pub name: String,
+    /// The game which automatically activates this profile.
+    #[serde(default)]
+    pub game: Option<String>,
+    /// The settings stored in the profile.
+    #[serde(default)]
+    pub settings: BTreeMap<String, serde_json::Value>,
+}
+
+impl Profile {
+    /// Test if the profile matches the given game.
+    pub fn matches_game(&self, game: &str) -> bool {
+        match self.game.as_ref() {
+            Some(g) => g.eq_ignore_ascii_case(game.trim()),
+            None => false,
+        }
+    }
+}
+
+#[derive(diesel::Queryable, diesel::Insertable)]
+#[table_name = "settings_profiles"]
+struct Row {
+    name: String,
+    game: Option<String>,
+    settings: String,
+}
+
+impl Row {
+    fn into_profile(self) -> Result<Profile> {
+        Ok(Profile {
+            name: self.name,
+            game: self.game,
+            settings: serde_json::from_str(&self.settings)?,
+        })
+    }
+}
+
+/// Storage for settings profiles.
+#[derive(Clone)]
+pub struct Profiles {
+    db: db::Database,
+    settings: settings::Settings,
+}
+
+impl Profiles {
+    /// Open the profiles database.
+    pub async fn load(db: db::Database, settings: settings::Settings) -> Result<Self> {
+        Ok(Profiles { db, settings })
+    }
+
+    /// List all profiles.
+    pub async fn list(&self) -> Result<Vec<Profile>> {
+        use self::settings_profiles::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                dsl::settings_profiles
+                    .order(dsl::name)
+                    .load::<Row>(c)?
+                    .into_iter()
+                    .map(Row::into_profile)
+                    .collect()
+            })
+            .await
+    }
+
+    /// Get the profile with the given name.
+    pub async fn get(&self, name: &str) -> Result<Option<Profile>> {
+        use self::settings_profiles::dsl;
+
+        let name = name.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                dsl::settings_profiles
+                    .filter(dsl::name.eq(&name))
+                    .first::<Row>(c)
+                    .optional()?
+                    .map(Row::into_profile)
+                    .transpose()
+            })
+            .await
+    }
+
+    /// Store the given profile, validating every setting against the schema.
+    pub async fn put(&self, profile: Profile) -> Result<()> {
+        use self::settings_profiles::dsl;
+
+        for (key, value) in &profile.settings {
+            self.validate(key, value)?;
+        }
+
+        let row = Row {
+            name: profile.name,
+            game: profile.game.filter(|g| !g.trim().is_empty()),
+            settings: serde_json::to_string(&profile.settings)?,
+        };
+
+        self.db
+            .asyncify(move |c| {
+                diesel::replace_into(dsl::settings_profiles)
+                    .values(&row)
+                    .execute(c)?;
+                Ok(())
+            })
+            .await
+    }
+
+    /// Snapshot the current value of the given keys into a profile.
+    ///
+    /// Keys which are not set are skipped, unless they are optional.
+    pub async fn snapshot(&self, name: &str, game: Option<String>, keys: &[String]) -> Result<()> {
+        let mut settings = BTreeMap::new();
+
+        for key in keys {
+            let schema = self
+                .settings
+                .lookup(key)
+                .ok_or_else(|| anyhow!("no such setting: {}", key))?;
+
+            let value = match self.settings.get::<serde_json::Value>(key).await? {
+                Some(value) => value,
+                None if schema.ty.optional => serde_json::Value::Null,
+                None => continue,
+            };
+
+            settings.insert(key.clone(), value);
+        }
+
+        self.put(Profile {
+            name: name.to_string(),
+            game,
+            settings,
+        })
+        .await
+    }
+
+    /// Delete the profile with the given name.
+    pub async fn delete(&self, name: &str) -> Result<bool> {
+        use self::settings_profiles::dsl;
+
+        let name = name.to_string();
+
+        self.db
+            .asyncify(move |c| {
+                let count = diesel::delete(dsl::settings_profiles.filter(dsl::name.eq(&name)))
+                    .execute(c)?;
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Find the profile which is activated by the given game.
+    pub async fn for_game(&self, game: &str) -> Result<Option<Profile>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|p| p.matches_game(game)))
+    }
+
+    /// Activate the profile with the given name, recording the changes as
+    /// performed by the given actor.
+    ///
+    /// Returns `false` if there is no such profile.
+    pub async fn activate(&self, name: &str, actor: settings::Actor) -> Result<bool> {
+        let profile = match self.get(name).await? {
+            Some(profile) => profile,
+            None => return Ok(false),
+        };
+
+        for (key, value) in &profile.settings {
+            self.validate(key, value)?;
+        }
+
+        let values = profile.settings.into_iter().collect();
+        self.settings.set_all_json_by(values, actor.clone()).await?;
+        self.settings
+            .set_by("profiles/active", profile.name, actor)
+            .await?;
+        Ok(true)
+    }
+
+    /// Validate that the given setting can be stored in a profile.
+    fn validate(&self, key: &str, value: &serde_json::Value) -> Result<()> {
+        if key.starts_with("secrets/") {
+            bail!("secret settings can't be stored in a profile: {}", key);
+        }
+
+        let schema = self
+            .settings
+            .lookup(key)
+            .ok_or_else(|| anyhow!("no such setting: {}", key))?;
+
+        if !schema.ty.is_compatible_with_json(value) {
+            bail!("value for `{}` is not a valid {}", key, schema.ty);
+        }
+
+        Ok(())
+    }
+}
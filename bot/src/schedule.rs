@@ -0,0 +1,343 @@
+//! Scheduled changes to settings.
+//!
+//! A scheduled change sets a setting at a given time, and can optionally revert
+//! it to its previous value after a delay. Changes are stored in the database,
+//! so they survive restarts.
+
+use crate::db;
+use crate::db::schema::scheduled_settings;
+use crate::settings;
+use crate::utils;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, NaiveTime, TimeZone as _, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use std::time;
+
+/// How frequently we check for changes which are due.
+const POLL_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
+/// A scheduled change to a setting.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Scheduled {
+    /// The identifier of the scheduled change.
+    pub id: i32,
+    /// The key of the setting to change.
+    pub key: String,
+    /// The value to set. `None` clears the setting.
+    pub value: Option<serde_json::Value>,
+    /// When the change will be applied.
+    pub run_at: DateTime<Utc>,
+    /// How long after being applied the change is reverted.
+    pub revert_after: Option<utils::Duration>,
+    /// Who scheduled the change.
+    pub actor: settings::Actor,
+    /// If this change reverts an earlier change.
+    pub is_revert: bool,
+}
+
+#[derive(diesel::Queryable)]
+struct Row {
+    id: i32,
+    key: String,
+    value: Option<String>,
+    run_at: chrono::NaiveDateTime,
+    revert_after: Option<String>,
+    actor: String,
+    is_revert: bool,
+}
+
+impl Row {
+    fn into_scheduled(self) -> Result<Scheduled> {
+        Ok(Scheduled {
+            id: self.id,
+            key: self.key,
+            value: self.value.map(|v| serde_json::from_str(&v)).transpose()?,
+            run_at: DateTime::from_utc(self.run_at, Utc),
+            revert_after: self.revert_after.map(|d| str::parse(&d)).transpose()?,
+            actor: serde_json::from_str(&self.actor)?,
+            is_revert: self.is_revert,
+        })
+    }
+}
+
+#[derive(diesel::Insertable)]
+#[table_name = "scheduled_settings"]
+struct InsertRow {
+    key: String,
+    value: Option<String>,
+    run_at: chrono::NaiveDateTime,
+    revert_after: Option<String>,
+    actor: String,
+    is_revert: bool,
+}
+
+/// Scheduler for changes to settings.
+#[derive(Clone)]
+pub struct Schedule {
+    db: db::Database,
+    settings: settings::Settings,
+}
+
+impl Schedule {
+    /// Open the schedule database.
+    pub async fn load(db: db::Database, settings: settings::Settings) -> Result<Self> {
+        Ok(Schedule { db, settings })
+    }
+
+    /// List all scheduled changes, in the order they will be applied.
+    pub async fn list(&self) -> Result<Vec<Scheduled>> {
+        use self::scheduled_settings::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                dsl::scheduled_settings
+                    .order((dsl::run_at, dsl::id))
+                    .load::<Row>(c)?
+                    .into_iter()
+                    .map(Row::into_scheduled)
+                    .collect()
+            })
+            .await
+    }
+
+    /// Schedule a change to the given setting.
+    ///
+    /// If `revert_after` is specified, the setting is restored to the value it
+    /// had before the change once that much time has passed.
+    pub async fn schedule(
+        &self,
+        key: &str,
+        value: Option<serde_json::Value>,
+        run_at: DateTime<Utc>,
+        revert_after: Option<utils::Duration>,
+        actor: settings::Actor,
+    ) -> Result<i32> {
+        self.validate(key, value.as_ref())?;
+
+        let row = InsertRow {
+            key: key.to_string(),
+            value: value.map(|v| serde_json::to_string(&v)).transpose()?,
+            run_at: run_at.naive_utc(),
+            revert_after: revert_after.map(|d| d.to_string()),
+            actor: serde_json::to_string(&actor)?,
+            is_revert: false,
+        };
+
+        self.insert(row).await
+    }
+
+    /// Cancel the scheduled change with the given id.
+    ///
+    /// Returns `false` if there is no such change.
+    pub async fn cancel(&self, id: i32) -> Result<bool> {
+        use self::scheduled_settings::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                let count =
+                    diesel::delete(dsl::scheduled_settings.filter(dsl::id.eq(id))).execute(c)?;
+                Ok(count == 1)
+            })
+            .await
+    }
+
+    /// Get the next time the given wall-clock time occurs, in the time zone
+    /// configured for the bot.
+    pub async fn next_time(&self, time: NaiveTime) -> Result<DateTime<Utc>> {
+        let tz = self
+            .settings
+            .get::<Tz>("time/timezone")
+            .await?
+            .unwrap_or(chrono_tz::Etc::UTC);
+
+        next_time(Utc::now(), tz, time)
+            .ok_or_else(|| anyhow!("{} doesn't occur in {}", time, tz.name()))
+    }
+
+    /// Apply scheduled changes as they become due.
+    pub async fn run(self) -> Result<()> {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let due = match self.due(Utc::now()).await {
+                Ok(due) => due,
+                Err(e) => {
+                    log_error!(e, "Failed to load scheduled setting changes");
+                    continue;
+                }
+            };
+
+            for scheduled in due {
+                let id = scheduled.id;
+
+                if let Err(e) = self.apply(scheduled).await {
+                    log_error!(e, "Failed to apply scheduled setting change");
+                }
+
+                if let Err(e) = self.cancel(id).await {
+                    log_error!(e, "Failed to remove scheduled setting change: {}", id);
+                }
+            }
+        }
+    }
+
+    /// Get all changes which are due at the given time.
+    async fn due(&self, now: DateTime<Utc>) -> Result<Vec<Scheduled>> {
+        use self::scheduled_settings::dsl;
+
+        let now = now.naive_utc();
+
+        self.db
+            .asyncify(move |c| {
+                dsl::scheduled_settings
+                    .filter(dsl::run_at.le(now))
+                    .order((dsl::run_at, dsl::id))
+                    .load::<Row>(c)?
+                    .into_iter()
+                    .map(Row::into_scheduled)
+                    .collect()
+            })
+            .await
+    }
+
+    /// Apply the given change, scheduling a revert if requested.
+    async fn apply(&self, scheduled: Scheduled) -> Result<()> {
+        let old = self
+            .settings
+            .get::<serde_json::Value>(&scheduled.key)
+            .await?;
+
+        log::info!(
+            "Applying scheduled setting change: {} = {:?}",
+            scheduled.key,
+            scheduled.value
+        );
+
+        match scheduled.value {
+            Some(value) => {
+                self.settings
+                    .set_json_by(&scheduled.key, value, scheduled.actor.clone())
+                    .await?;
+            }
+            None => {
+                self.settings
+                    .clear_by(&scheduled.key, scheduled.actor.clone())
+                    .await?;
+            }
+        }
+
+        if let Some(revert_after) = scheduled.revert_after {
+            let row = InsertRow {
+                key: scheduled.key,
+                value: old.map(|v| serde_json::to_string(&v)).transpose()?,
+                run_at: (Utc::now() + revert_after.as_chrono()).naive_utc(),
+                revert_after: None,
+                actor: serde_json::to_string(&scheduled.actor)?,
+                is_revert: true,
+            };
+
+            self.insert(row).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert the given row, returning its id.
+    async fn insert(&self, row: InsertRow) -> Result<i32> {
+        use self::scheduled_settings::dsl;
+
+        self.db
+            .asyncify(move |c| {
+                c.transaction(|| {
+                    diesel::insert_into(dsl::scheduled_settings)
+                        .values(&row)
+                        .execute(c)?;
+
+                    let id = dsl::scheduled_settings
+                        .select(dsl::id)
+                        .order(dsl::id.desc())
+                        .first::<i32>(c)?;
+
+                    Ok(id)
+                })
+            })
+            .await
+    }
+
+    /// Validate that the given value can be scheduled for the given setting.
+    fn validate(&self, key: &str, value: Option<&serde_json::Value>) -> Result<()> {
+        if key.starts_with("secrets/") {
+            bail!("secret settings can't be scheduled: {}", key);
+        }
+
+        let schema = self
+            .settings
+            .lookup(key)
+            .ok_or_else(|| anyhow!("no such setting: {}", key))?;
+
+        if schema.secret {
+            bail!("secret settings can't be scheduled: {}", key);
+        }
+
+        if let Some(value) = value {
+            if !schema.ty.is_compatible_with_json(value) {
+                bail!("value for `{}` is not a valid {}", key, schema.ty);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the first time after `now` that the given wall-clock time occurs in
+/// the given time zone.
+///
+/// Returns `None` if the time doesn't occur in the next couple of days, which
+/// can only happen due to time zone transitions.
+pub(crate) fn next_time(now: DateTime<Utc>, tz: Tz, time: NaiveTime) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(&tz);
+    let mut date = local.date().naive_local();
+
+    for _ in 0..3 {
+        if let Some(candidate) = tz.from_local_datetime(&date.and_time(time)).earliest() {
+            if candidate > local {
+                return Some(candidate.with_timezone(&Utc));
+            }
+        }
+
+        date = date.succ();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_time;
+    use chrono::{NaiveTime, TimeZone as _, Utc};
+
+    #[test]
+    fn test_next_time() {
+        let now = Utc.ymd(2020, 1, 10).and_hms(20, 30, 0);
+        let at = |h, m| NaiveTime::from_hms(h, m, 0);
+
+        assert_eq!(
+            next_time(now, chrono_tz::Etc::UTC, at(23, 0)),
+            Some(Utc.ymd(2020, 1, 10).and_hms(23, 0, 0))
+        );
+
+        assert_eq!(
+            next_time(now, chrono_tz::Etc::UTC, at(8, 0)),
+            Some(Utc.ymd(2020, 1, 11).and_hms(8, 0, 0))
+        );
+
+        // 21:30 in Stockholm (UTC+1) has already passed.
+        assert_eq!(
+            next_time(now, chrono_tz::Europe::Stockholm, at(21, 0)),
+            Some(Utc.ymd(2020, 1, 11).and_hms(20, 0, 0))
+        );
+    }
+}
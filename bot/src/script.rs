@@ -0,0 +1,919 @@
+//! Custom commands and message hooks written in [Rhai].
+//!
+//! Scripts are loaded from the `scripts/` directory in the root of the bot, and
+//! are reloaded when any of them change. A script registers its commands and
+//! hooks when it's loaded:
+//!
+//! ```text
+//! register_command("hello", "hello");
+//! register_command("boost", "boost", "currency/boost");
+//! register_hook("on_message");
+//!
+//! fn hello(user, args) {
+//!     respond("Hello " + user + "!");
+//! }
+//!
+//! fn on_message(user, message) {
+//! }
+//! ```
+//!
+//! Commands require the `script` scope, unless they specify another one.
+//! Scripts can only use the sandboxed API registered in [engine], which
+//! doesn't give access to the filesystem.
+//!
+//! [Rhai]: https://github.com/jonathandturner/rhai
+
+use crate::auth::Scope;
+use crate::command;
+use crate::currency::Currency;
+use crate::db;
+use crate::injector;
+use crate::irc;
+use crate::module;
+use crate::player;
+use crate::prelude::*;
+use crate::settings;
+use crate::task;
+use anyhow::{anyhow, bail, Result};
+use futures::executor::block_on;
+use hyper::body::HttpBody as _;
+use hyper::client::connect::dns::Name;
+use hyper::client::HttpConnector;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time;
+
+/// How frequently the scripts directory is checked for modifications.
+const POLL_INTERVAL: time::Duration = time::Duration::from_secs(5);
+/// How long a single script invocation may run before it's terminated.
+const CALL_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+/// Maximum number of operations a single script invocation may perform.
+const MAX_OPERATIONS: u64 = 1_000_000;
+/// Timeout for HTTP requests made by scripts.
+const HTTP_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+/// Maximum size of HTTP responses scripts can read.
+const HTTP_MAX_BODY: usize = 64 * 1024;
+/// Extension of script files.
+const EXTENSION: &str = "rhai";
+
+/// A single compiled script.
+struct Script {
+    path: PathBuf,
+    ast: rhai::AST,
+}
+
+/// A function in a script.
+#[derive(Clone)]
+struct Function {
+    script: Arc<Script>,
+    name: String,
+}
+
+/// A command registered by a script.
+#[derive(Clone)]
+struct Command {
+    function: Function,
+    scope: Scope,
+}
+
+/// Commands and hooks registered by all loaded scripts.
+#[derive(Default)]
+struct Registry {
+    commands: HashMap<String, Command>,
+    hooks: Vec<Function>,
+}
+
+/// A registration performed by a script while it's being loaded.
+enum Registration {
+    Command {
+        name: String,
+        function: String,
+        scope: Option<String>,
+    },
+    Hook {
+        function: String,
+    },
+}
+
+/// Output produced by a script invocation.
+enum Output {
+    /// Respond to the user.
+    Respond(String),
+    /// Send a message to the channel.
+    Privmsg(String),
+}
+
+/// Services available to scripts.
+#[derive(Clone)]
+struct Services {
+    settings: settings::Settings,
+    currency: injector::Var<Option<Currency>>,
+    player: injector::Var<Option<player::Player>>,
+    http: HttpClient,
+}
+
+/// Loaded scripts.
+#[derive(Clone)]
+pub struct Scripts {
+    dir: PathBuf,
+    enabled: settings::Var<bool>,
+    services: Services,
+    registry: Arc<RwLock<Registry>>,
+}
+
+impl Scripts {
+    /// Load all scripts from the given directory.
+    pub async fn load(
+        dir: PathBuf,
+        injector: &injector::Injector,
+        settings: settings::Settings,
+    ) -> Result<Self> {
+        let scripts = Scripts {
+            dir,
+            enabled: settings.var("script/enabled", false).await?,
+            services: Services {
+                settings,
+                currency: injector.var().await?,
+                player: injector.var().await?,
+                http: http_client(),
+            },
+            registry: Default::default(),
+        };
+
+        scripts.reload().await?;
+        Ok(scripts)
+    }
+
+    /// Watch the scripts directory for modifications, reloading all scripts
+    /// whenever it changes.
+    pub async fn watch(self) -> Result<()> {
+        let mut last = fingerprint(&self.dir);
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let current = fingerprint(&self.dir);
+
+            if current == last {
+                continue;
+            }
+
+            last = current;
+
+            // NB: keep watching, so that fixing whatever went wrong reloads
+            // the scripts.
+            if let Err(e) = self.reload().await {
+                log_error!(e, "Failed to reload scripts: {}", self.dir.display());
+                continue;
+            }
+
+            log::info!("Reloaded scripts: {}", self.dir.display());
+        }
+    }
+
+    /// Reload all scripts.
+    ///
+    /// Scripts which fail to load are reported and skipped.
+    async fn reload(&self) -> Result<()> {
+        let dir = self.dir.clone();
+        let services = self.services.clone();
+
+        let registry = task::asyncify(move || load_dir(&dir, &services)).await?;
+        *self.registry.write() = registry;
+        Ok(())
+    }
+
+    /// Call the given function with the given arguments.
+    async fn call(
+        &self,
+        function: Function,
+        channel: String,
+        args: (String, String),
+    ) -> Result<Vec<Output>> {
+        let services = self.services.clone();
+
+        task::asyncify(move || {
+            let env = Arc::new(Env::new(services, Some(channel)));
+            let engine = engine(&env);
+            let mut scope = rhai::Scope::new();
+
+            engine
+                .call_fn::<_, rhai::Dynamic>(&mut scope, &function.script.ast, &function.name, args)
+                .map_err(|e| {
+                    anyhow!(
+                        "{}: {}: {}",
+                        function.script.path.display(),
+                        function.name,
+                        e
+                    )
+                })?;
+
+            let output = env.output.lock().drain(..).collect::<Vec<_>>();
+            Ok::<_, anyhow::Error>(output)
+        })
+        .await
+    }
+}
+
+impl module::DynamicHandlers for Scripts {
+    fn get(&self, command: &str) -> Option<Arc<dyn command::Handler>> {
+        let command = self.registry.read().commands.get(command)?.clone();
+
+        Some(Arc::new(Handler {
+            scripts: self.clone(),
+            command,
+        }))
+    }
+}
+
+/// Handler for a command registered by a script.
+struct Handler {
+    scripts: Scripts,
+    command: Command,
+}
+
+#[async_trait]
+impl command::Handler for Handler {
+    fn scope(&self) -> Option<Scope> {
+        Some(self.command.scope)
+    }
+
+    async fn handle(&self, ctx: &mut command::Context) -> Result<()> {
+        if !self.scripts.enabled.load().await {
+            return Ok(());
+        }
+
+        let user = ctx.user.name().unwrap_or_default().to_string();
+        let args = ctx.rest().to_string();
+
+        let output = self
+            .scripts
+            .call(
+                self.command.function.clone(),
+                ctx.channel().to_string(),
+                (user, args),
+            )
+            .await?;
+
+        for output in output {
+            match output {
+                Output::Respond(m) => ctx.respond(m).await,
+                Output::Privmsg(m) => ctx.privmsg(m).await,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Message hook which runs the hooks registered by scripts.
+pub struct Hooks {
+    scripts: Scripts,
+}
+
+impl Hooks {
+    /// Construct a new message hook for the given scripts.
+    pub fn new(scripts: Scripts) -> Self {
+        Self { scripts }
+    }
+}
+
+#[async_trait]
+impl command::MessageHook for Hooks {
+    async fn peek(&self, user: &irc::User, m: &str) -> Result<()> {
+        if !self.scripts.enabled.load().await {
+            return Ok(());
+        }
+
+        let hooks = self.scripts.registry.read().hooks.clone();
+        let name = user.name().unwrap_or_default().to_string();
+
+        for hook in hooks {
+            let args = (name.clone(), m.to_string());
+
+            let output = match self
+                .scripts
+                .call(hook, user.channel().to_string(), args)
+                .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    log_error!(e, "Script hook failed");
+                    continue;
+                }
+            };
+
+            for output in output {
+                match output {
+                    Output::Respond(m) => user.respond(m).await,
+                    Output::Privmsg(m) => user.sender().privmsg(m).await,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The environment of a single script invocation.
+struct Env {
+    services: Services,
+    /// The channel the script is invoked in, if any.
+    channel: Option<String>,
+    /// Output collected from the script.
+    output: Mutex<Vec<Output>>,
+    /// Registrations collected while loading the script.
+    registrations: Mutex<Vec<Registration>>,
+}
+
+impl Env {
+    fn new(services: Services, channel: Option<String>) -> Self {
+        Self {
+            services,
+            channel,
+            output: Mutex::new(Vec::new()),
+            registrations: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Read the given setting as a string, or an empty string if it isn't
+    /// set. Secret settings can't be read.
+    fn setting(&self, key: &str) -> String {
+        let key = key.trim_matches('/');
+
+        let secret = key.starts_with("secrets/")
+            || self
+                .services
+                .settings
+                .lookup(key)
+                .map(|schema| schema.secret)
+                .unwrap_or(true);
+
+        if secret {
+            return String::new();
+        }
+
+        match block_on(self.services.settings.get::<serde_json::Value>(key)) {
+            Ok(Some(serde_json::Value::String(s))) => s,
+            Ok(Some(value)) => value.to_string(),
+            Ok(None) => String::new(),
+            Err(e) => {
+                log_error!(e, "Script failed to read setting: {}", key);
+                String::new()
+            }
+        }
+    }
+
+    /// Get the balance of the given user.
+    fn balance(&self, user: &str) -> i64 {
+        let channel = match self.channel.as_ref() {
+            Some(channel) => channel,
+            None => return 0,
+        };
+
+        let currency = block_on(self.services.currency.read());
+
+        let currency = match currency.as_ref() {
+            Some(currency) => currency,
+            None => return 0,
+        };
+
+        match block_on(currency.balance_of(channel, &db::user_id(user))) {
+            Ok(balance) => balance.map(|b| b.balance).unwrap_or_default(),
+            Err(e) => {
+                log_error!(e, "Script failed to get balance of: {}", user);
+                0
+            }
+        }
+    }
+
+    /// Add to the balance of the given user.
+    fn balance_add(&self, user: &str, amount: i64) {
+        let channel = match self.channel.as_ref() {
+            Some(channel) => channel,
+            None => return,
+        };
+
+        let currency = block_on(self.services.currency.read());
+
+        if let Some(currency) = currency.as_ref() {
+            if let Err(e) = block_on(currency.balance_add(channel, &db::user_id(user), amount)) {
+                log_error!(e, "Script failed to add to balance of: {}", user);
+            }
+        }
+    }
+
+    /// Get a description of the current song, or an empty string if nothing
+    /// is playing.
+    fn current_song(&self) -> String {
+        let player = block_on(self.services.player.read());
+
+        match player.as_ref() {
+            Some(player) => block_on(player.current())
+                .map(|song| song.item.what())
+                .unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
+    /// Fetch the given URL, returning the body or an empty string on errors.
+    fn http_get(&self, url: &str) -> String {
+        match block_on(self.inner_http_get(url)) {
+            Ok(body) => body,
+            Err(e) => {
+                log_error!(e, "Script failed to fetch: {}", url);
+                String::new()
+            }
+        }
+    }
+
+    async fn inner_http_get(&self, url: &str) -> Result<String> {
+        let url = reqwest::Url::parse(url)?;
+
+        match url.scheme() {
+            "http" | "https" => (),
+            scheme => bail!("unsupported scheme: {}", scheme),
+        }
+
+        // NB: hosts which are names are vetted by the resolver used to
+        // connect, see `PublicResolver`.
+        match url.host() {
+            Some(url::Host::Domain(..)) => (),
+            Some(url::Host::Ipv4(ip)) => check_public(IpAddr::V4(ip))?,
+            Some(url::Host::Ipv6(ip)) => check_public(IpAddr::V6(ip))?,
+            None => bail!("missing host"),
+        }
+
+        let uri = str::parse::<hyper::Uri>(url.as_str())?;
+
+        let request = async {
+            // NB: redirects are not followed, since they could lead to
+            // non-public addresses.
+            let mut res = self.services.http.get(uri).await?;
+
+            if res.status().is_client_error() || res.status().is_server_error() {
+                bail!("bad response: {}", res.status());
+            }
+
+            let mut body = Vec::new();
+
+            while let Some(chunk) = res.body_mut().data().await {
+                let chunk = chunk?;
+
+                if body.len() + chunk.len() > HTTP_MAX_BODY {
+                    bail!("response larger than {} bytes", HTTP_MAX_BODY);
+                }
+
+                body.extend_from_slice(&chunk);
+            }
+
+            Ok(body)
+        };
+
+        let body = tokio::time::timeout(HTTP_TIMEOUT, request)
+            .await
+            .map_err(|_| anyhow!("request timed out"))??;
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+/// HTTP client used by scripts.
+type HttpClient = hyper::Client<hyper_tls::HttpsConnector<HttpConnector<PublicResolver>>>;
+
+/// Construct the HTTP client used by scripts.
+fn http_client() -> HttpClient {
+    let mut http = HttpConnector::new_with_resolver(PublicResolver);
+    http.enforce_http(false);
+    hyper::Client::builder().build(hyper_tls::HttpsConnector::new_with_connector(http))
+}
+
+/// Resolver which refuses to resolve names to addresses that aren't public.
+///
+/// Addresses are checked as they are connected to, so a name can't resolve to
+/// a public address when checked and to a non-public one when connected to.
+#[derive(Debug, Clone)]
+struct PublicResolver;
+
+impl tower_service::Service<Name> for PublicResolver {
+    type Response = std::vec::IntoIter<IpAddr>;
+    type Error = anyhow::Error;
+    type Future = future::BoxFuture<'static, Result<Self::Response>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        Box::pin(async move {
+            let mut addrs = Vec::new();
+
+            for addr in tokio::net::lookup_host((name.as_str(), 0)).await? {
+                check_public(addr.ip())?;
+                addrs.push(addr.ip());
+            }
+
+            Ok(addrs.into_iter())
+        })
+    }
+}
+
+/// Make sure that scripts can access the given address.
+///
+/// NB: scripts must not be able to reach the bot itself, or anything else on
+/// the local network.
+fn check_public(ip: IpAddr) -> Result<()> {
+    if !is_public(ip) {
+        bail!("access to non-public address denied: {}", ip);
+    }
+
+    Ok(())
+}
+
+/// Test if the given address is publicly routable, and can be accessed by
+/// scripts.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Shared address space (100.64.0.0/10).
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4() {
+                return is_public(IpAddr::V4(ip));
+            }
+
+            let first = ip.segments()[0];
+
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local (fc00::/7) and link-local (fe80::/10).
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+/// Construct an engine which exposes the sandboxed API in the given
+/// environment.
+///
+/// The engine terminates scripts which perform too many operations, or run
+/// for longer than the call timeout.
+fn engine(env: &Arc<Env>) -> rhai::Engine {
+    use rhai::{ImmutableString, RegisterFn as _};
+
+    let mut engine = rhai::Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let deadline = time::Instant::now() + CALL_TIMEOUT;
+    engine.on_progress(move |_| time::Instant::now() < deadline);
+
+    let e = env.clone();
+    engine.register_fn(
+        "register_command",
+        move |name: ImmutableString, function: ImmutableString| {
+            e.registrations.lock().push(Registration::Command {
+                name: name.to_string(),
+                function: function.to_string(),
+                scope: None,
+            })
+        },
+    );
+
+    let e = env.clone();
+    engine.register_fn(
+        "register_command",
+        move |name: ImmutableString, function: ImmutableString, scope: ImmutableString| {
+            e.registrations.lock().push(Registration::Command {
+                name: name.to_string(),
+                function: function.to_string(),
+                scope: Some(scope.to_string()),
+            })
+        },
+    );
+
+    let e = env.clone();
+    engine.register_fn("register_hook", move |function: ImmutableString| {
+        e.registrations.lock().push(Registration::Hook {
+            function: function.to_string(),
+        })
+    });
+
+    let e = env.clone();
+    engine.register_fn("respond", move |m: ImmutableString| {
+        e.output.lock().push(Output::Respond(m.to_string()))
+    });
+
+    let e = env.clone();
+    engine.register_fn("privmsg", move |m: ImmutableString| {
+        e.output.lock().push(Output::Privmsg(m.to_string()))
+    });
+
+    let e = env.clone();
+    engine.register_fn("setting", move |key: ImmutableString| e.setting(&key));
+
+    let e = env.clone();
+    engine.register_fn("balance", move |user: ImmutableString| e.balance(&user));
+
+    let e = env.clone();
+    engine.register_fn("balance_add", move |user: ImmutableString, amount: i64| {
+        e.balance_add(&user, amount)
+    });
+
+    let e = env.clone();
+    engine.register_fn("current_song", move || e.current_song());
+
+    let e = env.clone();
+    engine.register_fn("http_get", move |url: ImmutableString| e.http_get(&url));
+
+    engine.register_fn("log", |m: ImmutableString| log::info!("script: {}", m));
+    engine
+}
+
+/// Load all scripts in the given directory.
+fn load_dir(dir: &Path, services: &Services) -> Result<Registry> {
+    let mut registry = Registry::default();
+
+    if !dir.is_dir() {
+        return Ok(registry);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+
+        if let Err(e) = load_script(&path, services, &mut registry) {
+            log_error!(e, "Failed to load script: {}", path.display());
+        }
+    }
+
+    Ok(registry)
+}
+
+/// Load a single script, adding everything it registers to the registry.
+fn load_script(path: &Path, services: &Services, registry: &mut Registry) -> Result<()> {
+    let source = fs::read_to_string(path)?;
+    load_source(path, &source, services, registry)
+}
+
+/// Load the source of a single script, adding everything it registers to the
+/// registry.
+fn load_source(
+    path: &Path,
+    source: &str,
+    services: &Services,
+    registry: &mut Registry,
+) -> Result<()> {
+    let env = Arc::new(Env::new(services.clone(), None));
+    let engine = engine(&env);
+
+    let ast = engine.compile(source).map_err(|e| anyhow!("{}", e))?;
+    engine.consume_ast(&ast).map_err(|e| anyhow!("{}", e))?;
+
+    let script = Arc::new(Script {
+        path: path.to_owned(),
+        ast,
+    });
+
+    for registration in env.registrations.lock().drain(..) {
+        match registration {
+            Registration::Command {
+                name,
+                function,
+                scope,
+            } => {
+                let scope = match scope {
+                    Some(scope) => match str::parse::<Scope>(&scope)? {
+                        Scope::Unknown => bail!("command `{}` uses unknown scope: {}", name, scope),
+                        scope => scope,
+                    },
+                    None => Scope::Script,
+                };
+
+                if registry.commands.contains_key(&name) {
+                    bail!("command `{}` is already registered by another script", name);
+                }
+
+                let function = Function {
+                    script: script.clone(),
+                    name: function,
+                };
+
+                registry.commands.insert(name, Command { function, scope });
+            }
+            Registration::Hook { function } => {
+                registry.hooks.push(Function {
+                    script: script.clone(),
+                    name: function,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fingerprint the scripts in the given directory, by their paths and
+/// modification times.
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<time::SystemTime>)> {
+    let mut out = Vec::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return out,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        out.push((path, modified));
+    }
+
+    out.sort();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{http_client, is_public, load_source, Output, Registry, Scripts, Services};
+    use crate::auth::Scope;
+    use crate::db;
+    use crate::injector;
+    use crate::settings;
+    use parking_lot::RwLock;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    const SOURCE: &str = r#"
+        register_command("hello", "hello");
+        register_command("boost", "boost", "currency/boost");
+        register_hook("on_message");
+
+        fn hello(user, args) {
+            respond("Hello " + user + ", you said: " + args);
+        }
+
+        fn boost(user, args) {
+            privmsg("Boosting!");
+        }
+
+        fn on_message(user, message) {
+            if message == "ping" {
+                respond("pong");
+            }
+        }
+
+        fn forever(user, args) {
+            loop {}
+        }
+    "#;
+
+    fn scripts() -> Scripts {
+        let db = db::Database::open(Path::new(":memory:")).unwrap();
+        let schema = settings::Schema::load_static().unwrap();
+
+        let services = Services {
+            settings: settings::Settings::new(db, schema),
+            currency: injector::Var::new(None),
+            player: injector::Var::new(None),
+            http: http_client(),
+        };
+
+        let mut registry = Registry::default();
+        load_source(Path::new("test.rhai"), SOURCE, &services, &mut registry).unwrap();
+
+        Scripts {
+            dir: PathBuf::new(),
+            enabled: settings::Var::new(true),
+            services,
+            registry: Arc::new(RwLock::new(registry)),
+        }
+    }
+
+    fn messages(output: Vec<Output>) -> Vec<String> {
+        output
+            .into_iter()
+            .map(|o| match o {
+                Output::Respond(m) => format!("respond: {}", m),
+                Output::Privmsg(m) => format!("privmsg: {}", m),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let scripts = scripts();
+
+        let (hello, boost) = {
+            let registry = scripts.registry.read();
+            assert!(registry.commands.get("forever").is_none());
+            (
+                registry.commands["hello"].clone(),
+                registry.commands["boost"].clone(),
+            )
+        };
+
+        assert_eq!(Scope::Script, hello.scope);
+        assert_eq!(Scope::CurrencyBoost, boost.scope);
+
+        let args = (String::from("setbac"), String::from("hi there"));
+        let output = scripts
+            .call(hello.function, String::from("#setbac"), args)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![String::from("respond: Hello setbac, you said: hi there")],
+            messages(output)
+        );
+
+        let args = (String::from("setbac"), String::new());
+        let output = scripts
+            .call(boost.function, String::from("#setbac"), args)
+            .await
+            .unwrap();
+        assert_eq!(vec![String::from("privmsg: Boosting!")], messages(output));
+    }
+
+    #[tokio::test]
+    async fn test_hooks() {
+        let scripts = scripts();
+        let hooks = scripts.registry.read().hooks.clone();
+        assert_eq!(1, hooks.len());
+
+        let args = (String::from("setbac"), String::from("ping"));
+        let output = scripts
+            .call(hooks[0].clone(), String::from("#setbac"), args)
+            .await
+            .unwrap();
+        assert_eq!(vec![String::from("respond: pong")], messages(output));
+
+        let args = (String::from("setbac"), String::from("hello"));
+        let output = scripts
+            .call(hooks[0].clone(), String::from("#setbac"), args)
+            .await
+            .unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_runaway_script() {
+        let scripts = scripts();
+
+        let function = {
+            let registry = scripts.registry.read();
+            let mut function = registry.commands["hello"].function.clone();
+            function.name = String::from("forever");
+            function
+        };
+
+        let args = (String::from("setbac"), String::new());
+        assert!(scripts
+            .call(function, String::from("#setbac"), args)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_is_public() {
+        let public = |s: &str| is_public(s.parse().unwrap());
+
+        assert!(public("1.1.1.1"));
+        assert!(public("2606:4700:4700::1111"));
+
+        assert!(!public("127.0.0.1"));
+        assert!(!public("10.0.0.1"));
+        assert!(!public("172.16.0.1"));
+        assert!(!public("192.168.1.1"));
+        assert!(!public("169.254.169.254"));
+        assert!(!public("100.64.0.1"));
+        assert!(!public("0.0.0.0"));
+        assert!(!public("::1"));
+        assert!(!public("::ffff:127.0.0.1"));
+        assert!(!public("fd00::1"));
+        assert!(!public("fe80::1"));
+    }
+}
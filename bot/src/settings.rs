@@ -4,11 +4,12 @@ use crate::auth::Scope;
 use crate::db;
 use crate::prelude::*;
 use crate::utils;
+use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
 use diesel::prelude::*;
 use futures::ready;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error;
 use std::fmt;
 use std::marker;
@@ -22,6 +23,8 @@ use tokio::{
 pub use crate::injector::Var;
 
 const SEPARATOR: char = '/';
+/// Prefix under which settings specific to a single channel are stored.
+const CHANNELS_PREFIX: &str = "channels/";
 
 type EventSender = mpsc::UnboundedSender<Event<serde_json::Value>>;
 type Subscriptions = Arc<RwLock<HashMap<String, Vec<EventSender>>>>;
@@ -67,6 +70,8 @@ pub enum Error {
     BadBoolean(std::str::ParseBoolError),
     /// Background task failed.
     TaskError(JoinError),
+    /// Setting is read-only since it's provided by the configuration file.
+    ReadOnly(String),
 }
 
 impl fmt::Display for Error {
@@ -100,6 +105,11 @@ impl fmt::Display for Error {
             Self::FailedToLoadSchema(ref e) => write!(fmt, "Failed to load settings.yaml: {}", e),
             Self::BadBoolean(ref e) => write!(fmt, "Bad boolean value: {}", e),
             Self::TaskError(..) => write!(fmt, "Task failed"),
+            Self::ReadOnly(ref key) => write!(
+                fmt,
+                "Setting `{}` is read-only since it's provided by the configuration file",
+                key
+            ),
         }
     }
 }
@@ -153,6 +163,9 @@ pub struct Setting {
     pub schema: SchemaType,
     pub key: String,
     pub value: serde_json::Value,
+    /// If the setting is provided by the configuration file, and can't be
+    /// modified.
+    pub read_only: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -160,6 +173,7 @@ pub struct SettingRef<'a, T> {
     pub schema: &'a SchemaType,
     pub key: Cow<'a, str>,
     pub value: Option<T>,
+    pub read_only: bool,
 }
 
 impl SettingRef<'_, serde_json::Value> {
@@ -172,6 +186,7 @@ impl SettingRef<'_, serde_json::Value> {
                 None => serde_json::Value::Null,
                 Some(value) => value,
             },
+            read_only: self.read_only,
         }
     }
 }
@@ -197,6 +212,152 @@ pub struct SchemaType {
     pub title: Option<String>,
 }
 
+/// The actor responsible for a change to a setting.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "name", rename_all = "kebab-case")]
+pub enum Actor {
+    /// The bot itself, or a component without a known user.
+    Bot,
+    /// The web interface.
+    Web,
+    /// The given user in chat.
+    Chat(String),
+    /// The configuration file.
+    Config,
+}
+
+impl Actor {
+    /// Convert into the columns stored in the database.
+    fn into_columns(self) -> (String, Option<String>) {
+        match self {
+            Actor::Bot => (String::from("bot"), None),
+            Actor::Web => (String::from("web"), None),
+            Actor::Chat(name) => (String::from("chat"), Some(name)),
+            Actor::Config => (String::from("config"), None),
+        }
+    }
+
+    /// Test if the actor is a user, whose changes are validated against the
+    /// schema.
+    fn is_user(&self) -> bool {
+        match self {
+            Actor::Web | Actor::Chat(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Convert from the columns stored in the database.
+    fn from_columns(actor: &str, name: Option<String>) -> Self {
+        match (actor, name) {
+            ("web", _) => Actor::Web,
+            ("chat", Some(name)) => Actor::Chat(name),
+            ("config", _) => Actor::Config,
+            _ => Actor::Bot,
+        }
+    }
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Actor::Bot => "bot".fmt(fmt),
+            Actor::Web => "web".fmt(fmt),
+            Actor::Chat(name) => name.fmt(fmt),
+            Actor::Config => "configuration file".fmt(fmt),
+        }
+    }
+}
+
+/// A recorded change to a setting.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Change {
+    /// The identifier of the change, used when reverting it.
+    pub id: i32,
+    /// The key of the setting that changed.
+    pub key: String,
+    /// The value before the change. `None` if it wasn't set.
+    pub old_value: Option<serde_json::Value>,
+    /// The value after the change. `None` if it was cleared.
+    pub new_value: Option<serde_json::Value>,
+    /// When the change happened.
+    pub changed_at: DateTime<Utc>,
+    /// Who performed the change.
+    pub actor: Actor,
+}
+
+impl Change {
+    fn from_model(change: db::models::SettingsChange) -> Result<Self, Error> {
+        Ok(Change {
+            id: change.id,
+            key: change.key,
+            old_value: change
+                .old_value
+                .map(|v| serde_json::from_str(&v))
+                .transpose()?,
+            new_value: change
+                .new_value
+                .map(|v| serde_json::from_str(&v))
+                .transpose()?,
+            changed_at: DateTime::from_utc(change.changed_at, Utc),
+            actor: Actor::from_columns(&change.actor, change.actor_name),
+        })
+    }
+}
+
+/// Number of days changes are kept in the settings history.
+const HISTORY_RETENTION_DAYS: i64 = 90;
+/// Maximum number of changes kept in the settings history.
+const HISTORY_MAX_CHANGES: i64 = 10_000;
+
+/// Record a change to the given setting in the settings history.
+///
+/// Nothing is recorded if the value didn't change. Changes older than the
+/// retention period, or beyond the maximum number of changes are pruned.
+fn record_change(
+    c: &SqliteConnection,
+    key: &str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    actor: Actor,
+) -> Result<(), Error> {
+    use self::db::schema::settings_history::dsl;
+
+    if old_value == new_value {
+        return Ok(());
+    }
+
+    let (actor, actor_name) = actor.into_columns();
+
+    let change = db::models::InsertSettingsChange {
+        key: key.to_string(),
+        old_value,
+        new_value,
+        actor,
+        actor_name,
+    };
+
+    diesel::insert_into(dsl::settings_history)
+        .values(&change)
+        .execute(c)?;
+
+    let cutoff = Utc::now().naive_utc() - chrono::Duration::days(HISTORY_RETENTION_DAYS);
+    diesel::delete(dsl::settings_history.filter(dsl::changed_at.lt(cutoff))).execute(c)?;
+
+    let oldest = dsl::settings_history
+        .select(dsl::id)
+        .order(dsl::id.desc())
+        .offset(HISTORY_MAX_CHANGES - 1)
+        .limit(1)
+        .first::<i32>(c)
+        .optional()?;
+
+    if let Some(oldest) = oldest {
+        diesel::delete(dsl::settings_history.filter(dsl::id.lt(oldest))).execute(c)?;
+    }
+
+    Ok(())
+}
+
 const SCHEMA: &[u8] = include_bytes!("settings.yaml");
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -254,12 +415,42 @@ impl Schema {
 
     /// Lookup the given type by key.
     pub fn lookup(&self, key: &str) -> Option<SchemaType> {
-        self.types.get(key).cloned()
+        self.get(key).cloned()
     }
 
     /// Test if schema contains the given key.
     pub fn contains(&self, key: &str) -> bool {
-        self.types.contains_key(key)
+        self.get(key).is_some()
+    }
+
+    /// Get the type of the given key.
+    ///
+    /// Channel-specific keys like `channels/<channel>/<key>` have the same type
+    /// as `<key>`.
+    fn get(&self, key: &str) -> Option<&SchemaType> {
+        if let Some(ty) = self.types.get(key) {
+            return Some(ty);
+        }
+
+        match split_channel(key) {
+            ("", _) => None,
+            (_, key) => self.types.get(key),
+        }
+    }
+}
+
+/// Split a key into its channel prefix (like `channels/<channel>/`) and the
+/// rest of the key.
+///
+/// The channel prefix is empty if the key is not specific to a channel.
+fn split_channel(key: &str) -> (&str, &str) {
+    if !key.starts_with(CHANNELS_PREFIX) {
+        return ("", key);
+    }
+
+    match key[CHANNELS_PREFIX.len()..].find(SEPARATOR) {
+        Some(index) => key.split_at(CHANNELS_PREFIX.len() + index + 1),
+        None => ("", key),
     }
 }
 
@@ -295,6 +486,8 @@ pub struct Inner {
     drivers: mpsc::UnboundedSender<Driver>,
     /// Receiver for drivers. Used by the run function.
     drivers_rx: Mutex<Option<mpsc::UnboundedReceiver<Driver>>>,
+    /// Keys which are provided by the configuration file.
+    read_only: RwLock<HashSet<String>>,
 }
 
 /// A container for settings from which we can subscribe for updates.
@@ -318,10 +511,16 @@ impl Settings {
                 prefixes: Arc::new(prefixes),
                 drivers,
                 drivers_rx: Mutex::new(Some(drivers_rx)),
+                read_only: Default::default(),
             }),
         }
     }
 
+    /// Access the schema of the settings.
+    pub fn schema(&self) -> &Schema {
+        &self.inner.schema
+    }
+
     /// Run all settings migrations.
     pub async fn run_migrations(&self) -> Result<(), Error> {
         for m in &self.inner.schema.migrations {
@@ -366,7 +565,7 @@ impl Settings {
                 Ok(()) => (),
                 Err(Error::NoTargetForSchema(..)) => {
                     log::warn!("Clearing setting without schema: {}", current_key);
-                    self.inner_clear(&current_key).await?;
+                    self.inner_clear(&current_key, Actor::Bot).await?;
                 }
                 Err(e) => return Err(e),
             }
@@ -408,14 +607,20 @@ impl Settings {
             );
         }
 
-        self.inner_clear(&from_key).await?;
+        self.inner_clear(&from_key, Actor::Bot).await?;
         Ok(())
     }
 
+    /// Get the full key of the given key in this scope, like
+    /// `channels/<channel>/<key>`.
+    pub fn full_key(&self, key: &str) -> String {
+        self.key(key).into_owned()
+    }
+
     /// Lookup the given schema.
     pub fn lookup(&self, key: &str) -> Option<&SchemaType> {
         let key = self.key(key);
-        self.inner.schema.types.get(key.as_ref())
+        self.inner.schema.get(key.as_ref())
     }
 
     /// Get a setting by prefix.
@@ -425,11 +630,14 @@ impl Settings {
         let prefix = self.key(prefix);
         let inner = self.inner.clone();
         let prefix = prefix.to_string();
+        let read_only = self.inner.read_only.read().await.clone();
 
         self.inner
             .db
             .asyncify(move |c| {
-                let prefix = match inner.prefixes.get(&prefix) {
+                let (channel, prefix) = split_channel(&prefix);
+
+                let prefix = match inner.prefixes.get(prefix) {
                     Some(prefix) => prefix,
                     None => return Ok(Vec::default()),
                 };
@@ -449,7 +657,9 @@ impl Settings {
                         None => continue,
                     };
 
-                    let value = match values.get(key) {
+                    let key = format!("{}{}", channel, key);
+
+                    let value = match values.get(&key) {
                         Some(value) => serde_json::from_str(value)?,
                         None if schema.ty.optional => serde_json::Value::Null,
                         None => continue,
@@ -457,7 +667,8 @@ impl Settings {
 
                     settings.push(Setting {
                         schema: schema.clone(),
-                        key: key.to_string(),
+                        read_only: read_only.contains(&key),
+                        key,
                         value,
                     });
                 }
@@ -476,13 +687,20 @@ impl Settings {
     {
         let key = self.key(key);
 
-        let schema = match self.inner.schema.types.get(key.as_ref()) {
+        let schema = match self.inner.schema.get(key.as_ref()) {
             Some(schema) => schema,
             None => return Ok(None),
         };
 
         let value = self.inner_get(&key).await?;
-        Ok(Some(SettingRef { schema, key, value }))
+        let read_only = self.inner.read_only.read().await.contains(key.as_ref());
+
+        Ok(Some(SettingRef {
+            schema,
+            key,
+            value,
+            read_only,
+        }))
     }
 
     /// Test if the given key exists in the database.
@@ -518,10 +736,135 @@ impl Settings {
         self.inner_set(key.as_ref(), value, true).await
     }
 
+    /// Insert the given setting, recording the change as performed by the
+    /// given actor.
+    pub async fn set_by<T>(&self, key: &str, value: T, actor: Actor) -> Result<(), Error>
+    where
+        T: serde::Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.set_json_by(key, value, actor).await
+    }
+
     /// Insert the given setting as raw JSON.
     pub async fn set_json(&self, key: &str, value: serde_json::Value) -> Result<(), Error> {
+        self.set_json_by(key, value, Actor::Bot).await
+    }
+
+    /// Insert the given setting as raw JSON, recording the change as performed
+    /// by the given actor.
+    pub async fn set_json_by(
+        &self,
+        key: &str,
+        value: serde_json::Value,
+        actor: Actor,
+    ) -> Result<(), Error> {
         let key = self.key(key);
-        self.inner_set_json(key.as_ref(), value, true).await
+
+        if actor.is_user() {
+            self.check_value(key.as_ref(), &value)?;
+        }
+
+        self.inner_set_json(key.as_ref(), value, true, actor).await
+    }
+
+    /// Insert all the given settings as raw JSON in a single transaction.
+    ///
+    /// Subscribers are only notified once every setting has been stored. No
+    /// settings are stored if any of them are read-only.
+    pub async fn set_all_json(
+        &self,
+        values: Vec<(String, serde_json::Value)>,
+    ) -> Result<(), Error> {
+        self.inner_set_all_json(values, Actor::Bot).await
+    }
+
+    /// Insert all the given settings as raw JSON in a single transaction,
+    /// recording the changes as performed by the given actor.
+    pub async fn set_all_json_by(
+        &self,
+        values: Vec<(String, serde_json::Value)>,
+        actor: Actor,
+    ) -> Result<(), Error> {
+        self.inner_set_all_json(values, actor).await
+    }
+
+    /// Apply the settings provided by the configuration file.
+    ///
+    /// The given keys are marked as read-only, replacing any keys which were
+    /// marked by a previous call. Settings which are no longer provided keep
+    /// their current value, but become writable again.
+    pub async fn set_config_file(
+        &self,
+        values: Vec<(String, serde_json::Value)>,
+    ) -> Result<(), Error> {
+        let keys = values
+            .iter()
+            .map(|(key, _)| self.key(key).into_owned())
+            .collect::<HashSet<_>>();
+
+        self.inner_set_all_json(values, Actor::Config).await?;
+        *self.inner.read_only.write().await = keys;
+        Ok(())
+    }
+
+    /// Inner implementation of set_all_json which records changes with the
+    /// given actor.
+    async fn inner_set_all_json(
+        &self,
+        values: Vec<(String, serde_json::Value)>,
+        actor: Actor,
+    ) -> Result<(), Error> {
+        use self::db::schema::settings::dsl;
+
+        let values = values
+            .into_iter()
+            .map(|(key, value)| {
+                let key = self.key(&key).into_owned();
+                let recorded = self.is_recorded(&key);
+                (key, value, recorded)
+            })
+            .collect::<Vec<_>>();
+
+        for (key, _, _) in &values {
+            self.check_writable(key, &actor).await?;
+        }
+
+        let values = self
+            .inner
+            .db
+            .asyncify(move |c| {
+                c.transaction(|| {
+                    for (key, value, recorded) in &values {
+                        let json = serde_json::to_string(value)?;
+
+                        if *recorded {
+                            let old = dsl::settings
+                                .select(dsl::value)
+                                .filter(dsl::key.eq(key))
+                                .first::<String>(c)
+                                .optional()?;
+
+                            record_change(c, key, old, Some(json.clone()), actor.clone())?;
+                        }
+
+                        diesel::replace_into(dsl::settings)
+                            .values((dsl::key.eq(key), dsl::value.eq(json)))
+                            .execute(c)?;
+                    }
+
+                    Ok::<_, Error>(())
+                })?;
+
+                Ok::<_, Error>(values)
+            })
+            .await?;
+
+        for (key, value, _) in values {
+            self.try_send(&key, Event::Set(value)).await;
+        }
+
+        Ok(())
     }
 
     /// Inner implementation of set_json which doesn't do key translation.
@@ -530,41 +873,53 @@ impl Settings {
         key: &str,
         value: serde_json::Value,
         notify: bool,
+        actor: Actor,
     ) -> Result<(), Error> {
         use self::db::schema::settings::dsl;
 
+        self.check_writable(key, &actor).await?;
+
         if log::log_enabled!(log::Level::Trace) {
             log::trace!("{}: Setting to {:?} (notify: {})", key, value, notify);
         }
 
+        let recorded = self.is_recorded(key);
         let key = key.to_string();
 
         let (key, value) = self
             .inner
             .db
             .asyncify(move |c| {
-                let filter = dsl::settings.filter(dsl::key.eq(&key));
+                c.transaction(|| {
+                    let filter = dsl::settings.filter(dsl::key.eq(&key));
 
-                let b = filter
-                    .clone()
-                    .select((dsl::key, dsl::value))
-                    .first::<(String, String)>(c)
-                    .optional()?;
+                    let b = filter
+                        .clone()
+                        .select(dsl::value)
+                        .first::<String>(c)
+                        .optional()?;
 
-                let json = serde_json::to_string(&value)?;
+                    let json = serde_json::to_string(&value)?;
 
-                match b {
-                    None => {
-                        diesel::insert_into(dsl::settings)
-                            .values((dsl::key.eq(&key), dsl::value.eq(json)))
-                            .execute(c)?;
+                    match b {
+                        None => {
+                            diesel::insert_into(dsl::settings)
+                                .values((dsl::key.eq(&key), dsl::value.eq(&json)))
+                                .execute(c)?;
+                        }
+                        Some(_) => {
+                            diesel::update(filter)
+                                .set((dsl::key.eq(&key), dsl::value.eq(&json)))
+                                .execute(c)?;
+                        }
                     }
-                    Some(_) => {
-                        diesel::update(filter)
-                            .set((dsl::key.eq(&key), dsl::value.eq(json)))
-                            .execute(c)?;
+
+                    if recorded {
+                        record_change(c, &key, b, Some(json), actor)?;
                     }
-                }
+
+                    Ok::<_, Error>(())
+                })?;
 
                 Ok::<_, Error>((key, value))
             })
@@ -582,6 +937,7 @@ impl Settings {
         use self::db::schema::settings::dsl;
 
         let inner = self.inner.clone();
+        let read_only = self.inner.read_only.read().await.clone();
 
         self.inner
             .db
@@ -606,6 +962,26 @@ impl Settings {
                         schema: schema.clone(),
                         key: key.to_string(),
                         value,
+                        read_only: read_only.contains(key),
+                    });
+                }
+
+                // Settings specific to a channel are only listed if they are set.
+                for (key, value) in &values {
+                    if !key.starts_with(CHANNELS_PREFIX) {
+                        continue;
+                    }
+
+                    let schema = match inner.schema.get(key) {
+                        Some(schema) => schema,
+                        None => continue,
+                    };
+
+                    settings.push(Setting {
+                        schema: schema.clone(),
+                        key: key.to_string(),
+                        value: serde_json::from_str(value)?,
+                        read_only: read_only.contains(key),
                     });
                 }
 
@@ -616,14 +992,23 @@ impl Settings {
 
     /// Clear the given setting. Returning `true` if it was removed.
     pub async fn clear(&self, key: &str) -> Result<bool, Error> {
+        self.clear_by(key, Actor::Bot).await
+    }
+
+    /// Clear the given setting, recording the change as performed by the
+    /// given actor. Returning `true` if it was removed.
+    pub async fn clear_by(&self, key: &str, actor: Actor) -> Result<bool, Error> {
         let key = self.key(key);
-        self.inner_clear(&key).await
+        self.inner_clear(&key, actor).await
     }
 
     /// Perform an inner clear of the given key.
-    async fn inner_clear(&self, key: &str) -> Result<bool, Error> {
+    async fn inner_clear(&self, key: &str, actor: Actor) -> Result<bool, Error> {
         use self::db::schema::settings::dsl;
 
+        self.check_writable(key, &actor).await?;
+
+        let recorded = self.is_recorded(key);
         let key = key.to_string();
 
         self.try_send(&key, Event::Clear).await;
@@ -631,12 +1016,139 @@ impl Settings {
         self.inner
             .db
             .asyncify(move |c| {
-                let count = diesel::delete(dsl::settings.filter(dsl::key.eq(key))).execute(c)?;
-                Ok(count == 1)
+                c.transaction(|| {
+                    let filter = dsl::settings.filter(dsl::key.eq(&key));
+
+                    let old = filter
+                        .clone()
+                        .select(dsl::value)
+                        .first::<String>(c)
+                        .optional()?;
+
+                    let count = diesel::delete(filter).execute(c)?;
+
+                    if recorded {
+                        record_change(c, &key, old, None, actor)?;
+                    }
+
+                    Ok(count == 1)
+                })
+            })
+            .await
+    }
+
+    /// List recorded changes to settings, newest first.
+    ///
+    /// If `key` is specified, only changes to that setting are listed.
+    pub async fn history(&self, key: Option<&str>, limit: i64) -> Result<Vec<Change>, Error> {
+        use self::db::schema::settings_history::dsl;
+
+        let key = key.map(|key| self.key(key).into_owned());
+
+        self.inner
+            .db
+            .asyncify(move |c| {
+                let mut query = dsl::settings_history
+                    .order((dsl::changed_at.desc(), dsl::id.desc()))
+                    .limit(limit)
+                    .into_boxed();
+
+                if let Some(key) = key {
+                    query = query.filter(dsl::key.eq(key));
+                }
+
+                query
+                    .load::<db::models::SettingsChange>(c)?
+                    .into_iter()
+                    .map(Change::from_model)
+                    .collect()
             })
             .await
     }
 
+    /// Revert the recorded change with the given id, restoring the value the
+    /// setting had before it.
+    ///
+    /// The revert is itself recorded as a change by the given actor. Returns
+    /// the reverted change, or `None` if there is no such change.
+    pub async fn revert(&self, id: i32, actor: Actor) -> Result<Option<Change>, Error> {
+        use self::db::schema::settings_history::dsl;
+
+        let change = self
+            .inner
+            .db
+            .asyncify(move |c| {
+                dsl::settings_history
+                    .filter(dsl::id.eq(id))
+                    .first::<db::models::SettingsChange>(c)
+                    .optional()?
+                    .map(Change::from_model)
+                    .transpose()
+            })
+            .await?;
+
+        let change = match change {
+            Some(change) => change,
+            None => return Ok(None),
+        };
+
+        // NB: recorded keys are absolute, so go through unscoped settings.
+        let settings = Settings {
+            scope: String::new(),
+            inner: self.inner.clone(),
+        };
+
+        match change.old_value.clone() {
+            Some(value) => {
+                settings.set_json_by(&change.key, value, actor).await?;
+            }
+            None => {
+                settings.clear_by(&change.key, actor).await?;
+            }
+        }
+
+        Ok(Some(change))
+    }
+
+    /// Test that the given key can be modified by the given actor.
+    ///
+    /// Read-only keys can only be modified by the configuration file.
+    async fn check_writable(&self, key: &str, actor: &Actor) -> Result<(), Error> {
+        if *actor != Actor::Config && self.inner.read_only.read().await.contains(key) {
+            return Err(Error::ReadOnly(key.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that the given value matches the schema of the given key.
+    fn check_value(&self, key: &str, value: &serde_json::Value) -> Result<(), Error> {
+        match self.inner.schema.get(key) {
+            Some(schema) if !schema.ty.is_compatible_with_json(value) => {
+                Err(Error::ExpectedType(schema.ty.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Test if changes to the given key should be recorded in the settings
+    /// history.
+    ///
+    /// Secrets are never recorded, since the history is visible to anyone
+    /// with access to the web interface.
+    fn is_recorded(&self, key: &str) -> bool {
+        let (_, rest) = split_channel(key);
+
+        if rest.starts_with("secrets/") {
+            return false;
+        }
+
+        match self.inner.schema.get(key) {
+            Some(schema) => !schema.secret,
+            None => true,
+        }
+    }
+
     /// Create a scoped setting.
     pub fn scoped(&self, s: &str) -> Settings {
         let mut scope = self.scope.clone();
@@ -807,7 +1319,7 @@ impl Settings {
         T: serde::Serialize,
     {
         let value = serde_json::to_value(value)?;
-        self.inner_set_json(key, value, notify).await
+        self.inner_set_json(key, value, notify, Actor::Bot).await
     }
 
     /// Subscribe for events on the given key.
@@ -1333,3 +1845,24 @@ impl fmt::Display for Type {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_channel;
+
+    #[test]
+    fn test_split_channel() {
+        assert_eq!(
+            ("", "chat/join-message"),
+            split_channel("chat/join-message")
+        );
+        assert_eq!(
+            ("channels/setbac/", "chat/join-message"),
+            split_channel("channels/setbac/chat/join-message")
+        );
+        assert_eq!(("channels/setbac/", ""), split_channel("channels/setbac/"));
+        // NB: a key without a channel name is not channel-specific.
+        assert_eq!(("", "channels/setbac"), split_channel("channels/setbac"));
+        assert_eq!(("", "channels"), split_channel("channels"));
+    }
+}
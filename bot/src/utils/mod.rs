@@ -2,6 +2,7 @@ use crate::api;
 use crate::prelude::*;
 use percent_encoding::PercentDecode;
 use std::borrow::Cow;
+use std::collections::hash_map;
 use std::fmt;
 use std::mem;
 use std::ops;
@@ -683,6 +684,72 @@ impl Cooldown {
     }
 }
 
+/// Get the cooldown in the given entry, making sure that its duration is up
+/// to date.
+pub fn update_cooldown<K>(
+    entry: hash_map::Entry<'_, K, Cooldown>,
+    duration: Duration,
+) -> &mut Cooldown {
+    match entry {
+        hash_map::Entry::Vacant(e) => e.insert(Cooldown::from_duration(duration)),
+        hash_map::Entry::Occupied(e) => {
+            let cooldown = e.into_mut();
+
+            if cooldown.cooldown != duration {
+                cooldown.cooldown = duration;
+            }
+
+            cooldown
+        }
+    }
+}
+
+/// A group of cooldowns which are checked together.
+///
+/// If none of the checked cooldowns are in effect, every cooldown in the
+/// group is poked.
+#[derive(Default)]
+pub struct Cooldowns<'a> {
+    checked: smallvec::SmallVec<[(&'static str, &'a mut Cooldown); 4]>,
+    poked: smallvec::SmallVec<[&'a mut Cooldown; 4]>,
+}
+
+impl<'a> Cooldowns<'a> {
+    /// Check the given cooldown, naming it `what` if it's in effect.
+    pub fn check(&mut self, what: &'static str, cooldown: &'a mut Cooldown) {
+        self.checked.push((what, cooldown));
+    }
+
+    /// Poke the given cooldown without checking it.
+    pub fn poke(&mut self, cooldown: &'a mut Cooldown) {
+        self.poked.push(cooldown);
+    }
+
+    /// Get the longest cooldown in effect, or poke all cooldowns if there are
+    /// none.
+    pub fn finish(mut self, now: time::Instant) -> Option<(&'static str, time::Duration)> {
+        let remaining = self
+            .checked
+            .iter_mut()
+            .flat_map(|(what, c)| c.check(now).map(|d| (*what, d)))
+            .max_by_key(|(_, d)| *d);
+
+        if remaining.is_some() {
+            return remaining;
+        }
+
+        for (_, cooldown) in self.checked {
+            cooldown.poke(now);
+        }
+
+        for cooldown in self.poked {
+            cooldown.poke(now);
+        }
+
+        None
+    }
+}
+
 impl serde::Serialize for Cooldown {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -842,7 +909,8 @@ impl serde::Serialize for PtDuration {
 
 #[cfg(test)]
 mod tests {
-    use super::{Offset, TrimmedWords, Urls, Words};
+    use super::{Cooldown, Cooldowns, Duration, Offset, TrimmedWords, Urls, Words};
+    use std::time;
 
     #[test]
     pub fn test_offset() -> Result<(), anyhow::Error> {
@@ -918,4 +986,26 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    pub fn test_cooldowns() {
+        let now = time::Instant::now();
+        let mut short = Cooldown::from_duration(Duration::seconds(10));
+        let mut long = Cooldown::from_duration(Duration::seconds(60));
+
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.check("Short", &mut short);
+        cooldowns.poke(&mut long);
+        assert_eq!(cooldowns.finish(now), None);
+
+        // NB: the longest cooldown in effect is reported.
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.check("Short", &mut short);
+        cooldowns.check("Long", &mut long);
+
+        assert_eq!(
+            cooldowns.finish(now + time::Duration::from_secs(5)),
+            Some(("Long", time::Duration::from_secs(55)))
+        );
+    }
 }
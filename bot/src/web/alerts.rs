@@ -0,0 +1,40 @@
+use crate::alerts;
+use crate::injector;
+use crate::web::EMPTY;
+use anyhow::{bail, Result};
+use warp::body;
+use warp::filters;
+use warp::Filter as _;
+
+/// Alert endpoints.
+#[derive(Clone)]
+pub struct Alerts(injector::Var<Option<alerts::Alerts>>);
+
+impl Alerts {
+    pub fn route(
+        alerts: injector::Var<Option<alerts::Alerts>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Alerts(alerts);
+
+        let test = warp::post()
+            .and(warp::path("test").and(body::json()).and_then({
+                move |alert: alerts::Alert| {
+                    let api = api.clone();
+                    async move { api.test(alert).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("alerts").and(test).boxed()
+    }
+
+    /// Queue up a test alert.
+    async fn test(&self, alert: alerts::Alert) -> Result<impl warp::Reply> {
+        match self.0.load().await {
+            Some(alerts) => alerts.push(alert)?,
+            None => bail!("alerts not configured"),
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
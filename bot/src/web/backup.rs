@@ -0,0 +1,156 @@
+use crate::auth;
+use crate::backup;
+use crate::db;
+use crate::injector;
+use crate::settings;
+use anyhow::{bail, Result};
+use warp::body;
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+#[derive(serde::Deserialize)]
+struct ExportRequest {
+    /// Include secrets in the export.
+    ///
+    /// This requires an admin token and a passphrase to encrypt them with.
+    #[serde(default)]
+    secrets: bool,
+    #[serde(default)]
+    passphrase: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ImportRequest {
+    archive: backup::Archive,
+    #[serde(default)]
+    passphrase: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ImportResponse {
+    /// Some parts of the configuration, like grants, bad words and the song
+    /// queue are only loaded when the bot starts.
+    restart_required: bool,
+}
+
+/// Backup endpoints.
+#[derive(Clone)]
+pub struct Backup {
+    injector: injector::Injector,
+    db: injector::Var<Option<db::Database>>,
+    settings: injector::Var<Option<settings::Settings>>,
+}
+
+impl Backup {
+    pub fn route(
+        injector: injector::Injector,
+        db: injector::Var<Option<db::Database>>,
+        settings: injector::Var<Option<settings::Settings>>,
+        tokens: injector::Var<Option<db::ApiTokens>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Backup {
+            injector,
+            db,
+            settings,
+        };
+
+        let export = warp::post()
+            .and(
+                path!("export")
+                    .and(super::token_scopes(tokens))
+                    .and(body::json())
+                    .and_then({
+                        let api = api.clone();
+                        move |scopes: Option<Vec<auth::Scope>>, request: ExportRequest| {
+                            let api = api.clone();
+
+                            async move {
+                                // NB: secrets are only handed out to admins,
+                                // regardless of whether tokens are required.
+                                if request.secrets {
+                                    super::check_scope(scopes, auth::Scope::WebAdmin)?;
+                                }
+
+                                api.export(request).await.map_err(super::custom_reject)
+                            }
+                        }
+                    }),
+            )
+            .boxed();
+
+        let import = warp::post()
+            .and(path!("import").and(body::json()).and_then({
+                move |request: ImportRequest| {
+                    let api = api.clone();
+                    async move { api.import(request).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("backup").and(export.or(import)).boxed()
+    }
+
+    /// Access the database and settings.
+    async fn parts(&self) -> Result<(db::Database, settings::Settings)> {
+        match (self.db.load().await, self.settings.load().await) {
+            (Some(db), Some(settings)) => Ok((db, settings)),
+            _ => bail!("backups not available"),
+        }
+    }
+
+    /// Export the configuration of the bot.
+    async fn export(&self, request: ExportRequest) -> Result<impl warp::Reply> {
+        let passphrase = request.passphrase.as_deref().filter(|p| !p.is_empty());
+
+        if request.secrets && passphrase.is_none() {
+            bail!("a passphrase is required to export secrets");
+        }
+
+        let (db, settings) = self.parts().await?;
+
+        let options = backup::ExportOptions {
+            secrets: request.secrets,
+            passphrase,
+        };
+
+        let archive = backup::export(&db, &settings, options).await?;
+        Ok(warp::reply::json(&archive))
+    }
+
+    /// Import the configuration of the bot.
+    async fn import(&self, request: ImportRequest) -> Result<impl warp::Reply> {
+        let (db, settings) = self.parts().await?;
+
+        let passphrase = request.passphrase.as_deref().filter(|p| !p.is_empty());
+        backup::import(
+            &db,
+            &settings,
+            request.archive,
+            passphrase,
+            settings::Actor::Web,
+        )
+        .await?;
+
+        // NB: reload everything which is read through the injector.
+        self.injector
+            .update(db::AfterStreams::load(db.clone()).await?)
+            .await;
+        self.injector
+            .update(db::Commands::load(db.clone()).await?)
+            .await;
+        self.injector
+            .update(db::Aliases::load(db.clone()).await?)
+            .await;
+        self.injector
+            .update(db::Promotions::load(db.clone()).await?)
+            .await;
+        self.injector
+            .update(db::Themes::load(db.clone()).await?)
+            .await;
+
+        Ok(warp::reply::json(&ImportResponse {
+            restart_required: true,
+        }))
+    }
+}
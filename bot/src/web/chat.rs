@@ -1,29 +1,62 @@
 use crate::bus;
+use crate::db;
+use crate::injector;
 use crate::message_log;
 use crate::web::EMPTY;
+use anyhow::bail;
 use std::sync::Arc;
+use tokio::sync::RwLockReadGuard;
 use warp::filters;
 use warp::path;
 use warp::Filter as _;
 
+/// Default number of messages to return from the durable chat log.
+const DEFAULT_LIMIT: i64 = 50;
+/// Maximum number of messages to return from the durable chat log.
+const MAX_LIMIT: i64 = 1000;
+
 #[derive(serde::Deserialize)]
 struct CommandQuery {
     command: String,
 }
 
+#[derive(serde::Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    q: Option<String>,
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
+#[derive(serde::Deserialize)]
+struct LimitQuery {
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
 /// Chat endpoint.
 #[derive(Clone)]
 pub struct Chat {
     bus: Arc<bus::Bus<bus::Command>>,
     message_log: message_log::MessageLog,
+    chat_messages: injector::Var<Option<db::ChatMessages>>,
 }
 
 impl Chat {
     pub fn route(
         bus: Arc<bus::Bus<bus::Command>>,
         message_log: message_log::MessageLog,
+        chat_messages: injector::Var<Option<db::ChatMessages>>,
     ) -> filters::BoxedFilter<(impl warp::Reply,)> {
-        let api = Self { bus, message_log };
+        let api = Self {
+            bus,
+            message_log,
+            chat_messages,
+        };
 
         let command = warp::get()
             .and(warp::path("command").and(warp::query::<CommandQuery>()))
@@ -36,6 +69,30 @@ impl Chat {
             })
             .boxed();
 
+        let search = warp::get()
+            .and(warp::path("search").and(path::end()))
+            .and(warp::query::<SearchQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: SearchQuery| {
+                    let api = api.clone();
+                    async move { api.search(query).await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
+        let user = warp::get()
+            .and(path!("user" / String).and(path::end()))
+            .and(warp::query::<LimitQuery>())
+            .and_then({
+                let api = api.clone();
+                move |user: String, query: LimitQuery| {
+                    let api = api.clone();
+                    async move { api.user(user, query).await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
         let messages = warp::get()
             .and(warp::path("messages").and(path::end()))
             .and_then({
@@ -46,7 +103,9 @@ impl Chat {
             })
             .boxed();
 
-        warp::path("chat").and(command.or(messages)).boxed()
+        warp::path("chat")
+            .and(command.or(search).or(user).or(messages))
+            .boxed()
     }
 
     /// Run a command.
@@ -65,4 +124,52 @@ impl Chat {
         let messages = self.message_log.messages().await;
         Ok(warp::reply::json(&*messages))
     }
+
+    /// Access the durable chat log.
+    async fn chat_messages(&self) -> anyhow::Result<RwLockReadGuard<'_, db::ChatMessages>> {
+        match RwLockReadGuard::try_map(self.chat_messages.read().await, |c| c.as_ref()) {
+            Ok(out) => Ok(out),
+            Err(_) => bail!("chat log not configured"),
+        }
+    }
+
+    /// Search the durable chat log by channel, user and phrase.
+    async fn search(&self, query: SearchQuery) -> Result<impl warp::Reply, anyhow::Error> {
+        // NB: channels are stored with their leading `#`.
+        let channel = query
+            .channel
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .map(|c| format!("#{}", c.trim_start_matches('#')));
+        let user = query.user.as_deref().filter(|u| !u.is_empty());
+        let phrase = query.q.as_deref().filter(|q| !q.is_empty());
+
+        let messages = self
+            .chat_messages()
+            .await?
+            .search(channel.as_deref(), user, phrase, limit(query.limit))
+            .await?;
+
+        Ok(warp::reply::json(&messages))
+    }
+
+    /// Get the last messages by the given user from the durable chat log.
+    async fn user(
+        &self,
+        user: String,
+        query: LimitQuery,
+    ) -> Result<impl warp::Reply, anyhow::Error> {
+        let messages = self
+            .chat_messages()
+            .await?
+            .last_by_user(&user, limit(query.limit))
+            .await?;
+
+        Ok(warp::reply::json(&messages))
+    }
+}
+
+/// Clamp the requested number of messages.
+fn limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_LIMIT).max(1).min(MAX_LIMIT)
 }
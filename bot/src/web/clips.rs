@@ -0,0 +1,63 @@
+use crate::db;
+use crate::injector;
+use crate::web::EMPTY;
+use anyhow::{bail, Result};
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+/// How many clips are listed.
+const LIMIT: i64 = 100;
+
+/// Clips endpoint.
+#[derive(Clone)]
+pub struct Clips(injector::Var<Option<db::Clips>>);
+
+impl Clips {
+    pub fn route(
+        clips: injector::Var<Option<db::Clips>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Clips(clips);
+
+        let list = warp::get()
+            .and(path::end().and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let delete = warp::delete()
+            .and(path!(i32).and_then({
+                move |id: i32| {
+                    let api = api.clone();
+                    async move { api.delete(id).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("clips").and(list.or(delete)).boxed()
+    }
+
+    /// Access the underlying clips.
+    async fn clips(&self) -> Result<db::Clips> {
+        match self.0.load().await {
+            Some(clips) => Ok(clips),
+            None => bail!("clips not configured"),
+        }
+    }
+
+    /// List the most recent clips.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let clips = self.clips().await?.list(LIMIT).await?;
+        Ok(warp::reply::json(&clips))
+    }
+
+    /// Delete the clip with the given id.
+    async fn delete(&self, id: i32) -> Result<impl warp::Reply> {
+        self.clips().await?.delete(id).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
@@ -0,0 +1,89 @@
+use crate::injector;
+use crate::module::gtav;
+use anyhow::{bail, Result};
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+/// Result of vetoing queued effects.
+#[derive(Debug, serde::Serialize)]
+struct Vetoed {
+    vetoed: usize,
+}
+
+/// GTA V effect queue endpoints.
+#[derive(Clone)]
+pub struct Gtav(injector::Var<Option<gtav::Queue>>);
+
+impl Gtav {
+    pub fn route(
+        queue: injector::Var<Option<gtav::Queue>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Gtav(queue);
+
+        let list = warp::get()
+            .and(path::end().and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let veto = warp::delete()
+            .and(path!(usize).and_then({
+                let api = api.clone();
+                move |id: usize| {
+                    let api = api.clone();
+                    async move { api.veto(id).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let clear = warp::delete()
+            .and(path::end().and_then({
+                move || {
+                    let api = api.clone();
+                    async move { api.clear().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("gtav")
+            .and(warp::path("queue"))
+            .and(list.or(veto).or(clear))
+            .boxed()
+    }
+
+    /// Access the underlying queue.
+    async fn queue(&self) -> Result<gtav::Queue> {
+        match self.0.load().await {
+            Some(queue) => Ok(queue),
+            None => bail!("gtav not configured"),
+        }
+    }
+
+    /// List pending and active effects.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let state = self.queue().await?.state();
+        Ok(warp::reply::json(&state))
+    }
+
+    /// Veto the queued effect with the given id.
+    async fn veto(&self, id: usize) -> Result<impl warp::Reply> {
+        let vetoed = if self.queue().await?.veto(id).await? {
+            1
+        } else {
+            0
+        };
+
+        Ok(warp::reply::json(&Vetoed { vetoed }))
+    }
+
+    /// Veto all queued effects.
+    async fn clear(&self) -> Result<impl warp::Reply> {
+        let vetoed = self.queue().await?.clear().await?;
+        Ok(warp::reply::json(&Vetoed { vetoed }))
+    }
+}
@@ -21,11 +21,21 @@ use std::sync::Arc;
 use tokio::sync::{RwLock, RwLockReadGuard};
 use warp::{body, filters, path, Filter as _};
 
+mod alerts;
+mod backup;
 mod cache;
 mod chat;
+mod clips;
+mod gtav;
+mod profiles;
+mod schedule;
 mod settings;
+mod tokens;
 
-use self::{cache::Cache, chat::Chat, settings::Settings};
+use self::{
+    alerts::Alerts, backup::Backup, cache::Cache, chat::Chat, clips::Clips, gtav::Gtav,
+    profiles::Profiles, schedule::Schedule, settings::Settings, tokens::Tokens,
+};
 
 pub const URL: &str = "http://localhost:12345";
 
@@ -44,6 +54,160 @@ pub(crate) fn custom_reject(error: impl Into<anyhow::Error>) -> warp::Rejection
     warp::reject::custom(CustomReject(error.into()))
 }
 
+/// Rejection for requests without a token granting the required scope.
+#[derive(Debug)]
+enum Unauthorized {
+    /// No valid token was provided.
+    MissingToken,
+    /// The token doesn't grant the required scope.
+    MissingScope(auth::Scope),
+}
+
+impl warp::reject::Reject for Unauthorized {}
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Unauthorized::MissingToken => "missing or invalid API token".fmt(fmt),
+            Unauthorized::MissingScope(scope) => {
+                write!(fmt, "API token doesn't grant the `{}` scope", scope)
+            }
+        }
+    }
+}
+
+/// Query parameters used to pass a token to websockets.
+#[derive(serde::Deserialize)]
+struct TokenQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Filter which extracts the scopes granted by the token passed in the
+/// request, if any.
+///
+/// Tokens are passed in the `Authorization: Bearer <token>` header, or in the
+/// `token` query parameter.
+fn token_scopes(
+    tokens: injector::Var<Option<db::ApiTokens>>,
+) -> filters::BoxedFilter<(Option<Vec<auth::Scope>>,)> {
+    warp::header::optional::<String>("authorization")
+        .and(warp::query::<TokenQuery>())
+        .and_then(move |authorization: Option<String>, query: TokenQuery| {
+            let tokens = tokens.clone();
+
+            async move {
+                let token = authorization
+                    .as_deref()
+                    .and_then(|a| a.strip_prefix("Bearer "))
+                    .map(|a| a.trim().to_string())
+                    .or(query.token);
+
+                let scopes = match (token, tokens.read().await.as_ref()) {
+                    (Some(token), Some(tokens)) => tokens.scopes(&token).await,
+                    _ => None,
+                };
+
+                Ok::<_, warp::Rejection>(scopes)
+            }
+        })
+        .boxed()
+}
+
+/// Check that the given scopes grant access to the given scope.
+fn check_scope(
+    scopes: Option<Vec<auth::Scope>>,
+    scope: auth::Scope,
+) -> Result<(), warp::Rejection> {
+    let scopes = scopes.ok_or_else(|| warp::reject::custom(Unauthorized::MissingToken))?;
+
+    if !scopes.contains(&scope) && !scopes.contains(&auth::Scope::WebAdmin) {
+        return Err(warp::reject::custom(Unauthorized::MissingScope(scope)));
+    }
+
+    Ok(())
+}
+
+/// Filter which requires a token granting the scope needed by the request, if
+/// tokens are required.
+fn require_token(
+    tokens: injector::Var<Option<db::ApiTokens>>,
+    require_token: crate::settings::Var<bool>,
+) -> filters::BoxedFilter<()> {
+    warp::method()
+        .and(path::full())
+        .and(token_scopes(tokens))
+        .and_then(
+            move |method: warp::http::Method,
+                  full: path::FullPath,
+                  scopes: Option<Vec<auth::Scope>>| {
+                let require_token = require_token.clone();
+
+                async move {
+                    if !require_token.load().await {
+                        return Ok(());
+                    }
+
+                    // NB: this is the redirect target when connecting to
+                    // setbac.tv, which can't carry a token.
+                    if full.as_str().trim_start_matches('/') == "api/auth/key" {
+                        return Ok(());
+                    }
+
+                    check_scope(scopes, required_scope(&method, full.as_str()))
+                }
+            },
+        )
+        .untuple_one()
+        .boxed()
+}
+
+/// Get the scope a token must grant to access the given path.
+fn required_scope(method: &warp::http::Method, path: &str) -> auth::Scope {
+    /// Paths which can be used to read or modify sensitive information.
+    const SENSITIVE: &[&str] = &["api/auth", "api/backup", "api/settings", "api/tokens"];
+
+    /// Paths which have a dedicated scope for modifications.
+    const SCOPED: &[(&str, auth::Scope)] = &[
+        ("api/after-stream", auth::Scope::WebAfterStreams),
+        ("api/alerts", auth::Scope::WebAlerts),
+        ("api/aliases", auth::Scope::WebCommands),
+        ("api/balances", auth::Scope::WebCurrency),
+        ("api/clips", auth::Scope::WebClips),
+        ("api/commands", auth::Scope::WebCommands),
+        ("api/device", auth::Scope::WebSong),
+        ("api/gtav", auth::Scope::WebGtav),
+        ("api/profiles", auth::Scope::WebProfiles),
+        ("api/promotions", auth::Scope::WebCommands),
+        ("api/themes", auth::Scope::WebCommands),
+    ];
+
+    let path = path.trim_start_matches('/');
+
+    let matches = |prefix: &str| match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    };
+
+    if path == "api/chat/command" {
+        return auth::Scope::WebCommand;
+    }
+
+    if SENSITIVE.iter().any(|p| matches(p)) {
+        return auth::Scope::WebAdmin;
+    }
+
+    if *method == warp::http::Method::GET {
+        return auth::Scope::WebRead;
+    }
+
+    SCOPED
+        .iter()
+        .find(|(p, _)| matches(p))
+        .map(|(_, scope)| *scope)
+        .unwrap_or(auth::Scope::WebAdmin)
+}
+
 #[derive(Debug)]
 enum Error {
     BadRequest,
@@ -596,12 +760,47 @@ struct Auth {
     settings: injector::Var<Option<crate::settings::Settings>>,
 }
 
+/// A grant of a scope. Temporary grants have an id, and when they expire.
+#[derive(serde::Serialize)]
+struct Grant {
+    scope: auth::Scope,
+    principal: auth::RoleOrUser,
+    /// Identifier of a temporary grant, used to revoke it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Time remaining until a temporary grant expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining: Option<utils::Duration>,
+}
+
 #[derive(serde::Deserialize)]
 pub struct AuthKeyQuery {
     #[serde(default)]
     key: Option<Fragment>,
 }
 
+/// Query for explaining why a user is allowed or denied a scope.
+#[derive(serde::Deserialize)]
+pub struct ExplainQuery {
+    user: String,
+    scope: auth::Scope,
+    /// Comma-separated built-in roles to assume the user has, like
+    /// `@moderator,@subscriber`, since they are only known in chat.
+    #[serde(default)]
+    roles: Option<String>,
+}
+
+/// Request to create or update a custom role.
+#[derive(serde::Deserialize)]
+pub struct PutRole {
+    #[serde(default)]
+    doc: Option<String>,
+    #[serde(default)]
+    inherits: Vec<auth::Role>,
+}
+
 impl Auth {
     fn route(
         auth: auth::Auth,
@@ -632,7 +831,68 @@ impl Auth {
                     let api = api.clone();
                     move || {
                         let api = api.clone();
-                        async move { api.roles().map_err(custom_reject) }
+                        async move { api.roles().await.map_err(custom_reject) }
+                    }
+                }))
+            .boxed();
+
+        let route = route
+            .or(warp::put()
+                .and(warp::path!("roles" / Fragment).and(path::end()))
+                .and(body::json())
+                .and_then({
+                    let api = api.clone();
+                    move |role: Fragment, body: PutRole| {
+                        let api = api.clone();
+                        async move {
+                            api.put_role(role.as_str(), body)
+                                .await
+                                .map_err(custom_reject)
+                        }
+                    }
+                }))
+            .boxed();
+
+        let route = route
+            .or(warp::delete()
+                .and(warp::path!("roles" / Fragment).and(path::end()))
+                .and_then({
+                    let api = api.clone();
+                    move |role: Fragment| {
+                        let api = api.clone();
+                        async move { api.delete_role(role.as_str()).await.map_err(custom_reject) }
+                    }
+                }))
+            .boxed();
+
+        let route = route
+            .or(warp::put()
+                .and(warp::path!("roles" / Fragment / "members" / Fragment).and(path::end()))
+                .and_then({
+                    let api = api.clone();
+                    move |role: Fragment, user: Fragment| {
+                        let api = api.clone();
+                        async move {
+                            api.insert_member(role.as_str(), user.as_str())
+                                .await
+                                .map_err(custom_reject)
+                        }
+                    }
+                }))
+            .boxed();
+
+        let route = route
+            .or(warp::delete()
+                .and(warp::path!("roles" / Fragment / "members" / Fragment).and(path::end()))
+                .and_then({
+                    let api = api.clone();
+                    move |role: Fragment, user: Fragment| {
+                        let api = api.clone();
+                        async move {
+                            api.delete_member(role.as_str(), user.as_str())
+                                .await
+                                .map_err(custom_reject)
+                        }
                     }
                 }))
             .boxed();
@@ -661,6 +921,18 @@ impl Auth {
                 }))
             .boxed();
 
+        let route = route
+            .or(warp::delete()
+                .and(warp::path!("grants" / i32).and(path::end()))
+                .and_then({
+                    let api = api.clone();
+                    move |id: i32| {
+                        let api = api.clone();
+                        async move { api.delete_temporary_grant(id).await.map_err(custom_reject) }
+                    }
+                }))
+            .boxed();
+
         let route = route
             .or(warp::put()
                 .and(warp::path!("grants").and(path::end()))
@@ -694,6 +966,22 @@ impl Auth {
                 }))
             .boxed();
 
+        let route = route
+            .or(warp::get()
+                .and(
+                    warp::path!("explain")
+                        .and(warp::query::<ExplainQuery>())
+                        .and(path::end()),
+                )
+                .and_then({
+                    let api = api.clone();
+                    move |query: ExplainQuery| {
+                        let api = api.clone();
+                        async move { api.explain(query).await.map_err(custom_reject) }
+                    }
+                }))
+            .boxed();
+
         let route = route
             .or(warp::get()
                 .and(
@@ -738,15 +1026,104 @@ impl Auth {
     }
 
     /// Get the list of all roles.
-    fn roles(&self) -> Result<impl warp::Reply> {
-        let roles = self.auth.roles();
+    async fn roles(&self) -> Result<impl warp::Reply> {
+        let roles = self.auth.roles().await;
         Ok(warp::reply::json(&roles))
     }
 
-    /// Get the list of all auth in the bot.
+    /// Explain why a user is allowed or denied a scope.
+    async fn explain(&self, query: ExplainQuery) -> Result<impl warp::Reply> {
+        if query.scope == auth::Scope::Unknown {
+            bail!("no such scope");
+        }
+
+        let mut roles = vec![auth::Role::Everyone];
+
+        let assumed = query
+            .roles
+            .iter()
+            .flat_map(|r| r.split(','))
+            .map(str::trim)
+            .filter(|r| !r.is_empty());
+
+        for role in assumed {
+            match str::parse::<auth::Role>(role)? {
+                auth::Role::Unknown => bail!("no such role: {}", role),
+                role => roles.push(role),
+            }
+        }
+
+        let explanation = self.auth.explain(query.scope, &query.user, roles).await;
+        Ok(warp::reply::json(&explanation))
+    }
+
+    /// Create or update a custom role.
+    async fn put_role(&self, role: &str, body: PutRole) -> Result<impl warp::Reply> {
+        let role = str::parse(role)?;
+        self.auth.put_role(role, body.doc, body.inherits).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete a custom role.
+    async fn delete_role(&self, role: &str) -> Result<impl warp::Reply> {
+        let role = str::parse(role)?;
+
+        if !self.auth.delete_role(role).await? {
+            bail!("no such custom role");
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Add a member to a custom role.
+    async fn insert_member(&self, role: &str, user: &str) -> Result<impl warp::Reply> {
+        let role = str::parse(role)?;
+        self.auth.insert_member(role, user).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Remove a member from a custom role.
+    async fn delete_member(&self, role: &str, user: &str) -> Result<impl warp::Reply> {
+        let role = str::parse(role)?;
+        self.auth.delete_member(role, user).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Get the list of all grants in the bot, including temporary grants
+    /// which haven't expired.
     async fn grants(&self) -> Result<impl warp::Reply> {
-        let auth = self.auth.list().await;
-        Ok(warp::reply::json(&auth))
+        let mut grants = Vec::new();
+
+        for (scope, role) in self.auth.list().await {
+            grants.push(Grant {
+                scope,
+                principal: auth::RoleOrUser::Role(role),
+                id: None,
+                expires_at: None,
+                remaining: None,
+            });
+        }
+
+        for g in self.auth.temporary_grants().await {
+            grants.push(Grant {
+                scope: g.scope,
+                principal: g.principal,
+                id: Some(g.id),
+                expires_at: Some(g.expires_at),
+                remaining: Some(g.remaining),
+            });
+        }
+
+        Ok(warp::reply::json(&grants))
+    }
+
+    /// Revoke a temporary grant.
+    async fn delete_temporary_grant(&self, id: i32) -> Result<impl warp::Reply> {
+        if !self.auth.delete_temporary(id).await? {
+            bail!("no such temporary grant");
+        }
+
+        Ok(warp::reply::json(&EMPTY))
     }
 
     /// Delete a single scope assignment.
@@ -981,9 +1358,15 @@ pub async fn setup(
     auth: auth::Auth,
     channel: injector::Var<Option<String>>,
     latest: injector::Var<Option<api::github::Release>>,
+    settings: crate::settings::Settings,
 ) -> Result<(Server, impl Future<Output = ()>)> {
     let addr: SocketAddr = str::parse("0.0.0.0:12345")?;
 
+    let require_token = require_token(
+        injector.var().await?,
+        settings.var("web/require-token", false).await?,
+    );
+
     let player = injector::Var::new(None);
     let active_connections: Arc<RwLock<HashMap<String, ConnectionMeta>>> = Default::default();
 
@@ -1088,7 +1471,19 @@ pub async fn setup(
         let route = route.or(Themes::route(injector.var().await?));
         let route = route.or(Settings::route(injector.var().await?));
         let route = route.or(Cache::route(injector.var().await?));
-        let route = route.or(Chat::route(command_bus, message_log));
+        let route = route.or(Chat::route(command_bus, message_log, injector.var().await?));
+        let route = route.or(Alerts::route(injector.var().await?));
+        let route = route.or(Profiles::route(injector.var().await?));
+        let route = route.or(Gtav::route(injector.var().await?));
+        let route = route.or(Clips::route(injector.var().await?));
+        let route = route.or(Schedule::route(injector.var().await?));
+        let route = route.or(Tokens::route(injector.var().await?));
+        let route = route.or(Backup::route(
+            injector.clone(),
+            injector.var().await?,
+            injector.var().await?,
+            injector.var().await?,
+        ));
 
         // TODO: move endpoint into abstraction thingie.
         let route = route
@@ -1106,19 +1501,22 @@ pub async fn setup(
             )
             .boxed();
 
-        warp::path("api").and(route)
+        warp::path("api").and(require_token.clone()).and(route)
     };
 
     let ws_messages = warp::get()
         .and(warp::path!("ws" / "messages"))
+        .and(require_token.clone())
         .and(send_bus(message_bus).recover(recover));
 
     let ws_overlay = warp::get()
         .and(warp::path!("ws" / "overlay"))
+        .and(require_token.clone())
         .and(send_bus(global_bus).recover(recover));
 
     let ws_youtube = warp::get()
         .and(warp::path!("ws" / "youtube"))
+        .and(require_token)
         .and(send_bus(youtube_bus).recover(recover));
 
     let routes = api.recover(recover);
@@ -1213,6 +1611,20 @@ impl<'de> serde::Deserialize<'de> for Fragment {
 // This function receives a `Rejection` and tries to return a custom
 // value, othewise simply passes the rejection along.
 async fn recover(err: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Some(e) = err.find::<Unauthorized>() {
+        let code = match *e {
+            Unauthorized::MissingToken => warp::http::StatusCode::UNAUTHORIZED,
+            Unauthorized::MissingScope(..) => warp::http::StatusCode::FORBIDDEN,
+        };
+
+        let json = warp::reply::json(&ErrorMessage {
+            code: code.as_u16(),
+            message: e.to_string(),
+        });
+
+        return Ok(warp::reply::with_status(json, code));
+    }
+
     if let Some(e) = err.find::<Error>() {
         let code = match *e {
             Error::BadRequest => warp::http::StatusCode::BAD_REQUEST,
@@ -0,0 +1,137 @@
+use crate::injector;
+use crate::profiles;
+use crate::settings;
+use crate::web::EMPTY;
+use anyhow::{bail, Result};
+use tokio::sync::RwLockReadGuard;
+use warp::body;
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+/// Request to snapshot the current value of settings into a profile.
+#[derive(Debug, serde::Deserialize)]
+struct SnapshotRequest {
+    #[serde(default)]
+    game: Option<String>,
+    keys: Vec<String>,
+}
+
+/// Settings profile endpoints.
+#[derive(Clone)]
+pub struct Profiles(injector::Var<Option<profiles::Profiles>>);
+
+impl Profiles {
+    pub fn route(
+        profiles: injector::Var<Option<profiles::Profiles>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Profiles(profiles);
+
+        let list = warp::get()
+            .and(path::end().and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let put = warp::put()
+            .and(path!(String).and(body::json()).and_then({
+                let api = api.clone();
+                move |name: String, profile: profiles::Profile| {
+                    let api = api.clone();
+                    async move { api.put(name, profile).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let snapshot = warp::post()
+            .and(path!(String / "snapshot").and(body::json()).and_then({
+                let api = api.clone();
+                move |name: String, request: SnapshotRequest| {
+                    let api = api.clone();
+                    async move {
+                        api.snapshot(name, request)
+                            .await
+                            .map_err(super::custom_reject)
+                    }
+                }
+            }))
+            .boxed();
+
+        let activate = warp::post()
+            .and(path!(String / "activate").and_then({
+                let api = api.clone();
+                move |name: String| {
+                    let api = api.clone();
+                    async move { api.activate(name).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let delete = warp::delete()
+            .and(path!(String).and_then({
+                move |name: String| {
+                    let api = api.clone();
+                    async move { api.delete(name).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("profiles")
+            .and(list.or(put).or(snapshot).or(activate).or(delete))
+            .boxed()
+    }
+
+    /// Access underlying profiles abstraction.
+    async fn profiles(&self) -> Result<RwLockReadGuard<'_, profiles::Profiles>> {
+        match RwLockReadGuard::try_map(self.0.read().await, |c| c.as_ref()) {
+            Ok(out) => Ok(out),
+            Err(_) => bail!("profiles not configured"),
+        }
+    }
+
+    /// List all profiles.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let profiles = self.profiles().await?.list().await?;
+        Ok(warp::reply::json(&profiles))
+    }
+
+    /// Store the given profile.
+    async fn put(&self, name: String, profile: profiles::Profile) -> Result<impl warp::Reply> {
+        let profile = profiles::Profile { name, ..profile };
+        self.profiles().await?.put(profile).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Snapshot the current value of settings into a profile.
+    async fn snapshot(&self, name: String, request: SnapshotRequest) -> Result<impl warp::Reply> {
+        self.profiles()
+            .await?
+            .snapshot(&name, request.game, &request.keys)
+            .await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Activate the given profile.
+    async fn activate(&self, name: String) -> Result<impl warp::Reply> {
+        if !self
+            .profiles()
+            .await?
+            .activate(&name, settings::Actor::Web)
+            .await?
+        {
+            bail!("no such profile: {}", name);
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// Delete the given profile.
+    async fn delete(&self, name: String) -> Result<impl warp::Reply> {
+        self.profiles().await?.delete(&name).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
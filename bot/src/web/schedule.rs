@@ -0,0 +1,127 @@
+use crate::injector;
+use crate::schedule;
+use crate::settings::Actor;
+use crate::utils;
+use crate::web::EMPTY;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLockReadGuard;
+use warp::body;
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+/// Request to schedule a change to a setting.
+#[derive(Debug, serde::Deserialize)]
+struct ScheduleRequest {
+    key: String,
+    /// The value to set, or `null` to clear the setting.
+    #[serde(default)]
+    value: Option<serde_json::Value>,
+    /// When to apply the change.
+    #[serde(default)]
+    run_at: Option<DateTime<Utc>>,
+    /// Apply the change after a delay instead.
+    #[serde(default)]
+    delay: Option<utils::Duration>,
+    /// Revert the change after the given duration.
+    #[serde(default)]
+    revert_after: Option<utils::Duration>,
+}
+
+/// Scheduled settings endpoints.
+#[derive(Clone)]
+pub struct Schedule(injector::Var<Option<schedule::Schedule>>);
+
+impl Schedule {
+    pub fn route(
+        schedule: injector::Var<Option<schedule::Schedule>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Schedule(schedule);
+
+        let list = warp::get()
+            .and(path::end().and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let create = warp::post()
+            .and(path::end().and(body::json()).and_then({
+                let api = api.clone();
+                move |request: ScheduleRequest| {
+                    let api = api.clone();
+                    async move { api.create(request).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let cancel = warp::delete()
+            .and(path!(i32).and_then({
+                move |id: i32| {
+                    let api = api.clone();
+                    async move { api.cancel(id).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("schedule")
+            .and(list.or(create).or(cancel))
+            .boxed()
+    }
+
+    /// Access underlying schedule abstraction.
+    async fn schedule(&self) -> Result<RwLockReadGuard<'_, schedule::Schedule>> {
+        match RwLockReadGuard::try_map(self.0.read().await, |c| c.as_ref()) {
+            Ok(out) => Ok(out),
+            Err(_) => bail!("schedule not configured"),
+        }
+    }
+
+    /// List all scheduled changes.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let scheduled = self.schedule().await?.list().await?;
+        Ok(warp::reply::json(&scheduled))
+    }
+
+    /// Schedule a change.
+    async fn create(&self, request: ScheduleRequest) -> Result<impl warp::Reply> {
+        let run_at = match (request.run_at, request.delay) {
+            (Some(_), Some(_)) => bail!("only one of `run_at` and `delay` can be specified"),
+            (Some(run_at), None) => run_at,
+            (None, Some(delay)) => Utc::now() + delay.as_chrono(),
+            (None, None) => Utc::now(),
+        };
+
+        let id = self
+            .schedule()
+            .await?
+            .schedule(
+                &request.key,
+                request.value,
+                run_at,
+                request.revert_after,
+                Actor::Web,
+            )
+            .await?;
+
+        Ok(warp::reply::json(&ScheduleResponse { id }))
+    }
+
+    /// Cancel the given scheduled change.
+    async fn cancel(&self, id: i32) -> Result<impl warp::Reply> {
+        if !self.schedule().await?.cancel(id).await? {
+            bail!("no scheduled change with id: {}", id);
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ScheduleResponse {
+    id: i32,
+}
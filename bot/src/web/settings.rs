@@ -1,10 +1,16 @@
 use crate::injector;
+use crate::settings::Actor;
 use crate::web::{Fragment, EMPTY};
 use anyhow::{bail, Result};
 use std::collections::HashSet;
 use tokio::sync::RwLockReadGuard;
 use warp::{body, filters, path, Filter as _};
 
+/// Default number of changes to return from the settings history.
+const DEFAULT_HISTORY_LIMIT: i64 = 100;
+/// Maximum number of changes to return from the settings history.
+const MAX_HISTORY_LIMIT: i64 = 1000;
+
 #[derive(serde::Deserialize)]
 pub struct PutSetting {
     value: serde_json::Value,
@@ -20,6 +26,14 @@ struct SettingsQuery {
     feature: Option<bool>,
 }
 
+#[derive(serde::Deserialize)]
+struct HistoryQuery {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    limit: Option<i64>,
+}
+
 /// Settings endpoint.
 #[derive(Clone)]
 pub struct Settings(injector::Var<Option<crate::settings::Settings>>);
@@ -30,6 +44,29 @@ impl Settings {
     ) -> filters::BoxedFilter<(impl warp::Reply,)> {
         let api = Settings(settings);
 
+        let history = warp::get()
+            .and(path!("settings" / "history").and(path::end()))
+            .and(warp::query::<HistoryQuery>())
+            .and_then({
+                let api = api.clone();
+                move |query: HistoryQuery| {
+                    let api = api.clone();
+                    async move { api.history(query).await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
+        let revert = warp::post()
+            .and(path!("settings" / "history" / i32 / "revert").and(path::end()))
+            .and_then({
+                let api = api.clone();
+                move |id: i32| {
+                    let api = api.clone();
+                    async move { api.revert(id).await.map_err(super::custom_reject) }
+                }
+            })
+            .boxed();
+
         let list = warp::get()
             .and(warp::path("settings").and(warp::query::<SettingsQuery>()))
             .and_then({
@@ -97,7 +134,13 @@ impl Settings {
             )
             .boxed();
 
-        list.or(get).or(delete).or(edit).boxed()
+        history
+            .or(revert)
+            .or(list)
+            .or(get)
+            .or(delete)
+            .or(edit)
+            .boxed()
     }
 
     /// Access underlying settings abstraction.
@@ -159,7 +202,7 @@ impl Settings {
     /// Delete the given setting by key.
     async fn delete_setting(&self, key: &str) -> Result<impl warp::Reply> {
         let settings = self.settings().await?;
-        settings.clear(key).await?;
+        settings.clear_by(key, Actor::Web).await?;
         Ok(warp::reply::json(&EMPTY))
     }
 
@@ -176,7 +219,31 @@ impl Settings {
     /// Delete the given setting by key.
     async fn edit_setting(&self, key: &str, value: serde_json::Value) -> Result<impl warp::Reply> {
         let settings = self.settings().await?;
-        settings.set_json(key, value).await?;
+        settings.set_json_by(key, value, Actor::Web).await?;
+        Ok(warp::reply::json(&EMPTY))
+    }
+
+    /// List recorded changes to settings, newest first.
+    async fn history(&self, query: HistoryQuery) -> Result<impl warp::Reply> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .max(1)
+            .min(MAX_HISTORY_LIMIT);
+
+        let settings = self.settings().await?;
+        let changes = settings.history(query.key.as_deref(), limit).await?;
+        Ok(warp::reply::json(&changes))
+    }
+
+    /// Revert the recorded change with the given id.
+    async fn revert(&self, id: i32) -> Result<impl warp::Reply> {
+        let settings = self.settings().await?;
+
+        if settings.revert(id, Actor::Web).await?.is_none() {
+            bail!("no change with id: {}", id);
+        }
+
         Ok(warp::reply::json(&EMPTY))
     }
 }
@@ -0,0 +1,100 @@
+use crate::auth;
+use crate::db;
+use crate::injector;
+use crate::web::{Fragment, EMPTY};
+use anyhow::{bail, Result};
+use tokio::sync::RwLockReadGuard;
+use warp::body;
+use warp::filters;
+use warp::path;
+use warp::Filter as _;
+
+/// Request to create a new token.
+#[derive(Debug, serde::Deserialize)]
+struct CreateRequest {
+    name: String,
+    scopes: Vec<auth::Scope>,
+}
+
+/// Response to creating a token, the only time the token itself is available.
+#[derive(serde::Serialize)]
+struct CreateResponse {
+    token: String,
+}
+
+/// API token endpoints.
+#[derive(Clone)]
+pub struct Tokens(injector::Var<Option<db::ApiTokens>>);
+
+impl Tokens {
+    pub fn route(
+        tokens: injector::Var<Option<db::ApiTokens>>,
+    ) -> filters::BoxedFilter<(impl warp::Reply,)> {
+        let api = Tokens(tokens);
+
+        let list = warp::get()
+            .and(path::end().and_then({
+                let api = api.clone();
+                move || {
+                    let api = api.clone();
+                    async move { api.list().await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let create = warp::post()
+            .and(path::end().and(body::json()).and_then({
+                let api = api.clone();
+                move |request: CreateRequest| {
+                    let api = api.clone();
+                    async move { api.create(request).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        let delete = warp::delete()
+            .and(path!(Fragment).and_then({
+                move |name: Fragment| {
+                    let api = api.clone();
+                    async move { api.delete(name).await.map_err(super::custom_reject) }
+                }
+            }))
+            .boxed();
+
+        warp::path("tokens").and(list.or(create).or(delete)).boxed()
+    }
+
+    /// Access underlying tokens abstraction.
+    async fn tokens(&self) -> Result<RwLockReadGuard<'_, db::ApiTokens>> {
+        match RwLockReadGuard::try_map(self.0.read().await, |c| c.as_ref()) {
+            Ok(out) => Ok(out),
+            Err(_) => bail!("tokens not configured"),
+        }
+    }
+
+    /// List all tokens.
+    async fn list(&self) -> Result<impl warp::Reply> {
+        let tokens = self.tokens().await?.list().await?;
+        Ok(warp::reply::json(&tokens))
+    }
+
+    /// Create a new token.
+    async fn create(&self, request: CreateRequest) -> Result<impl warp::Reply> {
+        let token = self
+            .tokens()
+            .await?
+            .create(&request.name, request.scopes)
+            .await?;
+
+        Ok(warp::reply::json(&CreateResponse { token }))
+    }
+
+    /// Delete the given token.
+    async fn delete(&self, name: Fragment) -> Result<impl warp::Reply> {
+        if !self.tokens().await?.delete(name.as_str()).await? {
+            bail!("no such token: {}", name.as_str());
+        }
+
+        Ok(warp::reply::json(&EMPTY))
+    }
+}